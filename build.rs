@@ -0,0 +1,34 @@
+use std::{env, path::Path, process::Command};
+
+// Generates the C header of the API in `src/capi.rs` into OUT_DIR, and records
+// the git commit for the metadata of benchmark results. The header checked in
+// under `include/` is only rewritten with OPTIMIZERS_WRITE_HEADER=1 set;
+// `tests/capi.rs` fails while it differs from the generated one.
+fn main() {
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=OPTIMIZERS_WRITE_HEADER");
+    if Path::new(".git").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs/heads");
+        // Refs move here on `git pack-refs`/`gc`; a missing path would rerun every build
+        if Path::new(".git/packed-refs").exists() {
+            println!("cargo:rerun-if-changed=.git/packed-refs");
+        }
+        let commit = Command::new("git").args(["rev-parse", "HEAD"]).output();
+        if let Some(output) = commit.ok().filter(|o| o.status.success()) {
+            println!("cargo:rustc-env=OPTIMIZERS_GIT_COMMIT={}", String::from_utf8_lossy(&output.stdout).trim());
+        }
+    }
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).unwrap();
+    let header = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{crate_dir}/src/capi.rs"))
+        .generate()
+        .expect("failed to generate the C header");
+    header.write_to_file(Path::new(&env::var("OUT_DIR").unwrap()).join("optimizers.h"));
+    if env::var_os("OPTIMIZERS_WRITE_HEADER").is_some_and(|v| v == "1") {
+        header.write_to_file(format!("{crate_dir}/include/optimizers.h"));
+    }
+}
@@ -0,0 +1,88 @@
+#ifndef OPTIMIZERS_H
+#define OPTIMIZERS_H
+
+/* Generated by build.rs from src/capi.rs with cbindgen; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Version of this API, raised on incompatible changes.
+#define OPT_API_VERSION 1
+
+// Status returned on success.
+#define OPT_OK 0
+
+// Status returned on failure; see `opt_last_error`.
+#define OPT_ERROR -1
+
+// An optimizer with its parameters, opaque to C.
+typedef struct OptOptimizer OptOptimizer;
+
+// Objective value of the point `x` with `dims` coordinates.
+typedef float (*OptObjective)(const float *x, size_t dims, void *user_data);
+
+typedef struct OptResult {
+  // Best value found.
+  float f_x;
+  // Number of objective calls.
+  uint64_t nfev;
+} OptResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+uint32_t opt_api_version(void);
+
+// Message of the last failure on the calling thread, valid until the next
+// failing call on it. Empty if nothing failed.
+const char *opt_last_error(void);
+
+// Creates an optimizer, or returns null on an unknown name, missing
+// parameters or values the algorithm cannot run with. Free it with
+// `opt_optimizer_free`.
+//
+// # Safety
+//
+// `name` must be a NUL-terminated string. `param_names` and `param_values`
+// must point to `param_count` NUL-terminated strings and floats; they may be
+// null when `param_count` is 0.
+struct OptOptimizer *opt_optimizer_new(const char *name,
+                                       const char *const *param_names,
+                                       const float *param_values,
+                                       size_t param_count);
+
+// # Safety
+//
+// `optimizer` must come from `opt_optimizer_new` and not be freed yet, or be null.
+void opt_optimizer_free(struct OptOptimizer *optimizer);
+
+// Minimizes `objective` within `bounds`, laid out as `dims` pairs of
+// `min, max`, using at most about `maxiter` evaluations and stopping early
+// once a value is at or below `stop_residual` (pass `-INFINITY` to use the
+// whole budget). On success writes the best point to `x` and returns `OPT_OK`.
+//
+// # Safety
+//
+// `optimizer` must be valid, `bounds` must point to `2 * dims` floats, `x`
+// to room for `dims` floats and `result` to an `OptResult`. `user_data` is
+// passed to `objective` untouched.
+int32_t opt_find_infimum(const struct OptOptimizer *optimizer,
+                         OptObjective objective,
+                         void *user_data,
+                         const float *bounds,
+                         size_t dims,
+                         uint64_t maxiter,
+                         uint64_t seed,
+                         float stop_residual,
+                         float *x,
+                         struct OptResult *result);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* OPTIMIZERS_H */
@@ -1,17 +1,20 @@
 use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Distribution, Normal, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{clamp_to_unit_cube, fit_in_bounds, BoundsSimd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    utils::{clamp_to_unit_cube, fit_in_bounds, split_bounds},
 };
 
 /// ANS with sorted population archive (2*popsize best solutions kept sorted).
+#[derive(Debug, Clone)]
 pub struct AnsSorted {
     pub popsize: usize,
     pub sigma: f32,
@@ -26,38 +29,31 @@ pub fn new_ans_sort(params: &BTreeMap<String, f32>) -> AnsSorted {
     }
 }
 
-impl Optimizer for AnsSorted {
-    fn find_infimum<F>(
+impl AnsSorted {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
         let dims = bounds.len();
         let popsize = self.popsize;
         let coll_size = popsize * 2;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min: Vec<f32> = vec![0.0; dims];
-        let mut range_max: Vec<f32> = vec![0.0; dims];
-        for i in 0..dims {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let bounds_simd = BoundsSimd::new(&range_min, &range_max);
-        let mut simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
+        let (range_min, range_max) = split_bounds(bounds);
         let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
 
         // Current population: popsize * dims
         let mut cur = vec![0.0f32; popsize * dims];
         for v in &mut cur {
-            *v = random.sample(&mut rng);
+            *v = random.sample(rng);
         }
         let mut cur_f = vec![f32::INFINITY; popsize];
 
@@ -69,7 +65,6 @@ impl Optimizer for AnsSorted {
 
         let sigma = self.sigma;
         let normal = Normal::new(0.0, sigma).unwrap();
-        let stop_residual = early_stop_callback.stop_residual();
         let mut history = OptimizationHistory {
             x: Vec::new(),
             f_x: Vec::new(),
@@ -89,10 +84,7 @@ impl Optimizer for AnsSorted {
 
         for epoch in 0..max_epoch {
             // Evaluate current population
-            for p in 0..popsize {
-                bounds_simd.transform_into(&cur[p * dims..(p + 1) * dims], &mut simd_buf);
-                cur_f[p] = func(&simd_buf);
-            }
+            evaluator.evaluate(&mut cur, &mut cur_f);
 
             // Revision: place current solutions in the second half of the archive
             for p in 0..popsize {
@@ -106,7 +98,7 @@ impl Optimizer for AnsSorted {
 
             // Sort archive by fitness
             indices.iter_mut().enumerate().for_each(|(i, v)| *v = i);
-            indices.sort_by(|&a, &b| best_f[a].partial_cmp(&best_f[b]).unwrap());
+            indices.sort_by(|&a, &b| best_f[a].total_cmp(&best_f[b]));
             let mut sorted_best = vec![0.0f32; coll_size * dims];
             let mut sorted_best_f = vec![f32::INFINITY; coll_size];
             for (new_i, &old_i) in indices.iter().enumerate() {
@@ -124,7 +116,7 @@ impl Optimizer for AnsSorted {
                 history.f_x.push(best_f[..popsize].to_vec());
             }
             current_epoch = epoch;
-            if best_f[0] <= stop_residual {
+            if best_f[0] <= stop_residual || evaluator.aborted() {
                 break;
             }
 
@@ -132,22 +124,22 @@ impl Optimizer for AnsSorted {
             for p in 0..popsize {
                 let po = p * dims;
                 for d in 0..dims {
-                    if random.sample(&mut rng) <= self_instead_neighbour {
+                    if random.sample(rng) <= self_instead_neighbour {
                         // Use own best (from archive, position p)
                         cur[po + d] = clamp_to_unit_cube(
                             best[po + d]
-                                + normal.sample(&mut rng) * f32::abs(best[po + d] - cur[po + d]),
+                                + normal.sample(rng) * f32::abs(best[po + d] - cur[po + d]),
                         );
                     } else {
                         // Use a random neighbor's best from the top popsize
-                        let mut r = popsize_distr.sample(&mut rng);
+                        let mut r = popsize_distr.sample(rng);
                         while r == p {
-                            r = popsize_distr.sample(&mut rng);
+                            r = popsize_distr.sample(rng);
                         }
                         let ro = r * dims;
                         cur[po + d] = clamp_to_unit_cube(
                             best[ro + d]
-                                + normal.sample(&mut rng) * f32::abs(best[ro + d] - cur[po + d]),
+                                + normal.sample(rng) * f32::abs(best[ro + d] - cur[po + d]),
                         );
                     }
                 }
@@ -162,13 +154,72 @@ impl Optimizer for AnsSorted {
     }
 }
 
+impl Optimizer for AnsSorted {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for AnsSorted {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for AnsSorted {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         early_stop_callback::EarlyStopCallback,
         functions::{shifted_sphere, SHIFTED_SPHERE_BOUNDS},
-        utils::broadcast_simd,
+        utils::{broadcast_simd, broadcast_simd_batch},
     };
 
     #[test]
@@ -231,4 +282,18 @@ mod tests {
         assert_eq!(r1.f_x, r2.f_x);
         assert_eq!(r1.nfev, r2.nfev);
     }
+
+    #[test]
+    fn test_ans_sort_batch_finds_minimum() {
+        let optimizer = AnsSorted {
+            popsize: 32,
+            sigma: 0.05,
+            self_instead_neighbour: 0.5,
+        };
+        let func = broadcast_simd_batch(shifted_sphere);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let result = optimizer.find_infimum_batch(&func, &bounds, 500_000, 0, false, 0.01);
+        assert!(result.f_x <= 0.01, "AnsSorted batch did not converge: f_x={}", result.f_x);
+        assert!(result.nfev > 0);
+    }
 }
@@ -1,16 +1,20 @@
 use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Distribution, Normal, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{clamp_to_unit_cube, fit_in_bounds, BoundsSimd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    restart::RestartDetector,
+    utils::{clamp_to_unit_cube, fit_in_bounds, split_bounds},
 };
 
+#[derive(Debug, Clone)]
 pub struct ANSR {
     pub popsize: usize,
     pub restart_tolerance: f32,
@@ -27,36 +31,29 @@ pub fn new_ansr(params: &BTreeMap<String, f32>) -> ANSR {
     }
 }
 
-impl Optimizer for ANSR {
-    fn find_infimum<F>(
+impl ANSR {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
         let dims = bounds.len();
         let popsize = self.popsize;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min: Vec<f32> = vec![0.0; dims];
-        let mut range_max: Vec<f32> = vec![0.0; dims];
-        for i in 0..dims {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let bounds_simd = BoundsSimd::new(&range_min, &range_max);
-        let mut simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
+        let (range_min, range_max) = split_bounds(bounds);
         let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
 
         // Flat storage: popsize * dims
         let mut cur = vec![0.0f32; popsize * dims];
-        for v in &mut cur { *v = random.sample(&mut rng); }
+        for v in &mut cur { *v = random.sample(rng); }
         let mut best = vec![0.0f32; popsize * dims];
         let mut best_f = vec![f32::INFINITY; popsize];
         let mut cur_f = vec![f32::INFINITY; popsize];
@@ -65,7 +62,6 @@ impl Optimizer for ANSR {
         let sigma = self.sigma;
         let normal = Normal::new(0.0, sigma).unwrap();
         let mut ind = 0;
-        let stop_residual = early_stop_callback.stop_residual();
         let mut history = OptimizationHistory { x: Vec::new(), f_x: Vec::new() };
         if use_history {
             history.x.push((0..popsize).map(|p| cur[p*dims..(p+1)*dims].to_vec()).collect());
@@ -74,12 +70,10 @@ impl Optimizer for ANSR {
         let mut current_epoch = 0;
         let popsize_distr = Uniform::new(0, popsize).unwrap();
         let self_instead_neighbour = self.self_instead_neighbour;
+        let mut restart_detector = RestartDetector::new(popsize);
 
         for epoch in 0..max_epoch {
-            for p in 0..popsize {
-                bounds_simd.transform_into(&cur[p*dims..(p+1)*dims], &mut simd_buf);
-                cur_f[p] = func(&simd_buf);
-            }
+            evaluator.evaluate(&mut cur, &mut cur_f);
             for p in 0..popsize {
                 if cur_f[p] < best_f[p] {
                     best_f[p] = cur_f[p];
@@ -94,55 +88,36 @@ impl Optimizer for ANSR {
                 history.f_x.push(best_f.clone());
             }
             current_epoch = epoch;
-            if best_f[ind] <= stop_residual {
+            if best_f[ind] <= stop_residual || evaluator.aborted() {
                 break;
             }
-            for lhs in 0..popsize {
-                if best_f[lhs] == f32::INFINITY {
-                    continue;
-                }
-                for rhs in (lhs + 1)..popsize {
-                    if best_f[rhs] == f32::INFINITY {
-                        continue;
-                    }
-                    let (min_residual, max_residual) = if best_f[lhs] <= best_f[rhs] {
-                        (best_f[lhs], best_f[rhs])
-                    } else {
-                        (best_f[rhs], best_f[lhs])
-                    };
-                    if max_residual != 0.0
-                        && (max_residual - min_residual) / max_residual < restart_tolerance
-                    {
-                        let loser = if lhs == ind || (rhs != ind && best_f[lhs] < best_f[rhs]) {
-                            rhs
-                        } else {
-                            lhs
-                        };
-                        best_f[loser] = f32::INFINITY;
-                        let lo = loser * dims;
-                        for d in 0..dims {
-                            best[lo + d] = random.sample(&mut rng);
-                            cur[lo + d] = random.sample(&mut rng);
-                        }
-                    }
+            let restarts = restart_detector.detect(&best_f, ind, restart_tolerance, |min, max| {
+                max != 0.0 && (max - min) / max < restart_tolerance
+            });
+            for restart in restarts {
+                best_f[restart.loser] = f32::INFINITY;
+                let lo = restart.loser * dims;
+                for d in 0..dims {
+                    best[lo + d] = random.sample(rng);
+                    cur[lo + d] = random.sample(rng);
                 }
             }
             for p in 0..popsize {
                 let po = p * dims;
                 for d in 0..dims {
-                    if random.sample(&mut rng) <= self_instead_neighbour {
+                    if random.sample(rng) <= self_instead_neighbour {
                         cur[po + d] = clamp_to_unit_cube(
                             best[po + d]
-                                + normal.sample(&mut rng)
+                                + normal.sample(rng)
                                     * f32::abs(best[po + d] - cur[po + d]),
                         )
                     } else {
-                        let mut r = popsize_distr.sample(&mut rng);
-                        while r == p { r = popsize_distr.sample(&mut rng); }
+                        let mut r = popsize_distr.sample(rng);
+                        while r == p { r = popsize_distr.sample(rng); }
                         let ro = r * dims;
                         cur[po + d] = clamp_to_unit_cube(
                             best[ro + d]
-                                + normal.sample(&mut rng)
+                                + normal.sample(rng)
                                     * f32::abs(best[ro + d] - cur[po + d]),
                         )
                     }
@@ -158,13 +133,73 @@ impl Optimizer for ANSR {
     }
 }
 
+impl Optimizer for ANSR {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for ANSR {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for ANSR {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         early_stop_callback::EarlyStopCallback,
         functions::{shifted_sphere, SHIFTED_SPHERE_BOUNDS},
-        utils::broadcast_simd,
+        optimizer::{FailurePolicy, FallibleOptimizer, NoisyOptimizer, ObjectiveError},
+        utils::{broadcast_simd, broadcast_simd_batch},
     };
 
     #[test]
@@ -230,4 +265,168 @@ mod tests {
         assert_eq!(r1.f_x, r2.f_x);
         assert_eq!(r1.nfev, r2.nfev);
     }
+
+    #[test]
+    fn test_ansr_batch_finds_minimum() {
+        let optimizer = ANSR {
+            popsize: 4,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let func = broadcast_simd_batch(shifted_sphere);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let result = optimizer.find_infimum_batch(&func, &bounds, 100_000, 0, false, 0.01);
+        assert!(result.f_x <= 0.01, "ANSR batch did not converge: f_x={}", result.f_x);
+        assert!(result.nfev > 0);
+    }
+
+    #[test]
+    fn test_ansr_with_rng() {
+        use crate::rng::{stream_rng, DefaultRng};
+        use rand::rngs::StdRng;
+
+        let optimizer = ANSR {
+            popsize: 8,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let func = broadcast_simd(shifted_sphere);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let early_stop = EarlyStopCallback::new(&func, 0.01);
+        let seeded = optimizer.find_infimum(&func, &bounds, 2_000, 5, false, &early_stop);
+        let mut rng: DefaultRng = stream_rng(5, 0);
+        let explicit =
+            optimizer.find_infimum_with_rng(&func, &bounds, 2_000, &mut rng, false, &early_stop);
+        assert_eq!(seeded.x, explicit.x);
+        assert_eq!(seeded.f_x, explicit.f_x);
+
+        let mut a: StdRng = stream_rng(5, 1);
+        let mut b: StdRng = stream_rng(5, 1);
+        let r1 = optimizer.find_infimum_with_rng(&func, &bounds, 2_000, &mut a, false, &early_stop);
+        let r2 = optimizer.find_infimum_with_rng(&func, &bounds, 2_000, &mut b, false, &early_stop);
+        assert_eq!(r1.x, r2.x);
+        assert_ne!(r1.x, seeded.x);
+    }
+
+    #[test]
+    fn test_ansr_fallible_objective() {
+        let optimizer = ANSR {
+            popsize: 8,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let sphere = broadcast_simd(shifted_sphere);
+        // Crashes on half of the domain; the optimum at -pi stays feasible
+        let func = |x: &[Vec8]| if x[0].0[0] > 0.0 { Err("crashed") } else { Ok(sphere(x)) };
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+
+        let result = optimizer.find_infimum_fallible(
+            &func,
+            &bounds,
+            100_000,
+            0,
+            false,
+            0.01,
+            FailurePolicy::Resample { attempts: 10 },
+        );
+        assert!(result.error.is_none());
+        assert!(result.failures > 0);
+        assert!(result.result.f_x <= 0.01, "f_x={}", result.result.f_x);
+
+        let aborted =
+            optimizer.find_infimum_fallible(&func, &bounds, 100_000, 0, false, 0.01, FailurePolicy::Abort);
+        assert_eq!(aborted.error, Some(ObjectiveError::Failed("crashed")));
+        assert_eq!(aborted.failures, 1);
+        assert!(aborted.result.nfev <= 16);
+    }
+
+    #[test]
+    fn test_ansr_noisy_objective() {
+        use crate::{
+            noise::Noise,
+            optimizer::{NoiseHandling, Resampling},
+            utils::fit_in_bounds_simd,
+        };
+
+        let optimizer = ANSR {
+            popsize: 16,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let exact = broadcast_simd(shifted_sphere);
+        let func = Noise::Gaussian { sigma: 0.01 }.wrap(broadcast_simd(shifted_sphere), 0);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let handling = NoiseHandling {
+            resampling: Resampling::AdaptiveElite {
+                elite_fraction: 0.25,
+                max_samples: 8,
+            },
+            final_samples: 32,
+        };
+        let result =
+            optimizer.find_infimum_noisy(&func, &bounds, 50_000, 0, false, f32::NEG_INFINITY, handling);
+        assert!(result.result.nfev <= 50_000);
+        // The search estimate is biased low; the final mean is not
+        assert!(result.result.f_x < result.mean_f_x + 3.0 * result.std_error);
+        let x = fit_in_bounds_simd(&result.result.x, &[0.0; 16], &[1.0; 16]);
+        assert!((result.mean_f_x - exact(&x)).abs() < 0.01);
+        assert!(result.mean_f_x < 0.05, "mean_f_x={}", result.mean_f_x);
+    }
+
+    #[test]
+    fn test_ansr_fallible_with_rng() {
+        use rand::rngs::StdRng;
+
+        use crate::rng::stream_rng;
+
+        let optimizer = ANSR {
+            popsize: 8,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let sphere = broadcast_simd(shifted_sphere);
+        let func = |x: &[Vec8]| if x[0].0[0] > 0.0 { Err("crashed") } else { Ok(sphere(x)) };
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let policy = FailurePolicy::Resample { attempts: 10 };
+        let run = |rng: &mut StdRng| {
+            optimizer.find_infimum_fallible_with_rng(&func, &bounds, 2_000, rng, false, 0.01, policy)
+        };
+        let (r1, r2) = (run(&mut stream_rng(5, 1)), run(&mut stream_rng(5, 1)));
+        assert!(r1.failures > 0);
+        assert_eq!((r1.result.x, r1.failures), (r2.result.x, r2.failures));
+    }
+
+    #[test]
+    fn test_ansr_noisy_with_rng() {
+        use crate::{
+            noise::Noise,
+            optimizer::{NoiseHandling, Resampling},
+            rng::{stream_rng, DefaultRng},
+        };
+
+        let optimizer = ANSR {
+            popsize: 8,
+            restart_tolerance: 0.01,
+            sigma: 0.05,
+            self_instead_neighbour: 0.9,
+        };
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        // The noise is drawn in call order, so each run gets a fresh stream
+        let noisy = || Noise::Gaussian { sigma: 0.01 }.wrap(broadcast_simd(shifted_sphere), 0);
+        let handling = NoiseHandling {
+            resampling: Resampling::Fixed { samples: 2 },
+            final_samples: 4,
+        };
+        let seeded = optimizer.find_infimum_noisy(&noisy(), &bounds, 2_000, 5, false, 0.01, handling);
+        let mut rng: DefaultRng = stream_rng(5, 0);
+        let explicit =
+            optimizer.find_infimum_noisy_with_rng(&noisy(), &bounds, 2_000, &mut rng, false, 0.01, handling);
+        assert_eq!(seeded.result.x, explicit.result.x);
+        assert_eq!(seeded.mean_f_x, explicit.mean_f_x);
+    }
 }
@@ -1,14 +1,17 @@
-use std::{collections::BTreeMap, mem::swap};
+use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Distribution, Normal, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{fit_in_bounds, BoundsSimd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    restart::RestartDetector,
+    utils::{fit_in_bounds, split_bounds},
 };
 
 fn wrap_to_unit_cube(mut v: f32) -> f32 {
@@ -67,6 +70,7 @@ fn wrap_to_unit_cube(mut v: f32) -> f32 {
 ///   restart_tolerance × (1 − t)^restart_decay_power. Higher values cause
 ///   the tolerance to shrink faster, reducing restarts in later epochs.
 ///
+#[derive(Debug, Clone)]
 pub struct AnsrDpnm {
     pub popsize: usize,
     pub restart_tolerance: f32,
@@ -87,39 +91,32 @@ pub fn new_ansr_dpnm(params: &BTreeMap<String, f32>) -> AnsrDpnm {
     }
 }
 
-impl Optimizer for AnsrDpnm {
-    fn find_infimum<F>(
+impl AnsrDpnm {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
         let params = bounds.len();
         let popsize = self.popsize;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min: Vec<f32> = vec![0.0; params];
-        let mut range_max: Vec<f32> = vec![0.0; params];
-        for i in 0..params {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let bounds_simd = BoundsSimd::new(&range_min, &range_max);
-        let mut simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
-        let mut current_positions: Vec<Vec<f32>> = vec![vec![0.0; params]; popsize];
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
+        let (range_min, range_max) = split_bounds(bounds);
         let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
-        for p in 0..popsize {
-            for d in 0..params {
-                current_positions[p][d] = random.sample(&mut rng);
-            }
+
+        // Flat storage: popsize * params
+        let mut current_positions = vec![0.0f32; popsize * params];
+        for v in &mut current_positions {
+            *v = random.sample(rng);
         }
-        let mut best_positions: Vec<Vec<f32>> = vec![vec![0.0; params]; popsize];
+        let mut best_positions = vec![0.0f32; popsize * params];
         let mut best_residuals: Vec<f32> = vec![f32::INFINITY; popsize];
         let restart_tolerance = self.restart_tolerance;
         let sigma = self.sigma;
@@ -132,102 +129,151 @@ impl Optimizer for AnsrDpnm {
             f_x: Vec::new(),
         };
         if use_history {
-            history.x.push(current_positions.clone());
+            history.x.push(
+                (0..popsize)
+                    .map(|p| current_positions[p * params..(p + 1) * params].to_vec())
+                    .collect(),
+            );
             history.f_x.push(current_residuals.clone());
         }
         let mut current_epoch = 0;
         let popsize_distr = Uniform::new(0, popsize).unwrap();
         let self_instead_neighbour = self.self_instead_neighbour;
+        let mut restart_detector = RestartDetector::new(popsize);
         for epoch in 0..max_epoch {
-            for p in 0..popsize {
-                bounds_simd.transform_into(&current_positions[p], &mut simd_buf);
-                current_residuals[p] = func(&simd_buf);
-            }
+            evaluator.evaluate(&mut current_positions, &mut current_residuals);
             for p in 0..popsize {
                 if current_residuals[p] < best_residuals[p] {
                     best_residuals[p] = current_residuals[p];
-                    best_positions[p] = current_positions[p].clone();
+                    best_positions[p * params..(p + 1) * params]
+                        .copy_from_slice(&current_positions[p * params..(p + 1) * params]);
                     if best_residuals[p] < best_residuals[ind] {
                         ind = p;
                     }
                 }
             }
             if use_history {
-                history.x.push(best_positions.clone());
+                history.x.push(
+                    (0..popsize)
+                        .map(|p| best_positions[p * params..(p + 1) * params].to_vec())
+                        .collect(),
+                );
                 history.f_x.push(best_residuals.clone());
             }
             current_epoch = epoch;
-            bounds_simd.transform_into(&best_positions[ind], &mut simd_buf);
-            if early_stop_callback.should_stop(&simd_buf) {
+            if best_residuals[ind] <= stop_residual || evaluator.aborted() {
                 break;
             }
             let t = epoch as f32 / max_epoch as f32;
             let effective_rt = restart_tolerance * (1.0 - t).powf(restart_decay_power);
-            for lhs in 0..popsize {
-                for rhs in (lhs + 1)..popsize {
-                    let mut min_residual = best_residuals[lhs];
-                    let mut max_residual = best_residuals[rhs];
-                    if min_residual > max_residual {
-                        swap(&mut min_residual, &mut max_residual);
-                    }
-                    if min_residual != f32::INFINITY
-                        && max_residual != f32::INFINITY
-                        && max_residual != 0.0
-                        && f32::abs((max_residual - min_residual) / max_residual)
-                            < effective_rt
-                    {
-                        let worse = if lhs != ind && rhs != ind {
-                            if best_residuals[lhs] < best_residuals[rhs] {
-                                rhs
-                            } else {
-                                lhs
-                            }
-                        } else if lhs != ind {
-                            lhs
-                        } else {
-                            rhs
-                        };
-                        let better = if worse == lhs { rhs } else { lhs };
-                        best_residuals[worse] = f32::INFINITY;
-                        for d in 0..params {
-                            best_positions[worse][d] = 1.0 - best_positions[better][d];
-                            current_positions[worse][d] = 1.0 - best_positions[better][d];
-                        }
-                    }
+            let restarts = restart_detector.detect(&best_residuals, ind, effective_rt, |min, max| {
+                max != 0.0 && f32::abs((max - min) / max) < effective_rt
+            });
+            for restart in restarts {
+                best_residuals[restart.loser] = f32::INFINITY;
+                let wo = restart.loser * params;
+                let bo = restart.winner * params;
+                for d in 0..params {
+                    best_positions[wo + d] = 1.0 - best_positions[bo + d];
+                    current_positions[wo + d] = 1.0 - best_positions[bo + d];
                 }
             }
             let cosine_t = 0.5 * (1.0 + f32::cos(std::f32::consts::PI * t));
             let effective_sigma = sigma * cosine_t;
             let neighbour_scale = effective_sigma * (1.0 + self.neighbour_multiplier * 2.0 * cosine_t);
             for p in 0..popsize {
-                let mut r = popsize_distr.sample(&mut rng);
+                let mut r = popsize_distr.sample(rng);
                 while r == p {
-                    r = popsize_distr.sample(&mut rng);
+                    r = popsize_distr.sample(rng);
                 }
+                let po = p * params;
+                let ro = r * params;
                 for d in 0..params {
-                    if random.sample(&mut rng) <= self_instead_neighbour {
-                        let dist = f32::abs(best_positions[p][d] - current_positions[p][d]);
-                        current_positions[p][d] = wrap_to_unit_cube(
-                            best_positions[p][d]
-                                + normal.sample(&mut rng) * effective_sigma * dist,
+                    if random.sample(rng) <= self_instead_neighbour {
+                        let dist = f32::abs(best_positions[po + d] - current_positions[po + d]);
+                        current_positions[po + d] = wrap_to_unit_cube(
+                            best_positions[po + d]
+                                + normal.sample(rng) * effective_sigma * dist,
                         )
                     } else {
-                        current_positions[p][d] = wrap_to_unit_cube(
-                            best_positions[r][d]
-                                + normal.sample(&mut rng) * neighbour_scale
-                                    * f32::abs(best_positions[r][d] - current_positions[p][d]),
+                        current_positions[po + d] = wrap_to_unit_cube(
+                            best_positions[ro + d]
+                                + normal.sample(rng) * neighbour_scale
+                                    * f32::abs(best_positions[ro + d] - current_positions[po + d]),
                         )
                     }
                 }
             }
         }
-        let result = OptimizerResult {
-            x: fit_in_bounds(&best_positions[ind], &range_min, &range_max),
+        OptimizerResult {
+            x: fit_in_bounds(
+                &best_positions[ind * params..(ind + 1) * params],
+                &range_min,
+                &range_max,
+            ),
             f_x: best_residuals[ind],
             nfev: (current_epoch + 1) * popsize as u64,
             history: if use_history { Some(history) } else { None },
-        };
-        return result;
+        }
+    }
+}
+
+impl Optimizer for AnsrDpnm {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for AnsrDpnm {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for AnsrDpnm {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
     }
 }
 
@@ -235,7 +281,7 @@ impl Optimizer for AnsrDpnm {
 mod tests {
     use super::*;
     use crate::early_stop_callback::EarlyStopCallback;
-    use crate::utils::broadcast_simd;
+    use crate::utils::{broadcast_simd, broadcast_simd_batch};
 
     fn sphere(x: Vec8, y: Vec8) -> Vec8 {
         x * x + y * y
@@ -428,4 +474,14 @@ mod tests {
         assert!(wrap_to_unit_cube(-0.3) >= 0.0);
         assert!(wrap_to_unit_cube(-0.3) <= 1.0);
     }
+
+    #[test]
+    fn test_batch_small_dimension_converges() {
+        let ansr = AnsrDpnm { popsize: 12, ..default_ansr_dpnm() };
+        let bounds = vec![[-5.0f32, 5.0]; 4];
+        let func = broadcast_simd_batch(sphere);
+        let result = ansr.find_infimum_batch(&func, &bounds, 100_000, 42, false, 0.01);
+        assert!(result.f_x <= 0.01, "f_x={} > 0.01", result.f_x);
+        assert_eq!(result.x.len(), 4);
+    }
 }
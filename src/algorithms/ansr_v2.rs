@@ -1,18 +1,21 @@
-use std::{collections::BTreeMap, mem::swap};
+use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Distribution, Normal, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{fit_in_bounds, fit_in_bounds_simd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    restart::RestartDetector,
+    utils::{fit_in_bounds, split_bounds},
 };
 
 fn wrap_to_unit_cube(mut v: f32) -> f32 {
-    v = v % 1.0;
+    v %= 1.0;
     if v < 0.0 { v += 1.0; }
     v
 }
@@ -51,6 +54,7 @@ fn wrap_to_unit_cube(mut v: f32) -> f32 {
 ///   - σ = 0.01: fine local search (~1% of distance)
 ///   - σ = 1.0: aggressive exploration (~100% of distance, ~32% of samples
 ///     exceed ±1 and get clamped to boundaries)
+///
 ///   Must be > 0: at σ = 0 the Normal constructor panics.
 ///   σ > 1 wastes evaluations — most perturbations are clamped to 0 or 1,
 ///   biasing toward boundaries with no benefit.
@@ -87,149 +91,168 @@ pub fn new_ansr_v2(params: &BTreeMap<String, f32>) -> AnsrV2 {
     }
 }
 
-impl Optimizer for AnsrV2 {
-    fn find_infimum<F>(
+impl AnsrV2 {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
-        let params = bounds.len();
+        let dims = bounds.len();
         let popsize = self.popsize;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min: Vec<f32> = vec![0.0; params];
-        let mut range_max: Vec<f32> = vec![0.0; params];
-        for i in 0..params {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let mut current_positions: Vec<Vec<f32>> = vec![vec![0.0; params]; popsize];
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
+        let (range_min, range_max) = split_bounds(bounds);
         let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
-        for p in 0..popsize {
-            for d in 0..params {
-                current_positions[p][d] = random.sample(&mut rng);
-            }
-        }
-        let mut best_positions: Vec<Vec<f32>> = vec![vec![0.0; params]; popsize];
-        let mut best_residuals: Vec<f32> = vec![f32::INFINITY; popsize];
+
+        // Flat storage: popsize * dims
+        let mut cur = vec![0.0f32; popsize * dims];
+        for v in &mut cur { *v = random.sample(rng); }
+        let mut best = vec![0.0f32; popsize * dims];
+        let mut best_f = vec![f32::INFINITY; popsize];
+        let mut cur_f = vec![f32::INFINITY; popsize];
+
         let restart_tolerance = self.restart_tolerance;
         let sigma = self.sigma;
         let restart_decay_power = self.restart_decay_power;
         let normal = Normal::new(0.0, 1.0).unwrap();
-        let mut current_residuals: Vec<f32> = vec![f32::INFINITY; popsize];
         let mut ind = 0;
-        let mut history = OptimizationHistory {
-            x: Vec::new(),
-            f_x: Vec::new(),
-        };
+        let mut history = OptimizationHistory { x: Vec::new(), f_x: Vec::new() };
         if use_history {
-            history.x.push(current_positions.clone());
-            history.f_x.push(current_residuals.clone());
+            history.x.push((0..popsize).map(|p| cur[p*dims..(p+1)*dims].to_vec()).collect());
+            history.f_x.push(cur_f.clone());
         }
         let mut current_epoch = 0;
         let popsize_distr = Uniform::new(0, popsize).unwrap();
         let self_instead_neighbour = self.self_instead_neighbour;
+        let mut restart_detector = RestartDetector::new(popsize);
         for epoch in 0..max_epoch {
+            evaluator.evaluate(&mut cur, &mut cur_f);
             for p in 0..popsize {
-                current_residuals[p] = func(&fit_in_bounds_simd(
-                    &current_positions[p],
-                    &range_min,
-                    &range_max,
-                ))
-            }
-            for p in 0..popsize {
-                if current_residuals[p] < best_residuals[p] {
-                    best_residuals[p] = current_residuals[p];
-                    best_positions[p] = current_positions[p].clone();
-                    if best_residuals[p] < best_residuals[ind] {
+                if cur_f[p] < best_f[p] {
+                    best_f[p] = cur_f[p];
+                    best[p*dims..(p+1)*dims].copy_from_slice(&cur[p*dims..(p+1)*dims]);
+                    if best_f[p] < best_f[ind] {
                         ind = p;
                     }
                 }
             }
             if use_history {
-                history.x.push(best_positions.clone());
-                history.f_x.push(best_residuals.clone());
+                history.x.push((0..popsize).map(|p| best[p*dims..(p+1)*dims].to_vec()).collect());
+                history.f_x.push(best_f.clone());
             }
             current_epoch = epoch;
-            if early_stop_callback.should_stop(&fit_in_bounds_simd(
-                &best_positions[ind],
-                &range_min,
-                &range_max,
-            )) {
+            if best_f[ind] <= stop_residual || evaluator.aborted() {
                 break;
             }
             let t = epoch as f32 / max_epoch as f32;
             let effective_rt = restart_tolerance * (1.0 - t).powf(restart_decay_power);
-            for lhs in 0..popsize {
-                for rhs in (lhs + 1)..popsize {
-                    let mut min_residual = best_residuals[lhs];
-                    let mut max_residual = best_residuals[rhs];
-                    if min_residual > max_residual {
-                        swap(&mut min_residual, &mut max_residual);
-                    }
-                    if min_residual != f32::INFINITY
-                        && max_residual != f32::INFINITY
-                        && max_residual != 0.0
-                        && f32::abs((max_residual - min_residual) / max_residual)
-                            < effective_rt
-                    {
-                        let worse = if lhs != ind && rhs != ind {
-                            if best_residuals[lhs] < best_residuals[rhs] {
-                                rhs
-                            } else {
-                                lhs
-                            }
-                        } else if lhs != ind {
-                            lhs
-                        } else {
-                            rhs
-                        };
-                        let better = if worse == lhs { rhs } else { lhs };
-                        best_residuals[worse] = f32::INFINITY;
-                        for d in 0..params {
-                            best_positions[worse][d] = 1.0 - best_positions[better][d];
-                            current_positions[worse][d] = 1.0 - best_positions[better][d];
-                        }
-                    }
+            let restarts = restart_detector.detect(&best_f, ind, effective_rt, |min, max| {
+                max != 0.0 && f32::abs((max - min) / max) < effective_rt
+            });
+            for restart in restarts {
+                best_f[restart.loser] = f32::INFINITY;
+                let (lo, wo) = (restart.loser * dims, restart.winner * dims);
+                for d in 0..dims {
+                    best[lo + d] = 1.0 - best[wo + d];
+                    cur[lo + d] = 1.0 - best[wo + d];
                 }
             }
             let effective_sigma = sigma * 0.5 * (1.0 + f32::cos(std::f32::consts::PI * t));
             for p in 0..popsize {
-                let mut r = popsize_distr.sample(&mut rng);
+                let mut r = popsize_distr.sample(rng);
                 while r == p {
-                    r = popsize_distr.sample(&mut rng);
+                    r = popsize_distr.sample(rng);
                 }
-                for d in 0..params {
-                    if random.sample(&mut rng) <= self_instead_neighbour {
-                        let dist = f32::abs(best_positions[p][d] - current_positions[p][d]);
-                        current_positions[p][d] = wrap_to_unit_cube(
-                            best_positions[p][d]
-                                + normal.sample(&mut rng) * effective_sigma * dist,
+                let (po, ro) = (p * dims, r * dims);
+                for d in 0..dims {
+                    if random.sample(rng) <= self_instead_neighbour {
+                        let dist = f32::abs(best[po + d] - cur[po + d]);
+                        cur[po + d] = wrap_to_unit_cube(
+                            best[po + d]
+                                + normal.sample(rng) * effective_sigma * dist,
                         )
                     } else {
-                        current_positions[p][d] = wrap_to_unit_cube(
-                            best_positions[r][d]
-                                + normal.sample(&mut rng) * effective_sigma * (1.0 + self.neighbour_multiplier * (1.0 + f32::cos(std::f32::consts::PI * t)))
-                                    * f32::abs(best_positions[r][d] - current_positions[p][d]),
+                        cur[po + d] = wrap_to_unit_cube(
+                            best[ro + d]
+                                + normal.sample(rng) * effective_sigma * (1.0 + self.neighbour_multiplier * (1.0 + f32::cos(std::f32::consts::PI * t)))
+                                    * f32::abs(best[ro + d] - cur[po + d]),
                         )
                     }
                 }
             }
         }
-        let result = OptimizerResult {
-            x: fit_in_bounds(&best_positions[ind], &range_min, &range_max),
-            f_x: best_residuals[ind],
+        OptimizerResult {
+            x: fit_in_bounds(&best[ind*dims..(ind+1)*dims], &range_min, &range_max),
+            f_x: best_f[ind],
             nfev: (current_epoch + 1) * popsize as u64,
             history: if use_history { Some(history) } else { None },
-        };
-        return result;
+        }
+    }
+}
+
+impl Optimizer for AnsrV2 {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for AnsrV2 {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for AnsrV2 {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
     }
 }
 
@@ -237,7 +260,8 @@ impl Optimizer for AnsrV2 {
 mod tests {
     use super::*;
     use crate::early_stop_callback::EarlyStopCallback;
-    use crate::utils::broadcast_simd;
+    use crate::optimizer::{FailurePolicy, FallibleOptimizer, NoiseHandling, NoisyOptimizer, ObjectiveError, Resampling};
+    use crate::utils::{broadcast_simd, broadcast_simd_batch};
 
     fn sphere(x: Vec8, y: Vec8) -> Vec8 {
         x * x + y * y
@@ -423,6 +447,44 @@ mod tests {
         assert_eq!(history.x.len(), history.f_x.len());
     }
 
+    #[test]
+    fn test_nan_region_is_avoided() {
+        let ansr = default_ansr_v2();
+        let bounds = vec![[-5.0f32, 5.0]; 16];
+        let sphere = broadcast_simd(sphere);
+        // NaN on half of the domain; the optimum at 0 stays on its edge
+        let func = |x: &[Vec8]| if x[0].0[0] > 0.0 { f32::NAN } else { sphere(x) };
+        let early_stop = EarlyStopCallback::new(&func, 0.01);
+        let result = ansr.find_infimum(&func, &bounds, 100_000, 42, false, &early_stop);
+        assert!(result.f_x <= 0.01, "f_x={}", result.f_x);
+        assert!(result.x[0] <= 0.0);
+
+        let fallible = |x: &[Vec8]| -> Result<f32, ()> { Ok(func(x)) };
+        let aborted = ansr.find_infimum_fallible(&fallible, &bounds, 100_000, 42, false, 0.01, FailurePolicy::Abort);
+        assert_eq!(aborted.error, Some(ObjectiveError::NaN));
+        assert_eq!(aborted.failures, 1);
+    }
+
+    #[test]
+    fn test_batch_converges() {
+        let ansr = default_ansr_v2();
+        let bounds = vec![[-5.0f32, 5.0]; 16];
+        let result = ansr.find_infimum_batch(&broadcast_simd_batch(sphere), &bounds, 100_000, 42, false, 0.01);
+        assert!(result.f_x <= 0.01, "f_x={}", result.f_x);
+        assert!(result.nfev < 100_000);
+    }
+
+    #[test]
+    fn test_noisy_objective() {
+        let ansr = default_ansr_v2();
+        let bounds = vec![[-5.0f32, 5.0]; 16];
+        let func = crate::noise::Noise::Gaussian { sigma: 0.01 }.wrap(broadcast_simd(sphere), 0);
+        let handling = NoiseHandling { resampling: Resampling::Fixed { samples: 2 }, final_samples: 16 };
+        let result = ansr.find_infimum_noisy(&func, &bounds, 20_000, 42, false, f32::NEG_INFINITY, handling);
+        assert!(result.result.nfev <= 20_000);
+        assert!(result.mean_f_x < 0.1, "mean_f_x={}", result.mean_f_x);
+    }
+
     #[test]
     fn test_wrap_to_unit_cube() {
         assert!((wrap_to_unit_cube(0.5) - 0.5).abs() < 1e-6);
@@ -1,17 +1,20 @@
 use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Distribution, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{clamp_to_unit_cube, fit_in_bounds, BoundsSimd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    utils::{clamp_to_unit_cube, fit_in_bounds, split_bounds},
 };
 
 /// Classic Differential Evolution (DE/rand/1/bin)
+#[derive(Debug, Clone)]
 pub struct DE {
     pub popsize: usize,
     pub f: f32,
@@ -26,32 +29,25 @@ pub fn new_de(params: &BTreeMap<String, f32>) -> DE {
     }
 }
 
-impl Optimizer for DE {
-    fn find_infimum<F>(
+impl DE {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
         let dims = bounds.len();
         let popsize = self.popsize;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min = vec![0.0f32; dims];
-        let mut range_max = vec![0.0f32; dims];
-        for i in 0..dims {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let bounds_simd = BoundsSimd::new(&range_min, &range_max);
-        let mut simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        let (range_min, range_max) = split_bounds(bounds);
 
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
         let uniform_init = Uniform::new_inclusive(0.0f32, 1.0).unwrap();
         let uniform01 = Uniform::new(0.0f32, 1.0).unwrap();
         let dim_distr = Uniform::new(0, dims).unwrap();
@@ -59,13 +55,10 @@ impl Optimizer for DE {
 
         // Flat storage: popsize * dims
         let mut pop = vec![0.0f32; popsize * dims];
-        for v in &mut pop { *v = uniform_init.sample(&mut rng); }
+        for v in &mut pop { *v = uniform_init.sample(rng); }
         let mut fitness = vec![f32::INFINITY; popsize];
 
-        for p in 0..popsize {
-            bounds_simd.transform_into(&pop[p*dims..(p+1)*dims], &mut simd_buf);
-            fitness[p] = func(&simd_buf);
-        }
+        evaluator.evaluate(&mut pop, &mut fitness);
 
         let mut best_idx = 0;
         for p in 1..popsize {
@@ -74,7 +67,6 @@ impl Optimizer for DE {
             }
         }
 
-        let stop_residual = early_stop_callback.stop_residual();
         let mut history = OptimizationHistory {
             x: Vec::new(),
             f_x: Vec::new(),
@@ -86,7 +78,8 @@ impl Optimizer for DE {
 
         let f_scale = self.f;
         let cr = self.cr;
-        let mut trial = vec![0.0f32; dims];
+        let mut trials = vec![0.0f32; popsize * dims];
+        let mut trial_fitness = vec![f32::INFINITY; popsize];
         let mut current_epoch = 0;
         // Next generation buffers for generational replacement
         let mut next_pop = vec![0.0f32; popsize * dims];
@@ -94,43 +87,46 @@ impl Optimizer for DE {
 
         for epoch in 0..max_epoch {
             current_epoch = epoch;
-            if fitness[best_idx] <= stop_residual {
+            if fitness[best_idx] <= stop_residual || evaluator.aborted() {
                 break;
             }
 
             for i in 0..popsize {
                 // Select 3 distinct indices != i
-                let mut r1 = pop_distr.sample(&mut rng);
+                let mut r1 = pop_distr.sample(rng);
                 while r1 == i {
-                    r1 = pop_distr.sample(&mut rng);
+                    r1 = pop_distr.sample(rng);
                 }
-                let mut r2 = pop_distr.sample(&mut rng);
+                let mut r2 = pop_distr.sample(rng);
                 while r2 == i || r2 == r1 {
-                    r2 = pop_distr.sample(&mut rng);
+                    r2 = pop_distr.sample(rng);
                 }
-                let mut r3 = pop_distr.sample(&mut rng);
+                let mut r3 = pop_distr.sample(rng);
                 while r3 == i || r3 == r1 || r3 == r2 {
-                    r3 = pop_distr.sample(&mut rng);
+                    r3 = pop_distr.sample(rng);
                 }
 
                 // Mutation + binomial crossover (read from old generation)
-                let j_rand = dim_distr.sample(&mut rng);
+                let j_rand = dim_distr.sample(rng);
+                let io = i * dims;
                 for d in 0..dims {
-                    if d == j_rand || uniform01.sample(&mut rng) < cr {
-                        trial[d] = clamp_to_unit_cube(
+                    if d == j_rand || uniform01.sample(rng) < cr {
+                        trials[io + d] = clamp_to_unit_cube(
                             pop[r1 * dims + d] + f_scale * (pop[r2 * dims + d] - pop[r3 * dims + d]),
                         );
                     } else {
-                        trial[d] = pop[i * dims + d];
+                        trials[io + d] = pop[io + d];
                     }
                 }
+            }
+
+            evaluator.evaluate(&mut trials, &mut trial_fitness);
 
-                // Selection — write to next generation
-                bounds_simd.transform_into(&trial, &mut simd_buf);
-                let trial_fitness = func(&simd_buf);
-                if trial_fitness <= fitness[i] {
-                    next_pop[i*dims..(i+1)*dims].copy_from_slice(&trial);
-                    next_fitness[i] = trial_fitness;
+            // Selection — write to next generation
+            for i in 0..popsize {
+                if trial_fitness[i] <= fitness[i] {
+                    next_pop[i*dims..(i+1)*dims].copy_from_slice(&trials[i*dims..(i+1)*dims]);
+                    next_fitness[i] = trial_fitness[i];
                 } else {
                     next_pop[i*dims..(i+1)*dims].copy_from_slice(&pop[i*dims..(i+1)*dims]);
                     next_fitness[i] = fitness[i];
@@ -164,13 +160,72 @@ impl Optimizer for DE {
     }
 }
 
+impl Optimizer for DE {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for DE {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for DE {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         early_stop_callback::EarlyStopCallback,
         functions::{shifted_sphere, SHIFTED_SPHERE_BOUNDS},
-        utils::broadcast_simd,
+        utils::{broadcast_simd, broadcast_simd_batch},
     };
 
     #[test]
@@ -231,4 +286,18 @@ mod tests {
         assert_eq!(r1.f_x, r2.f_x);
         assert_eq!(r1.nfev, r2.nfev);
     }
+
+    #[test]
+    fn test_de_batch_finds_minimum() {
+        let optimizer = DE {
+            popsize: 32,
+            f: 0.8,
+            cr: 0.9,
+        };
+        let func = broadcast_simd_batch(shifted_sphere);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let result = optimizer.find_infimum_batch(&func, &bounds, 100_000, 0, false, 0.01);
+        assert!(result.f_x <= 0.01, "DE batch did not converge: f_x={}", result.f_x);
+        assert!(result.nfev > 0);
+    }
 }
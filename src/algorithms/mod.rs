@@ -2,6 +2,9 @@ pub mod ans;
 pub mod ans_sort;
 pub mod ansr;
 pub mod ansr_dpnm;
+pub mod ansr_v2;
 pub mod de;
+pub mod moead;
+pub mod nsga2;
 pub mod shade;
 pub mod zero_gradient;
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand_distr::{Distribution, Uniform};
+
+use crate::{
+    algorithms::nsga2::{pareto_result, polynomial_mutation},
+    evaluator::MultiObjectiveEvaluator,
+    optimizer::{MultiObjectiveOptimizer, ParetoResult},
+    pareto::{lattice_divisions, simplex_lattice},
+    utils::{clamp_to_unit_cube, split_bounds},
+};
+
+/// Distribution index of the polynomial mutation after the DE step.
+const MUTATION_ETA: f32 = 20.0;
+
+/// Smallest weight in the Tchebycheff function, so that every objective counts.
+const MIN_WEIGHT: f32 = 1e-6;
+
+/// MOEA/D-DE (Li and Zhang 2009): one Tchebycheff subproblem per weight
+/// vector, DE/rand/1 between neighbouring subproblems, polynomial mutation
+/// and a limited number of replacements per child.
+///
+/// The weights form a simplex lattice, so the population is the largest
+/// lattice with at most `popsize` vectors (exactly `popsize` for two objectives).
+#[derive(Debug, Clone)]
+pub struct MOEAD {
+    pub popsize: usize,
+    pub neighbourhood_size: usize,
+    /// Probability of mating and replacing within the neighbourhood instead
+    /// of the whole population.
+    pub neighbourhood_rate: f32,
+    pub max_replacements: usize,
+    pub f: f32,
+    pub cr: f32,
+}
+
+pub fn new_moead(params: &BTreeMap<String, f32>) -> MOEAD {
+    MOEAD {
+        popsize: params["popsize"] as usize,
+        neighbourhood_size: params["neighbourhood_size"] as usize,
+        neighbourhood_rate: params["neighbourhood_rate"],
+        max_replacements: params["max_replacements"] as usize,
+        f: params["f"],
+        cr: params["cr"],
+    }
+}
+
+fn tchebycheff(f: &[f32], weight: &[f32], ideal: &[f32]) -> f32 {
+    f.iter()
+        .zip(weight)
+        .zip(ideal)
+        .map(|((v, w), z)| w.max(MIN_WEIGHT) * (v - z).abs())
+        .fold(f32::NEG_INFINITY, f32::max)
+}
+
+/// For every weight, the indices of the `count` closest ones, itself first.
+fn neighbourhoods(weights: &[Vec<f32>], count: usize) -> Vec<Vec<usize>> {
+    let distance = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>();
+    weights
+        .iter()
+        .map(|w| {
+            let mut order: Vec<usize> = (0..weights.len()).collect();
+            order.sort_by(|&a, &b| distance(w, &weights[a]).total_cmp(&distance(w, &weights[b])));
+            order.truncate(count);
+            order
+        })
+        .collect()
+}
+
+fn shuffle<T, R: Rng>(values: &mut [T], rng: &mut R) {
+    for i in (1..values.len()).rev() {
+        let j = Uniform::new_inclusive(0, i).unwrap().sample(rng);
+        values.swap(i, j);
+    }
+}
+
+impl MultiObjectiveOptimizer for MOEAD {
+    fn find_pareto_front_with_rng<R, F>(
+        &self,
+        func: &F,
+        objective_count: usize,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+    ) -> ParetoResult
+    where
+        R: Rng,
+        F: Fn(&[f32], &mut [f32]) + Sync,
+    {
+        let dims = bounds.len();
+        let m = objective_count;
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = MultiObjectiveEvaluator::new(func, m, &range_min, &range_max);
+
+        let weights = simplex_lattice(m, lattice_divisions(m, self.popsize.max(2)));
+        let popsize = weights.len();
+        let neighbours = neighbourhoods(&weights, self.neighbourhood_size.clamp(2, popsize));
+        let everyone: Vec<usize> = (0..popsize).collect();
+
+        let uniform01 = Uniform::new(0.0f32, 1.0).unwrap();
+        let dim_distr = Uniform::new(0, dims).unwrap();
+        let mutation_rate = 1.0 / dims as f32;
+
+        // Flat storage: popsize * dims positions, popsize * m objectives
+        let mut pop = vec![0.0f32; popsize * dims];
+        for v in &mut pop {
+            *v = uniform01.sample(rng);
+        }
+        let mut objectives = vec![f32::INFINITY; popsize * m];
+        evaluator.evaluate(&pop, &mut objectives);
+        let mut nfev = popsize as u64;
+        let mut ideal = vec![f32::INFINITY; m];
+        for f in objectives.chunks_exact(m) {
+            for (z, v) in ideal.iter_mut().zip(f) {
+                *z = z.min(*v);
+            }
+        }
+
+        let mut child = vec![0.0f32; dims];
+        let mut child_objectives = vec![0.0f32; m];
+        let mut pool: Vec<usize> = Vec::with_capacity(popsize);
+        let mut order: Vec<usize> = (0..popsize).collect();
+
+        'search: loop {
+            shuffle(&mut order, rng);
+            for &i in &order {
+                if nfev >= maxiter {
+                    break 'search;
+                }
+                pool.clear();
+                if uniform01.sample(rng) < self.neighbourhood_rate {
+                    pool.extend(&neighbours[i]);
+                } else {
+                    pool.extend(&everyone);
+                }
+                let pool_distr = Uniform::new(0, pool.len()).unwrap();
+                let r1 = pool[pool_distr.sample(rng)];
+                let mut r2 = pool[pool_distr.sample(rng)];
+                while r2 == r1 {
+                    r2 = pool[pool_distr.sample(rng)];
+                }
+
+                let j_rand = dim_distr.sample(rng);
+                for d in 0..dims {
+                    let x = pop[i * dims + d];
+                    child[d] = if d == j_rand || uniform01.sample(rng) < self.cr {
+                        clamp_to_unit_cube(x + self.f * (pop[r1 * dims + d] - pop[r2 * dims + d]))
+                    } else {
+                        x
+                    };
+                    if uniform01.sample(rng) < mutation_rate {
+                        child[d] = polynomial_mutation(child[d], MUTATION_ETA, rng);
+                    }
+                }
+                evaluator.evaluate(&child, &mut child_objectives);
+                nfev += 1;
+                for (z, v) in ideal.iter_mut().zip(&child_objectives) {
+                    *z = z.min(*v);
+                }
+
+                shuffle(&mut pool, rng);
+                let mut replaced = 0;
+                for &j in &pool {
+                    if replaced == self.max_replacements {
+                        break;
+                    }
+                    let current = &objectives[j * m..(j + 1) * m];
+                    if tchebycheff(&child_objectives, &weights[j], &ideal)
+                        <= tchebycheff(current, &weights[j], &ideal)
+                    {
+                        pop[j * dims..(j + 1) * dims].copy_from_slice(&child);
+                        objectives[j * m..(j + 1) * m].copy_from_slice(&child_objectives);
+                        replaced += 1;
+                    }
+                }
+            }
+        }
+
+        pareto_result(&pop, &objectives, m, &range_min, &range_max, nfev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_algorithms_params::DEFAULT_MOEAD,
+        functions::multi_objective::{MultiObjectiveProblem, DTLZ_TEST_FUNCTIONS, ZDT_TEST_FUNCTIONS},
+        pareto::{hypervolume, igd},
+    };
+
+    #[test]
+    fn test_neighbourhoods_start_with_self() {
+        let weights = simplex_lattice(2, 9);
+        let neighbours = neighbourhoods(&weights, 3);
+        assert_eq!(neighbours[0], vec![0, 1, 2]);
+        assert_eq!(neighbours[5][0], 5);
+        assert!(neighbours[5].contains(&4) && neighbours[5].contains(&6));
+    }
+
+    #[test]
+    fn test_moead_approximates_fronts() {
+        for (problem, maxiter, max_igd) in [
+            (&ZDT_TEST_FUNCTIONS["zdt2"], 50_000, 0.01),
+            // 91 weights cannot cover the sampled sphere more closely
+            (&DTLZ_TEST_FUNCTIONS["dtlz2"], 20_000, 0.08),
+        ] {
+            let dims = problem.default_dims();
+            let result = DEFAULT_MOEAD.find_pareto_front(
+                &|x: &[f32], f: &mut [f32]| problem.evaluate(x, f),
+                problem.objective_count(),
+                &problem.bounds(dims),
+                maxiter,
+                2,
+            );
+            assert_eq!(result.nfev, maxiter);
+            let distance = igd(&result.f_x, &problem.pareto_front(300));
+            assert!(distance < max_igd, "IGD {distance}");
+            let volume = hypervolume(&result.f_x, &problem.reference_point());
+            let optimum = hypervolume(&problem.pareto_front(300), &problem.reference_point());
+            assert!(volume > 0.9 * optimum, "{volume} of {optimum}");
+        }
+    }
+}
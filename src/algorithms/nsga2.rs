@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand_distr::{Distribution, Uniform};
+
+use crate::{
+    evaluator::MultiObjectiveEvaluator,
+    optimizer::{MultiObjectiveOptimizer, ParetoResult},
+    pareto::{crowding_distance, fast_non_dominated_sort, non_dominated},
+    utils::{fit_in_bounds, split_bounds},
+};
+
+/// NSGA-II (Deb et al. 2002): fast non-dominated sort and crowding distance,
+/// simulated binary crossover and polynomial mutation
+#[derive(Debug, Clone)]
+pub struct NSGA2 {
+    pub popsize: usize,
+    pub crossover_rate: f32,
+    pub crossover_eta: f32,
+    pub mutation_eta: f32,
+}
+
+pub fn new_nsga2(params: &BTreeMap<String, f32>) -> NSGA2 {
+    NSGA2 {
+        popsize: params["popsize"] as usize,
+        crossover_rate: params["crossover_rate"],
+        crossover_eta: params["crossover_eta"],
+        mutation_eta: params["mutation_eta"],
+    }
+}
+
+/// Polynomial mutation (Deb and Goyal 1996) of a coordinate in `[0, 1]`.
+pub(crate) fn polynomial_mutation<R: Rng>(x: f32, eta: f32, rng: &mut R) -> f32 {
+    let u: f32 = Uniform::new(0.0f32, 1.0).unwrap().sample(rng);
+    let power = 1.0 / (eta + 1.0);
+    let delta = if u < 0.5 {
+        let val = 2.0 * u + (1.0 - 2.0 * u) * (1.0 - x).powf(eta + 1.0);
+        val.powf(power) - 1.0
+    } else {
+        let val = 2.0 * (1.0 - u) + 2.0 * (u - 0.5) * x.powf(eta + 1.0);
+        1.0 - val.powf(power)
+    };
+    (x + delta).clamp(0.0, 1.0)
+}
+
+/// Bounded simulated binary crossover (Deb and Agrawal 1995) of two
+/// coordinates in `[0, 1]`.
+fn sbx<R: Rng>(a: f32, b: f32, eta: f32, rng: &mut R) -> (f32, f32) {
+    let uniform01 = Uniform::new(0.0f32, 1.0).unwrap();
+    if (a - b).abs() < 1e-7 {
+        return (a, b);
+    }
+    let (lo, hi) = (a.min(b), a.max(b));
+    let power = 1.0 / (eta + 1.0);
+    let spread = |beta: f32, u: f32| {
+        let alpha = 2.0 - beta.powf(-(eta + 1.0));
+        if u <= 1.0 / alpha {
+            (u * alpha).powf(power)
+        } else {
+            (1.0 / (2.0 - u * alpha)).powf(power)
+        }
+    };
+    let u = uniform01.sample(rng);
+    let beta_lo = spread(1.0 + 2.0 * lo / (hi - lo), u);
+    let beta_hi = spread(1.0 + 2.0 * (1.0 - hi) / (hi - lo), u);
+    let c1 = (0.5 * ((lo + hi) - beta_lo * (hi - lo))).clamp(0.0, 1.0);
+    let c2 = (0.5 * ((lo + hi) + beta_hi * (hi - lo))).clamp(0.0, 1.0);
+    if uniform01.sample(rng) < 0.5 { (c1, c2) } else { (c2, c1) }
+}
+
+/// Front index and crowding distance of every point.
+fn rank_and_crowding(objectives: &[f32], m: usize) -> (Vec<usize>, Vec<f32>) {
+    let count = objectives.len() / m;
+    let mut rank = vec![0; count];
+    let mut crowding = vec![0.0; count];
+    for (r, front) in fast_non_dominated_sort(objectives, m).iter().enumerate() {
+        for (&i, d) in front.iter().zip(crowding_distance(objectives, m, front)) {
+            rank[i] = r;
+            crowding[i] = d;
+        }
+    }
+    (rank, crowding)
+}
+
+/// The non-dominated points of a population, mapped to bounds.
+pub(crate) fn pareto_result(
+    pop: &[f32],
+    objectives: &[f32],
+    m: usize,
+    range_min: &[f32],
+    range_max: &[f32],
+    nfev: u64,
+) -> ParetoResult {
+    let dims = range_min.len();
+    let f_x: Vec<Vec<f32>> = objectives.chunks_exact(m).map(<[f32]>::to_vec).collect();
+    let front = non_dominated(&f_x);
+    ParetoResult {
+        x: front
+            .iter()
+            .map(|&i| fit_in_bounds(&pop[i * dims..(i + 1) * dims], range_min, range_max))
+            .collect(),
+        f_x: front.iter().map(|&i| f_x[i].clone()).collect(),
+        nfev,
+    }
+}
+
+impl MultiObjectiveOptimizer for NSGA2 {
+    fn find_pareto_front_with_rng<R, F>(
+        &self,
+        func: &F,
+        objective_count: usize,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+    ) -> ParetoResult
+    where
+        R: Rng,
+        F: Fn(&[f32], &mut [f32]) + Sync,
+    {
+        let dims = bounds.len();
+        let m = objective_count;
+        let popsize = self.popsize;
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = MultiObjectiveEvaluator::new(func, m, &range_min, &range_max);
+
+        let uniform01 = Uniform::new(0.0f32, 1.0).unwrap();
+        let pop_distr = Uniform::new(0, popsize).unwrap();
+        let mutation_rate = 1.0 / dims as f32;
+
+        // Flat storage: popsize * dims positions, popsize * m objectives;
+        // parents and offspring side by side for the environmental selection
+        let mut pop = vec![0.0f32; 2 * popsize * dims];
+        for v in &mut pop[..popsize * dims] {
+            *v = uniform01.sample(rng);
+        }
+        let mut objectives = vec![f32::INFINITY; 2 * popsize * m];
+        evaluator.evaluate(&pop[..popsize * dims], &mut objectives[..popsize * m]);
+        let mut nfev = popsize as u64;
+        let (mut rank, mut crowding) = rank_and_crowding(&objectives[..popsize * m], m);
+
+        let mut next_pop = pop.clone();
+        let mut next_objectives = objectives.clone();
+        let mut children = vec![0.0f32; 2 * dims];
+
+        while nfev + popsize as u64 <= maxiter {
+            // Binary tournaments on rank, then crowding
+            let mut tournament = || {
+                let (a, b) = (pop_distr.sample(rng), pop_distr.sample(rng));
+                if rank[b] < rank[a] || (rank[b] == rank[a] && crowding[b] > crowding[a]) { b } else { a }
+            };
+            let parents: Vec<usize> = (0..popsize.next_multiple_of(2)).map(|_| tournament()).collect();
+
+            for (pair, p) in parents.chunks_exact(2).enumerate() {
+                let (c1, c2) = children.split_at_mut(dims);
+                c1.copy_from_slice(&pop[p[0] * dims..(p[0] + 1) * dims]);
+                c2.copy_from_slice(&pop[p[1] * dims..(p[1] + 1) * dims]);
+                if uniform01.sample(rng) < self.crossover_rate {
+                    for d in 0..dims {
+                        if uniform01.sample(rng) < 0.5 {
+                            (c1[d], c2[d]) = sbx(c1[d], c2[d], self.crossover_eta, rng);
+                        }
+                    }
+                }
+                for v in children.iter_mut() {
+                    if uniform01.sample(rng) < mutation_rate {
+                        *v = polynomial_mutation(*v, self.mutation_eta, rng);
+                    }
+                }
+                // With an odd popsize the second child of the last pair is dropped
+                for (k, child) in children.chunks_exact(dims).enumerate() {
+                    let i = 2 * pair + k;
+                    if i < popsize {
+                        pop[(popsize + i) * dims..(popsize + i + 1) * dims].copy_from_slice(child);
+                    }
+                }
+            }
+            let (offspring, offspring_objectives) = (&pop[popsize * dims..], &mut objectives[popsize * m..]);
+            evaluator.evaluate(offspring, offspring_objectives);
+            nfev += popsize as u64;
+
+            // Environmental selection: whole fronts, the last one by crowding
+            let mut selected = Vec::with_capacity(popsize);
+            for front in fast_non_dominated_sort(&objectives, m) {
+                if selected.len() + front.len() <= popsize {
+                    selected.extend(front);
+                } else {
+                    let distance = crowding_distance(&objectives, m, &front);
+                    let mut order: Vec<usize> = (0..front.len()).collect();
+                    order.sort_by(|&a, &b| distance[b].total_cmp(&distance[a]));
+                    let missing = popsize - selected.len();
+                    selected.extend(order[..missing].iter().map(|&k| front[k]));
+                }
+                if selected.len() == popsize {
+                    break;
+                }
+            }
+            for (i, &s) in selected.iter().enumerate() {
+                next_pop[i * dims..(i + 1) * dims].copy_from_slice(&pop[s * dims..(s + 1) * dims]);
+                next_objectives[i * m..(i + 1) * m].copy_from_slice(&objectives[s * m..(s + 1) * m]);
+            }
+            std::mem::swap(&mut pop, &mut next_pop);
+            std::mem::swap(&mut objectives, &mut next_objectives);
+            (rank, crowding) = rank_and_crowding(&objectives[..popsize * m], m);
+        }
+
+        pareto_result(&pop[..popsize * dims], &objectives[..popsize * m], m, &range_min, &range_max, nfev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        default_algorithms_params::DEFAULT_NSGA2,
+        functions::multi_objective::{MultiObjectiveProblem, ZDT_TEST_FUNCTIONS},
+        pareto::igd,
+    };
+
+    #[test]
+    fn test_operators_stay_in_unit_interval() {
+        let mut rng = crate::rng::stream_rng::<crate::rng::DefaultRng>(1, 0);
+        for i in 0..1000 {
+            let x = (i % 11) as f32 / 10.0;
+            let y = polynomial_mutation(x, 20.0, &mut rng);
+            assert!((0.0..=1.0).contains(&y));
+            let (a, b) = sbx(x, 1.0 - x, 15.0, &mut rng);
+            assert!((0.0..=1.0).contains(&a) && (0.0..=1.0).contains(&b));
+        }
+    }
+
+    #[test]
+    fn test_nsga2_approximates_zdt1() {
+        let problem = &ZDT_TEST_FUNCTIONS["zdt1"];
+        let dims = 30;
+        let result = DEFAULT_NSGA2.find_pareto_front(
+            &|x: &[f32], f: &mut [f32]| problem.evaluate(x, f),
+            problem.objective_count(),
+            &problem.bounds(dims),
+            25_000,
+            1,
+        );
+        assert!(result.nfev <= 25_000);
+        assert_eq!(result.x.len(), result.f_x.len());
+        let distance = igd(&result.f_x, &problem.pareto_front(200));
+        assert!(distance < 0.02, "IGD {distance}");
+    }
+}
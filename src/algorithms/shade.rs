@@ -1,14 +1,16 @@
 use std::collections::BTreeMap;
 
-use rand::SeedableRng;
+use rand::Rng;
 use rand_distr::{Cauchy, Distribution, Normal, Uniform};
-use rand_pcg::Pcg64Mcg;
 use simd_vector::Vec8;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{fit_in_bounds, BoundsSimd},
+    evaluator::{BatchEvaluator, PopulationEvaluator, SingleEvaluator},
+    optimizer::{
+        BatchOptimizer, OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer,
+    },
+    utils::{fit_in_bounds, split_bounds},
 };
 
 /// Midpoint boundary repair (JADE/SHADE standard):
@@ -29,6 +31,7 @@ fn midpoint_repair(v: f32, parent: f32) -> f32 {
 /// (Tanabe & Fukunaga, CEC 2013)
 /// Uses current-to-pbest/1 mutation with adaptive F and CR,
 /// external archive, midpoint boundary repair, and generational replacement.
+#[derive(Debug, Clone)]
 pub struct SHADE {
     pub popsize: usize,
     pub h: usize,
@@ -43,32 +46,25 @@ pub fn new_shade(params: &BTreeMap<String, f32>) -> SHADE {
     }
 }
 
-impl Optimizer for SHADE {
-    fn find_infimum<F>(
+impl SHADE {
+    fn search<E, R>(
         &self,
-        func: &F,
+        evaluator: &mut E,
         bounds: &[[f32; 2]],
         maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
-        early_stop_callback: &EarlyStopCallback<&F>,
+        stop_residual: f32,
     ) -> OptimizerResult
     where
-        F: Fn(&[Vec8]) -> f32 + Sync,
+        E: PopulationEvaluator,
+        R: Rng,
     {
         let dims = bounds.len();
         let popsize = self.popsize;
         let max_epoch = f64::ceil(maxiter as f64 / popsize as f64) as u64;
-        let mut range_min = vec![0.0f32; dims];
-        let mut range_max = vec![0.0f32; dims];
-        for i in 0..dims {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let bounds_simd = BoundsSimd::new(&range_min, &range_max);
-        let mut simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        let (range_min, range_max) = split_bounds(bounds);
 
-        let mut rng: Pcg64Mcg = SeedableRng::seed_from_u64(seed);
         let uniform_init = Uniform::new_inclusive(0.0f32, 1.0).unwrap();
         let uniform01 = Uniform::new(0.0f32, 1.0).unwrap();
         let dim_distr = Uniform::new(0, dims).unwrap();
@@ -84,14 +80,11 @@ impl Optimizer for SHADE {
         // Flat storage: popsize * dims
         let mut pop = vec![0.0f32; popsize * dims];
         for v in &mut pop {
-            *v = uniform_init.sample(&mut rng);
+            *v = uniform_init.sample(rng);
         }
         let mut fitness: Vec<f32> = vec![f32::INFINITY; popsize];
 
-        for p in 0..popsize {
-            bounds_simd.transform_into(&pop[p * dims..(p + 1) * dims], &mut simd_buf);
-            fitness[p] = func(&simd_buf);
-        }
+        evaluator.evaluate(&mut pop, &mut fitness);
 
         let mut best_idx = 0;
         for p in 1..popsize {
@@ -110,7 +103,6 @@ impl Optimizer for SHADE {
         let mut archive: Vec<f32> = Vec::with_capacity(popsize * dims);
         let mut archive_len: usize = 0;
 
-        let stop_residual = early_stop_callback.stop_residual();
         let mut history = OptimizationHistory {
             x: Vec::new(),
             f_x: Vec::new(),
@@ -136,7 +128,7 @@ impl Optimizer for SHADE {
 
         for epoch in 0..max_epoch {
             current_epoch = epoch;
-            if fitness[best_idx] <= stop_residual {
+            if fitness[best_idx] <= stop_residual || evaluator.aborted() {
                 break;
             }
 
@@ -146,45 +138,45 @@ impl Optimizer for SHADE {
             // Phase 1: Generate all trial vectors from current generation
             for i in 0..popsize {
                 // Sample F and CR from history
-                let r_idx = h_distr.sample(&mut rng);
+                let r_idx = h_distr.sample(rng);
                 let cauchy = Cauchy::new(m_f[r_idx] as f64, 0.1).unwrap();
-                let mut fi = cauchy.sample(&mut rng) as f32;
+                let mut fi = cauchy.sample(rng) as f32;
                 while fi <= 0.0 {
-                    fi = cauchy.sample(&mut rng) as f32;
+                    fi = cauchy.sample(rng) as f32;
                 }
                 if fi > 1.0 {
                     fi = 1.0;
                 }
 
                 let normal = Normal::new(m_cr[r_idx] as f64, 0.1).unwrap();
-                let cri = (normal.sample(&mut rng) as f32).clamp(0.0, 1.0);
+                let cri = (normal.sample(rng) as f32).clamp(0.0, 1.0);
 
                 trial_f[i] = fi;
                 trial_cr[i] = cri;
 
                 // Random p per individual (paper eq. 20)
-                let pi = p_distr.sample(&mut rng);
+                let pi = p_distr.sample(rng);
                 let p_num = ((popsize as f32 * pi).ceil() as usize).max(2);
-                let pbest = sorted_idx[Uniform::new(0, p_num).unwrap().sample(&mut rng)];
+                let pbest = sorted_idx[Uniform::new(0, p_num).unwrap().sample(rng)];
 
                 // Select r1 != i from population
-                let mut r1 = pop_distr.sample(&mut rng);
+                let mut r1 = pop_distr.sample(rng);
                 while r1 == i {
-                    r1 = pop_distr.sample(&mut rng);
+                    r1 = pop_distr.sample(rng);
                 }
 
                 // Select r2 != i, r1 from population ∪ archive
                 let union_size = popsize + archive_len;
-                let mut r2 = Uniform::new(0, union_size).unwrap().sample(&mut rng);
+                let mut r2 = Uniform::new(0, union_size).unwrap().sample(rng);
                 while r2 == i || r2 == r1 {
-                    r2 = Uniform::new(0, union_size).unwrap().sample(&mut rng);
+                    r2 = Uniform::new(0, union_size).unwrap().sample(rng);
                 }
 
                 // current-to-pbest/1 mutation + binomial crossover
-                let j_rand = dim_distr.sample(&mut rng);
+                let j_rand = dim_distr.sample(rng);
                 let io = i * dims;
                 for d in 0..dims {
-                    if d == j_rand || uniform01.sample(&mut rng) < cri {
+                    if d == j_rand || uniform01.sample(rng) < cri {
                         let r2_d = if r2 < popsize {
                             pop[r2 * dims + d]
                         } else {
@@ -200,12 +192,10 @@ impl Optimizer for SHADE {
                         trials[io + d] = pop[io + d];
                     }
                 }
-
-                // Evaluate trial
-                bounds_simd.transform_into(&trials[io..io + dims], &mut simd_buf);
-                trial_fitness[i] = func(&simd_buf);
             }
 
+            evaluator.evaluate(&mut trials, &mut trial_fitness);
+
             // Phase 2: Selection (generational replacement)
             s_f.clear();
             s_cr.clear();
@@ -223,7 +213,7 @@ impl Optimizer for SHADE {
                             archive_len += 1;
                         } else {
                             let arc_idx =
-                                Uniform::new(0, archive_len).unwrap().sample(&mut rng);
+                                Uniform::new(0, archive_len).unwrap().sample(rng);
                             archive[arc_idx * dims..(arc_idx + 1) * dims]
                                 .copy_from_slice(&pop[i * dims..(i + 1) * dims]);
                         }
@@ -243,9 +233,10 @@ impl Optimizer for SHADE {
                 }
             }
 
-            // Update history with weighted Lehmer mean
-            if !s_f.is_empty() {
-                let sum_delta: f32 = s_delta.iter().sum();
+            // Update history with weighted Lehmer mean; skipped when a parent
+            // scored as worst (f32::INFINITY) makes the weights non-finite
+            let sum_delta: f32 = s_delta.iter().sum();
+            if !s_f.is_empty() && sum_delta.is_finite() {
                 let weights: Vec<f32> = s_delta.iter().map(|d| d / sum_delta).collect();
 
                 let num: f32 = weights.iter().zip(&s_f).map(|(w, f)| w * f * f).sum();
@@ -279,13 +270,72 @@ impl Optimizer for SHADE {
     }
 }
 
+impl Optimizer for SHADE {
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        let stop_residual = early_stop_callback.stop_residual();
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl BatchOptimizer for SHADE {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = BatchEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
+impl PopulationOptimizer for SHADE {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         early_stop_callback::EarlyStopCallback,
         functions::{shifted_sphere, SHIFTED_SPHERE_BOUNDS},
-        utils::broadcast_simd,
+        utils::{broadcast_simd, broadcast_simd_batch},
     };
 
     #[test]
@@ -353,4 +403,18 @@ mod tests {
         assert_eq!(midpoint_repair(-0.2, 0.4), 0.2); // below: (0 + 0.4) / 2
         assert_eq!(midpoint_repair(1.3, 0.6), 0.8);  // above: (1 + 0.6) / 2
     }
+
+    #[test]
+    fn test_shade_batch_finds_minimum() {
+        let optimizer = SHADE {
+            popsize: 32,
+            h: 10,
+            p_best_rate: 0.1,
+        };
+        let func = broadcast_simd_batch(shifted_sphere);
+        let bounds = SHIFTED_SPHERE_BOUNDS.repeat(8);
+        let result = optimizer.find_infimum_batch(&func, &bounds, 100_000, 0, false, 0.01);
+        assert!(result.f_x <= 0.01, "SHADE batch did not converge: f_x={}", result.f_x);
+        assert!(result.nfev > 0);
+    }
 }
@@ -1,11 +1,12 @@
 use simd_vector::Vec8;
-use rand::{SeedableRng, rngs::StdRng};
+use rand::Rng;
 use rand_distr::{Distribution, Uniform};
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    optimizer::{OptimizationHistory, Optimizer, OptimizerResult},
-    utils::{clamp_to_unit_cube, fit_in_bounds, fit_in_bounds_simd},
+    evaluator::{PopulationEvaluator, SingleEvaluator},
+    optimizer::{OptimizationHistory, Optimizer, OptimizerResult, PopulationOptimizer},
+    utils::{clamp_to_unit_cube, fit_in_bounds, split_bounds},
 };
 
 pub fn zero_gradient<F>(
@@ -18,32 +19,50 @@ pub fn zero_gradient<F>(
 ) -> OptimizerResult
 where
     F: Fn(&[Vec8]) -> f32 + Sync,
+{
+    let mut evaluator = SingleEvaluator::new(func, range_min, range_max);
+    zero_gradient_search(&mut evaluator, range_min, range_max, current_positions, init_jump, use_history)
+}
+
+/// Scores the single candidate `position` (NaN as worst).
+fn evaluate<E: PopulationEvaluator>(evaluator: &mut E, position: &mut [f32]) -> f32 {
+    let mut f_x = [0.0];
+    evaluator.evaluate(position, &mut f_x);
+    f_x[0]
+}
+
+/// `zero_gradient` against any evaluator; stops early once it aborts.
+pub fn zero_gradient_search<E>(
+    evaluator: &mut E,
+    range_min: &[f32],
+    range_max: &[f32],
+    current_positions: &[f32],
+    init_jump: f32,
+    use_history: bool,
+) -> OptimizerResult
+where
+    E: PopulationEvaluator,
 {
     let mut history = OptimizationHistory {
         x: Vec::new(),
         f_x: Vec::new(),
     };
-    let mut current_positions = current_positions.clone();
-    let mut current_residual = func(&fit_in_bounds_simd(
-        &current_positions,
-        range_min,
-        range_max,
-    ));
+    let mut current_positions = current_positions.to_vec();
+    let mut current_residual = evaluate(evaluator, &mut current_positions);
     if use_history {
         history.x.push([current_positions.clone()].to_vec());
         history.f_x.push([current_residual].to_vec());
     }
     let mut nfev = 1;
     for p in 0..current_positions.len() {
+        if evaluator.aborted() {
+            break;
+        }
         let current_coordinate = current_positions[p];
         let mut multiplicator = 1.0;
         let lhs_coordinate = clamp_to_unit_cube(current_coordinate - init_jump * multiplicator);
         current_positions[p] = lhs_coordinate;
-        let lhs_residual = func(&fit_in_bounds_simd(
-            &current_positions,
-            range_min,
-            range_max,
-        ));
+        let lhs_residual = evaluate(evaluator, &mut current_positions);
         if use_history {
             history.x.push([current_positions.clone()].to_vec());
             history.f_x.push([lhs_residual].to_vec());
@@ -51,11 +70,7 @@ where
         nfev += 1;
         let rhs_coordinate = clamp_to_unit_cube(current_coordinate + init_jump * multiplicator);
         current_positions[p] = rhs_coordinate;
-        let rhs_residual = func(&fit_in_bounds_simd(
-            &current_positions,
-            range_min,
-            range_max,
-        ));
+        let rhs_residual = evaluate(evaluator, &mut current_positions);
         if use_history {
             history.x.push([current_positions.clone()].to_vec());
             history.f_x.push([rhs_residual].to_vec());
@@ -80,11 +95,7 @@ where
             let new_coordinate =
                 clamp_to_unit_cube(current_coordinate + init_jump * multiplicator * turn);
             current_positions[p] = new_coordinate;
-            let new_residual = func(&fit_in_bounds_simd(
-                &current_positions,
-                range_min,
-                range_max,
-            ));
+            let new_residual = evaluate(evaluator, &mut current_positions);
             if use_history {
                 history.x.push([current_positions.clone()].to_vec());
                 history.f_x.push([new_residual].to_vec());
@@ -105,11 +116,7 @@ where
         let new_coordinate =
             clamp_to_unit_cube(current_coordinate + init_jump * multiplicator * turn);
         current_positions[p] = new_coordinate;
-        let new_residual = func(&fit_in_bounds_simd(
-            &current_positions,
-            range_min,
-            range_max,
-        ));
+        let new_residual = evaluate(evaluator, &mut current_positions);
         if use_history {
             history.x.push([current_positions.clone()].to_vec());
             history.f_x.push([new_residual].to_vec());
@@ -129,11 +136,7 @@ where
             }
             let lhs_coordinate = clamp_to_unit_cube(current_coordinate - add);
             current_positions[p] = lhs_coordinate;
-            let lhs_residual = func(&fit_in_bounds_simd(
-                &current_positions,
-                range_min,
-                range_max,
-            ));
+            let lhs_residual = evaluate(evaluator, &mut current_positions);
             if use_history {
                 history.x.push([current_positions.clone()].to_vec());
                 history.f_x.push([lhs_residual].to_vec());
@@ -141,11 +144,7 @@ where
             nfev += 1;
             let rhs_coordinate = clamp_to_unit_cube(current_coordinate + add);
             current_positions[p] = rhs_coordinate;
-            let rhs_residual = func(&fit_in_bounds_simd(
-                &current_positions,
-                range_min,
-                range_max,
-            ));
+            let rhs_residual = evaluate(evaluator, &mut current_positions);
             if use_history {
                 history.x.push([current_positions.clone()].to_vec());
                 history.f_x.push([rhs_residual].to_vec());
@@ -176,11 +175,7 @@ where
             let new_coordinate =
                 clamp_to_unit_cube(current_coordinate + init_jump * multiplicator * turn);
             current_positions[p] = new_coordinate;
-            let new_residual = func(&fit_in_bounds_simd(
-                &current_positions,
-                range_min,
-                range_max,
-            ));
+            let new_residual = evaluate(evaluator, &mut current_positions);
             if use_history {
                 history.x.push([current_positions.clone()].to_vec());
                 history.f_x.push([new_residual].to_vec());
@@ -213,42 +208,54 @@ pub fn new_zero_gradient(params: &std::collections::BTreeMap<String, f32>) -> Ze
     }
 }
 
+impl ZeroGradient {
+    fn search<E, R>(&self, evaluator: &mut E, bounds: &[[f32; 2]], rng: &mut R, use_history: bool) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        let (range_min, range_max) = split_bounds(bounds);
+        let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
+        let current_positions: Vec<f32> = (0..bounds.len()).map(|_| random.sample(rng)).collect();
+        zero_gradient_search(evaluator, &range_min, &range_max, &current_positions, self.init_jump, use_history)
+    }
+}
+
 impl Optimizer for ZeroGradient {
-    fn find_infimum<F>(
+    fn find_infimum_with_rng<R, F>(
         &self,
         func: &F,
         bounds: &[[f32; 2]],
         _maxiter: u64,
-        seed: u64,
+        rng: &mut R,
         use_history: bool,
         _early_stop_callback: &EarlyStopCallback<&F>,
     ) -> OptimizerResult
     where
+        R: Rng,
         F: Fn(&[Vec8]) -> f32 + Sync,
     {
-        let params = bounds.len();
-        let mut range_min: Vec<f32> = vec![0.0; params];
-        let mut range_max: Vec<f32> = vec![0.0; params];
-        for i in 0..params {
-            range_min[i] = bounds[i][0];
-            range_max[i] = bounds[i][1];
-        }
-        let mut current_positions: Vec<f32> = vec![0.0; params];
-        let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
-        let random = Uniform::new_inclusive(0.0, 1.0).unwrap();
-        for d in 0..params {
-            current_positions[d] = random.sample(&mut rng);
-        }
-        let init_jump = self.init_jump;
-        let result = zero_gradient(
-            func,
-            &range_min,
-            &range_max,
-            &current_positions,
-            init_jump,
-            use_history,
-        );
-        result
+        let (range_min, range_max) = split_bounds(bounds);
+        let mut evaluator = SingleEvaluator::new(func, &range_min, &range_max);
+        self.search(&mut evaluator, bounds, rng, use_history)
+    }
+}
+
+impl PopulationOptimizer for ZeroGradient {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        _maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        _stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng,
+    {
+        self.search(evaluator, bounds, rng, use_history)
     }
 }
 
@@ -332,6 +339,19 @@ mod tests {
         assert!(result.f_x < 0.05);
     }
 
+    #[test]
+    fn test_zero_gradient_nan_is_worst() {
+        let sphere = broadcast_simd(sphere);
+        // NaN right of the optimum in the first coordinate
+        let func = |x: &[Vec8]| if x[0].0[0] > 0.0 { f32::NAN } else { sphere(x) };
+        let range_min = vec![-5.0; 16];
+        let range_max = vec![5.0; 16];
+        let positions = vec![0.3; 16];
+        let result = zero_gradient(&func, &range_min, &range_max, &positions, 0.1, false);
+        assert!(result.f_x < 0.1, "f_x={}", result.f_x);
+        assert!(result.x[0] <= 0.0);
+    }
+
     #[test]
     fn test_zero_gradient_standalone_with_history() {
         let func = broadcast_simd(sphere);
@@ -0,0 +1,961 @@
+//! Ask/tell optimization: the caller evaluates candidates and reports values.
+//!
+//! A `Study` runs a population optimizer on a worker thread whose evaluator
+//! hands each population out through `ask` and waits until every candidate of
+//! it has been answered through `tell`. Candidate ids count evaluations from
+//! zero, so the same seed and the same answers always reproduce the same
+//! candidates; this is what lets a study be restored from the values told so
+//! far by replaying them.
+//!
+//! `StudyServer` hosts named studies behind a line-delimited JSON protocol,
+//! one request object per line and one response object per line:
+//!
+//! - `{"op":"create","study":S,"algorithm":A,"bounds":[[lo,hi],...],"maxiter":N}`,
+//!   optionally with `"params":{...}` (all parameters of the algorithm,
+//!   default values otherwise) and `"seed":K` (default 0)
+//! - `{"op":"ask","study":S,"count":N}` answers `"candidates":[{"id":I,"x":[...]}]`
+//!   and `"done"`; fewer than `count` candidates are returned when the rest of
+//!   the current population is still being evaluated
+//! - `{"op":"tell","study":S,"results":[{"id":I,"value":V}]}`, a `null` value
+//!   marks a failed evaluation
+//! - `{"op":"best","study":S}` answers `"x"`, `"value"`, `"evaluations"` and `"done"`
+//! - `{"op":"checkpoint"}` or `{"op":"checkpoint","study":S}`
+//! - `{"op":"list"}` and `{"op":"delete","study":S}`
+//!
+//! Responses carry `"ok":true` or `"ok":false` with an `"error"` message.
+//! With a directory, every study is kept as a snapshot `<S>.json`, written on
+//! create and checkpoint, plus a log `<S>.tells` of the values told since,
+//! appended on every tell, and is restored when a server opens the directory.
+//! Log lines are numbered by the tells before them, so that lines already in
+//! the snapshot are skipped when a crash left the log of the previous one.
+//!
+//! Seeds and ids are integers up to 2^53 - 1, which JSON numbers hold exactly.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+
+use rand::Rng;
+
+use crate::{
+    algorithms::{
+        ans::{new_ans, ANS},
+        ans_sort::{new_ans_sort, AnsSorted},
+        ansr::{new_ansr, ANSR},
+        ansr_dpnm::{new_ansr_dpnm, AnsrDpnm},
+        de::{new_de, DE},
+        shade::{new_shade, SHADE},
+    },
+    default_algorithms_params::{
+        ans_params, ans_sort_params, ansr_dpnm_params, ansr_params, check_params, de_params,
+        shade_params, DEFAULT_ANS, DEFAULT_ANSR, DEFAULT_ANSR_DPNM, DEFAULT_ANS_SORT, DEFAULT_DE, DEFAULT_SHADE,
+    },
+    evaluator::PopulationEvaluator,
+    json::{Json, MAX_INTEGER},
+    optimizer::{OptimizerResult, PopulationOptimizer},
+    rng::{stream_rng, DefaultRng},
+    utils::{fit_in_bounds, split_bounds},
+};
+
+/// Names accepted by `Algorithm::new`.
+pub const ALGORITHMS: [&str; 6] = ["ans", "ans_sort", "ansr", "ansr_dpnm", "de", "shade"];
+
+/// The population optimizers that can be driven by ask/tell.
+#[derive(Debug, Clone)]
+pub enum Algorithm {
+    Ans(ANS),
+    AnsSort(AnsSorted),
+    Ansr(ANSR),
+    AnsrDpnm(AnsrDpnm),
+    De(DE),
+    Shade(SHADE),
+}
+
+impl Algorithm {
+    /// `params` must hold every parameter of the algorithm; `None` takes the defaults.
+    pub fn new(
+        name: &str,
+        params: Option<&BTreeMap<String, f32>>,
+        dims: usize,
+    ) -> Result<Algorithm, String> {
+        let grid = match name {
+            "ans" => ans_params(dims),
+            "ans_sort" => ans_sort_params(dims),
+            "ansr" => ansr_params(dims),
+            "ansr_dpnm" => ansr_dpnm_params(dims),
+            "de" => de_params(dims),
+            "shade" => shade_params(dims),
+            _ => {
+                return Err(format!(
+                    "unknown algorithm `{name}`, expected one of {}",
+                    ALGORITHMS.join(", ")
+                ));
+            }
+        };
+        let Some(params) = params else {
+            return Ok(match name {
+                "ans" => Algorithm::Ans(DEFAULT_ANS.clone()),
+                "ans_sort" => Algorithm::AnsSort(DEFAULT_ANS_SORT.clone()),
+                "ansr" => Algorithm::Ansr(DEFAULT_ANSR.clone()),
+                "ansr_dpnm" => Algorithm::AnsrDpnm(DEFAULT_ANSR_DPNM.clone()),
+                "de" => Algorithm::De(DEFAULT_DE.clone()),
+                _ => Algorithm::Shade(DEFAULT_SHADE.clone()),
+            });
+        };
+        let missing: Vec<&str> =
+            grid.keys().filter(|k| !params.contains_key(*k)).map(|k| k.as_str()).collect();
+        if !missing.is_empty() {
+            return Err(format!("missing parameters for `{name}`: {}", missing.join(", ")));
+        }
+        check_params(name, params)?;
+        Ok(match name {
+            "ans" => Algorithm::Ans(new_ans(params)),
+            "ans_sort" => Algorithm::AnsSort(new_ans_sort(params)),
+            "ansr" => Algorithm::Ansr(new_ansr(params)),
+            "ansr_dpnm" => Algorithm::AnsrDpnm(new_ansr_dpnm(params)),
+            "de" => Algorithm::De(new_de(params)),
+            _ => Algorithm::Shade(new_shade(params)),
+        })
+    }
+
+    pub fn find_infimum_population<E: PopulationEvaluator, R: Rng>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        stop_residual: f32,
+    ) -> OptimizerResult {
+        match self {
+            Algorithm::Ans(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+            Algorithm::AnsSort(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+            Algorithm::Ansr(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+            Algorithm::AnsrDpnm(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+            Algorithm::De(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+            Algorithm::Shade(o) => {
+                o.find_infimum_population(evaluator, bounds, maxiter, rng, false, stop_residual)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StudySpec {
+    pub algorithm: String,
+    pub params: Option<BTreeMap<String, f32>>,
+    pub bounds: Vec<[f32; 2]>,
+    pub seed: u64,
+    /// Evaluation budget of the optimizer.
+    pub maxiter: u64,
+}
+
+impl StudySpec {
+    pub fn to_json(&self) -> Json {
+        let params = match &self.params {
+            Some(params) => Json::object(params.iter().map(|(k, v)| (k.as_str(), Json::from(*v)))),
+            None => Json::Null,
+        };
+        let bounds = self.bounds.iter().map(|b| Json::from(&b[..])).collect::<Vec<_>>();
+        Json::object([
+            ("algorithm", Json::from(self.algorithm.as_str())),
+            ("params", params),
+            ("bounds", Json::from(bounds)),
+            ("seed", Json::from(self.seed)),
+            ("maxiter", Json::from(self.maxiter)),
+        ])
+    }
+
+    /// Reads the members of a `create` request or a snapshot.
+    pub fn from_json(json: &Json) -> Result<StudySpec, String> {
+        let algorithm = json
+            .get("algorithm")
+            .and_then(Json::as_str)
+            .ok_or("`algorithm` must be a string")?
+            .to_string();
+        let params = match json.get("params") {
+            None | Some(Json::Null) => None,
+            Some(params) => {
+                let members = params.as_object().ok_or("`params` must be an object")?;
+                let mut map = BTreeMap::new();
+                for (k, v) in members {
+                    let v = v.as_f64().ok_or_else(|| format!("parameter `{k}` must be a number"))?;
+                    map.insert(k.clone(), v as f32);
+                }
+                Some(map)
+            }
+        };
+        let bounds = json
+            .get("bounds")
+            .and_then(Json::as_array)
+            .ok_or("`bounds` must be an array of [min, max] pairs")?
+            .iter()
+            .map(|b| match b.as_array() {
+                Some([lo, hi]) => match (lo.as_f64(), hi.as_f64()) {
+                    (Some(lo), Some(hi)) if lo < hi => Ok([lo as f32, hi as f32]),
+                    _ => Err(format!("invalid bounds {b}")),
+                },
+                _ => Err(format!("invalid bounds {b}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if bounds.is_empty() {
+            return Err("`bounds` must not be empty".to_string());
+        }
+        let seed = match json.get("seed") {
+            None => 0,
+            Some(seed) => seed.as_u64().ok_or("`seed` must be a non-negative integer below 2^53")?,
+        };
+        let maxiter = json
+            .get("maxiter")
+            .and_then(Json::as_u64)
+            .filter(|&n| n > 0)
+            .ok_or("`maxiter` must be a positive integer")?;
+        Ok(StudySpec {
+            algorithm,
+            params,
+            bounds,
+            seed,
+            maxiter,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub id: u64,
+    pub x: Vec<f32>,
+}
+
+/// Hands populations to the study and waits for their values.
+struct ChannelEvaluator {
+    range_min: Vec<f32>,
+    range_max: Vec<f32>,
+    populations: Sender<Vec<f32>>,
+    values: Receiver<Vec<f32>>,
+    closed: bool,
+}
+
+impl PopulationEvaluator for ChannelEvaluator {
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        fitness.fill(f32::INFINITY);
+        if self.closed {
+            return;
+        }
+        let points = positions
+            .chunks_exact(self.range_min.len())
+            .flat_map(|p| fit_in_bounds(p, &self.range_min, &self.range_max))
+            .collect();
+        if self.populations.send(points).is_err() {
+            self.closed = true;
+            return;
+        }
+        match self.values.recv() {
+            Ok(values) => fitness.copy_from_slice(&values),
+            Err(_) => self.closed = true,
+        }
+    }
+
+    fn aborted(&self) -> bool {
+        self.closed
+    }
+}
+
+/// The population currently being evaluated.
+struct Population {
+    first_id: u64,
+    points: Vec<f32>,
+    values: Vec<Option<f32>>,
+    handed_out: Vec<bool>,
+}
+
+/// One optimization driven by ask/tell. Dropping it stops the worker thread.
+pub struct Study {
+    spec: StudySpec,
+    populations: Receiver<Vec<f32>>,
+    values: Sender<Vec<f32>>,
+    current: Option<Population>,
+    /// Values of all completed populations, in id order.
+    told: Vec<f32>,
+    best: Option<(Vec<f32>, f32)>,
+    done: bool,
+}
+
+impl Study {
+    pub fn new(spec: StudySpec) -> Result<Study, String> {
+        // The seed has to survive the snapshot
+        if spec.seed > MAX_INTEGER {
+            return Err("`seed` must be a non-negative integer below 2^53".to_string());
+        }
+        let algorithm = Algorithm::new(&spec.algorithm, spec.params.as_ref(), spec.bounds.len())?;
+        let (population_sender, populations) = mpsc::channel();
+        let (values, value_receiver) = mpsc::channel();
+        let (range_min, range_max) = split_bounds(&spec.bounds);
+        let bounds = spec.bounds.clone();
+        let (seed, maxiter) = (spec.seed, spec.maxiter);
+        thread::spawn(move || {
+            let mut evaluator = ChannelEvaluator {
+                range_min,
+                range_max,
+                populations: population_sender,
+                values: value_receiver,
+                closed: false,
+            };
+            let mut rng: DefaultRng = stream_rng(seed, 0);
+            // Studies run to the end of their budget
+            algorithm.find_infimum_population(&mut evaluator, &bounds, maxiter, &mut rng, f32::NEG_INFINITY);
+        });
+        Ok(Study {
+            spec,
+            populations,
+            values,
+            current: None,
+            told: Vec::new(),
+            best: None,
+            done: false,
+        })
+    }
+
+    pub fn spec(&self) -> &StudySpec {
+        &self.spec
+    }
+
+    /// Waits for the next population unless one is pending or the optimizer finished.
+    fn fetch(&mut self) {
+        if self.current.is_some() || self.done {
+            return;
+        }
+        match self.populations.recv() {
+            Ok(points) => {
+                let count = points.len() / self.spec.bounds.len();
+                self.current = Some(Population {
+                    first_id: self.told.len() as u64,
+                    points,
+                    values: vec![None; count],
+                    handed_out: vec![false; count],
+                });
+            }
+            Err(_) => self.done = true,
+        }
+    }
+
+    /// Up to `count` candidates not handed out before.
+    pub fn ask(&mut self, count: usize) -> Vec<Candidate> {
+        self.fetch();
+        let dims = self.spec.bounds.len();
+        let Some(population) = &mut self.current else {
+            return Vec::new();
+        };
+        let mut candidates = Vec::new();
+        for i in 0..population.values.len() {
+            if candidates.len() == count {
+                break;
+            }
+            if population.values[i].is_none() && !population.handed_out[i] {
+                population.handed_out[i] = true;
+                candidates.push(Candidate {
+                    id: population.first_id + i as u64,
+                    x: population.points[i * dims..(i + 1) * dims].to_vec(),
+                });
+            }
+        }
+        candidates
+    }
+
+    /// Records the value of candidate `id`; NaN counts as a failed evaluation.
+    pub fn tell(&mut self, id: u64, value: f32) -> Result<(), String> {
+        self.fetch();
+        let dims = self.spec.bounds.len();
+        let Some(population) = &mut self.current else {
+            return Err(format!("candidate {id} is not pending, the study is done"));
+        };
+        let index = id
+            .checked_sub(population.first_id)
+            .map(|i| i as usize)
+            .filter(|&i| i < population.values.len())
+            .ok_or_else(|| format!("candidate {id} is not pending"))?;
+        if population.values[index].is_some() {
+            return Err(format!("candidate {id} was already told"));
+        }
+        let value = if value.is_nan() { f32::INFINITY } else { value };
+        population.values[index] = Some(value);
+        if self.best.as_ref().is_none_or(|(_, best)| value < *best) {
+            self.best = Some((population.points[index * dims..(index + 1) * dims].to_vec(), value));
+        }
+        if population.values.iter().all(Option::is_some) {
+            let values: Vec<f32> = population.values.iter().map(|v| v.unwrap()).collect();
+            self.told.extend(&values);
+            self.current = None;
+            if self.values.send(values).is_err() {
+                self.done = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Best candidate told so far.
+    pub fn best(&self) -> Option<(&[f32], f32)> {
+        self.best.as_ref().map(|(x, value)| (&x[..], *value))
+    }
+
+    pub fn evaluations(&self) -> u64 {
+        let pending = self.current.as_ref().map_or(0, |p| p.values.iter().flatten().count());
+        (self.told.len() + pending) as u64
+    }
+
+    /// True once the optimizer has used its budget and no candidates are left.
+    pub fn is_done(&mut self) -> bool {
+        self.fetch();
+        self.done
+    }
+
+    /// The spec and every value told so far.
+    pub fn to_json(&self) -> Json {
+        let told: Vec<Json> = self.told.iter().map(|v| Json::from(*v)).collect();
+        let partial: Vec<Json> = match &self.current {
+            Some(population) => population
+                .values
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| {
+                    let id = population.first_id + i as u64;
+                    v.map(|v| Json::from(vec![Json::from(id), Json::from(v)]))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Json::object([
+            ("spec", self.spec.to_json()),
+            ("told", Json::from(told)),
+            ("partial", Json::from(partial)),
+        ])
+    }
+
+    /// Restores a study by replaying its values through a fresh optimizer.
+    pub fn from_json(json: &Json) -> Result<Study, String> {
+        let spec = StudySpec::from_json(json.get("spec").ok_or("missing `spec`")?)?;
+        let mut study = Study::new(spec)?;
+        let told = json.get("told").and_then(Json::as_array).ok_or("`told` must be an array")?;
+        for (id, value) in told.iter().enumerate() {
+            let value = value.as_f32().ok_or("`told` must hold numbers")?;
+            study.tell(id as u64, value)?;
+        }
+        let partial = json.get("partial").and_then(Json::as_array).unwrap_or(&[]);
+        for entry in partial {
+            let (id, value) = entry
+                .as_array()
+                .and_then(|e| match e {
+                    [id, value] => Some((id.as_u64()?, value.as_f32()?)),
+                    _ => None,
+                })
+                .ok_or("`partial` must hold [id, value] pairs")?;
+            study.tell(id, value)?;
+        }
+        Ok(study)
+    }
+}
+
+struct Entry {
+    study: Study,
+    /// Values told since the last snapshot, when persisted.
+    log: Option<File>,
+}
+
+/// Named studies behind the line protocol described in the module docs.
+pub struct StudyServer {
+    dir: Option<PathBuf>,
+    studies: Mutex<BTreeMap<String, Arc<Mutex<Entry>>>>,
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && !name.starts_with('.')
+}
+
+fn error(message: impl Into<String>) -> Json {
+    Json::object([("ok", Json::from(false)), ("error", Json::from(message.into()))])
+}
+
+impl StudyServer {
+    /// Without a directory studies live only in memory; with one, the studies
+    /// saved there are restored.
+    pub fn open(dir: Option<&Path>) -> Result<StudyServer, String> {
+        let mut studies = BTreeMap::new();
+        if let Some(dir) = dir {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+            let entries = fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+            for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+                if path.extension().is_none_or(|e| e != "json") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let entry = StudyServer::restore(&path, &path.with_extension("tells"))
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+                studies.insert(name.to_string(), Arc::new(Mutex::new(entry)));
+            }
+        }
+        Ok(StudyServer {
+            dir: dir.map(Path::to_path_buf),
+            studies: Mutex::new(studies),
+        })
+    }
+
+    fn restore(snapshot: &Path, log: &Path) -> Result<Entry, String> {
+        let source = fs::read_to_string(snapshot).map_err(|e| e.to_string())?;
+        let mut study = Study::from_json(&Json::parse(&source)?)?;
+        let in_snapshot = study.evaluations();
+        if let Ok(file) = File::open(log) {
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| e.to_string())?;
+                let mut fields = line.split_whitespace();
+                // A torn last line from a crash is dropped
+                let (Some(Ok(sequence)), Some(Ok(id)), Some(Ok(value))) = (
+                    fields.next().map(str::parse::<u64>),
+                    fields.next().map(str::parse),
+                    fields.next().map(str::parse),
+                ) else {
+                    break;
+                };
+                if sequence >= in_snapshot {
+                    study.tell(id, value)?;
+                }
+            }
+        }
+        let log = OpenOptions::new().create(true).append(true).open(log).map_err(|e| e.to_string())?;
+        Ok(Entry {
+            study,
+            log: Some(log),
+        })
+    }
+
+    fn paths(&self, name: &str) -> Option<(PathBuf, PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        Some((dir.join(format!("{name}.json")), dir.join(format!("{name}.tells"))))
+    }
+
+    /// Writes a snapshot and starts an empty log. A crash in between leaves
+    /// the old log, whose lines `restore` finds in the snapshot.
+    fn save(&self, name: &str, entry: &mut Entry) -> Result<(), String> {
+        let Some((snapshot, log)) = self.paths(name) else {
+            return Ok(());
+        };
+        let temp = snapshot.with_extension("json.tmp");
+        fs::write(&temp, entry.study.to_json().to_string()).map_err(|e| e.to_string())?;
+        fs::rename(&temp, &snapshot).map_err(|e| e.to_string())?;
+        entry.log = Some(File::create(&log).map_err(|e| e.to_string())?);
+        Ok(())
+    }
+
+    fn study(&self, request: &Json) -> Result<(String, Arc<Mutex<Entry>>), String> {
+        let name = request.get("study").and_then(Json::as_str).ok_or("`study` must be a string")?;
+        let entry = self.studies.lock().unwrap().get(name).cloned();
+        entry
+            .map(|entry| (name.to_string(), entry))
+            .ok_or_else(|| format!("unknown study `{name}`"))
+    }
+
+    /// Answers one request line.
+    pub fn handle(&self, line: &str) -> String {
+        let response = match Json::parse(line) {
+            Ok(request) => match self.dispatch(&request) {
+                Ok(members) => {
+                    let mut members = members;
+                    members.insert("ok".to_string(), Json::from(true));
+                    Json::Object(members)
+                }
+                Err(message) => error(message),
+            },
+            Err(message) => error(format!("invalid JSON: {message}")),
+        };
+        response.to_string()
+    }
+
+    fn dispatch(&self, request: &Json) -> Result<BTreeMap<String, Json>, String> {
+        let op = request.get("op").and_then(Json::as_str).ok_or("`op` must be a string")?;
+        let mut response = BTreeMap::new();
+        match op {
+            "create" => {
+                let name = request.get("study").and_then(Json::as_str).ok_or("`study` must be a string")?;
+                if !valid_name(name) {
+                    return Err(format!("invalid study name `{name}`"));
+                }
+                let study = Study::new(StudySpec::from_json(request)?)?;
+                let mut studies = self.studies.lock().unwrap();
+                if studies.contains_key(name) {
+                    return Err(format!("study `{name}` already exists"));
+                }
+                let mut entry = Entry { study, log: None };
+                self.save(name, &mut entry)?;
+                studies.insert(name.to_string(), Arc::new(Mutex::new(entry)));
+            }
+            "ask" => {
+                let (_, entry) = self.study(request)?;
+                let count = match request.get("count") {
+                    None => 1,
+                    Some(count) => count.as_u64().ok_or("`count` must be a non-negative integer")? as usize,
+                };
+                let mut entry = entry.lock().unwrap();
+                let candidates = entry.study.ask(count);
+                let candidates = candidates
+                    .into_iter()
+                    .map(|c| Json::object([("id", Json::from(c.id)), ("x", Json::from(&c.x[..]))]))
+                    .collect::<Vec<_>>();
+                response.insert("candidates".to_string(), Json::from(candidates));
+                response.insert("done".to_string(), Json::from(entry.study.is_done()));
+            }
+            "tell" => {
+                let (_, entry) = self.study(request)?;
+                let results = request
+                    .get("results")
+                    .and_then(Json::as_array)
+                    .ok_or("`results` must be an array")?;
+                let mut entry = entry.lock().unwrap();
+                let mut told = 0u64;
+                let mut outcome = Ok(());
+                for result in results {
+                    let id = result.get("id").and_then(Json::as_u64);
+                    let value = result.get("value").and_then(Json::as_f32);
+                    let (Some(id), Some(value)) = (id, value) else {
+                        outcome = Err(format!("invalid result {result}"));
+                        break;
+                    };
+                    let sequence = entry.study.evaluations();
+                    if let Err(message) = entry.study.tell(id, value) {
+                        outcome = Err(message);
+                        break;
+                    }
+                    if let Some(log) = &mut entry.log {
+                        writeln!(log, "{sequence} {id} {value}").map_err(|e| e.to_string())?;
+                    }
+                    told += 1;
+                }
+                // Results before a bad one stay told
+                outcome.map_err(|message| format!("{message} ({told} told)"))?;
+                response.insert("told".to_string(), Json::from(told));
+            }
+            "best" => {
+                let (_, entry) = self.study(request)?;
+                let mut entry = entry.lock().unwrap();
+                let (x, value) = match entry.study.best() {
+                    Some((x, value)) => (Json::from(x), Json::from(value)),
+                    None => (Json::Null, Json::Null),
+                };
+                response.insert("x".to_string(), x);
+                response.insert("value".to_string(), value);
+                response.insert("evaluations".to_string(), Json::from(entry.study.evaluations()));
+                response.insert("done".to_string(), Json::from(entry.study.is_done()));
+            }
+            "checkpoint" => {
+                let names = match request.get("study") {
+                    Some(_) => vec![self.study(request)?],
+                    None => self.studies.lock().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                };
+                for (name, entry) in &names {
+                    self.save(name, &mut entry.lock().unwrap())?;
+                }
+                response.insert("saved".to_string(), Json::from(names.len()));
+            }
+            "list" => {
+                let studies = self.studies.lock().unwrap().clone();
+                let list = studies
+                    .iter()
+                    .map(|(name, entry)| {
+                        let mut entry = entry.lock().unwrap();
+                        Json::object([
+                            ("study", Json::from(name.as_str())),
+                            ("algorithm", Json::from(entry.study.spec().algorithm.as_str())),
+                            ("evaluations", Json::from(entry.study.evaluations())),
+                            ("done", Json::from(entry.study.is_done())),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                response.insert("studies".to_string(), Json::from(list));
+            }
+            "delete" => {
+                let (name, _) = self.study(request)?;
+                self.studies.lock().unwrap().remove(&name);
+                if let Some((snapshot, log)) = self.paths(&name) {
+                    let _ = fs::remove_file(snapshot);
+                    let _ = fs::remove_file(log);
+                }
+            }
+            _ => return Err(format!("unknown op `{op}`")),
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere(x: &[f32]) -> f32 {
+        x.iter().map(|v| v * v).sum()
+    }
+
+    fn spec(algorithm: &str, maxiter: u64) -> StudySpec {
+        StudySpec {
+            algorithm: algorithm.to_string(),
+            params: None,
+            bounds: vec![[-5.0, 5.0]; 8],
+            seed: 3,
+            maxiter,
+        }
+    }
+
+    #[test]
+    fn test_ask_tell_minimizes() {
+        let mut study = Study::new(spec("ansr", 5_000)).unwrap();
+        let mut next_id = 0;
+        while !study.is_done() {
+            let candidates = study.ask(7);
+            for c in candidates {
+                assert_eq!(c.id, next_id);
+                next_id += 1;
+                study.tell(c.id, sphere(&c.x)).unwrap();
+            }
+        }
+        assert!(study.evaluations() >= 5_000);
+        let (x, value) = study.best().unwrap();
+        assert_eq!(value, sphere(x));
+        assert!(value < 0.1, "{value}");
+        assert!(study.ask(1).is_empty());
+    }
+
+    #[test]
+    fn test_restored_study_continues_identically() {
+        let mut study = Study::new(spec("de", 2_000)).unwrap();
+        for _ in 0..10 {
+            for c in study.ask(5) {
+                study.tell(c.id, sphere(&c.x)).unwrap();
+            }
+        }
+        // Leave the current population half told
+        let pending = study.ask(usize::MAX);
+        let (told, untold) = pending.split_at(pending.len() / 2);
+        for c in told {
+            study.tell(c.id, sphere(&c.x)).unwrap();
+        }
+        let json = Json::parse(&study.to_json().to_string()).unwrap();
+        let mut restored = Study::from_json(&json).unwrap();
+        assert_eq!(restored.evaluations(), study.evaluations());
+        assert_eq!(restored.best(), study.best());
+        assert_eq!(restored.ask(usize::MAX), untold);
+        for c in untold {
+            study.tell(c.id, sphere(&c.x)).unwrap();
+            restored.tell(c.id, sphere(&c.x)).unwrap();
+        }
+        assert_eq!(restored.ask(3), study.ask(3));
+    }
+
+    #[test]
+    fn test_tell_rejects_unknown_and_repeated_ids() {
+        let mut study = Study::new(spec("ans", 1_000)).unwrap();
+        let c = study.ask(1).remove(0);
+        study.tell(c.id, f32::NAN).unwrap();
+        assert!(study.tell(c.id, 1.0).unwrap_err().contains("already told"));
+        assert!(study.tell(10_000, 1.0).unwrap_err().contains("not pending"));
+        assert_eq!(study.best().unwrap().1, f32::INFINITY);
+    }
+
+    #[test]
+    fn test_algorithm_params_are_validated() {
+        assert!(Algorithm::new("cmaes", None, 4).unwrap_err().contains("unknown algorithm"));
+        let params = BTreeMap::from([("popsize".to_string(), 10.0)]);
+        assert!(Algorithm::new("de", Some(&params), 4).unwrap_err().contains("missing parameters"));
+        for name in ALGORITHMS {
+            assert!(Algorithm::new(name, None, 4).is_ok());
+        }
+        let params = BTreeMap::from([("popsize".to_string(), 3.0), ("f".to_string(), 0.5), ("cr".to_string(), 0.5)]);
+        assert!(Algorithm::new("de", Some(&params), 4).unwrap_err().contains("popsize"));
+    }
+
+    struct SphereEvaluator(usize);
+
+    impl PopulationEvaluator for SphereEvaluator {
+        fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+            for (x, f) in positions.chunks_exact(self.0).zip(fitness) {
+                *f = sphere(x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_smallest_valid_inputs_run() {
+        for name in ALGORITHMS {
+            let grid = match name {
+                "ans" => ans_params(4),
+                "ans_sort" => ans_sort_params(4),
+                "ansr" => ansr_params(4),
+                "ansr_dpnm" => ansr_dpnm_params(4),
+                "de" => de_params(4),
+                _ => shade_params(4),
+            };
+            // The first value of every parameter and the smallest accepted popsize
+            let mut params: BTreeMap<String, f32> = grid.into_iter().map(|(k, v)| (k, v[0])).collect();
+            let popsize = (2..).map(|p| p as f32).find(|&p| {
+                params.insert("popsize".to_string(), p);
+                check_params(name, &params).is_ok()
+            });
+            params.insert("popsize".to_string(), popsize.unwrap());
+            for dims in [1, 3] {
+                let algorithm = Algorithm::new(name, Some(&params), dims).unwrap();
+                let mut rng: DefaultRng = stream_rng(0, 0);
+                let result =
+                    algorithm.find_infimum_population(&mut SphereEvaluator(dims), &vec![[-1.0, 1.0]; dims], 200, &mut rng, 0.0);
+                assert!(result.f_x.is_finite(), "{name} in {dims}D");
+            }
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ask_tell_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn request(server: &StudyServer, line: &str) -> Json {
+        Json::parse(&server.handle(line)).unwrap()
+    }
+
+    fn answer(server: &StudyServer, study: &str, count: usize) -> usize {
+        let response = request(server, &format!(r#"{{"op":"ask","study":"{study}","count":{count}}}"#));
+        let candidates = response.get("candidates").unwrap().as_array().unwrap();
+        let results = candidates
+            .iter()
+            .map(|c| {
+                let x: Vec<f32> =
+                    c.get("x").unwrap().as_array().unwrap().iter().map(|v| v.as_f32().unwrap()).collect();
+                Json::object([("id", c.get("id").unwrap().clone()), ("value", Json::from(sphere(&x)))])
+            })
+            .collect::<Vec<_>>();
+        let tell = Json::object([
+            ("op", Json::from("tell")),
+            ("study", Json::from(study)),
+            ("results", Json::from(results)),
+        ]);
+        let response = request(server, &tell.to_string());
+        assert_eq!(response.get("ok"), Some(&Json::Bool(true)), "{response}");
+        candidates.len()
+    }
+
+    #[test]
+    fn test_server_persists_studies() {
+        let dir = temp_dir("persist");
+        let server = StudyServer::open(Some(&dir)).unwrap();
+        let create = r#"{"op":"create","study":"a","algorithm":"shade","bounds":[[-5,5],[-5,5],[-5,5]],"seed":7,"maxiter":3000}"#;
+        assert_eq!(request(&server, create).get("ok"), Some(&Json::Bool(true)));
+        let create_b = create.replace("\"a\"", "\"b\"").replace("shade", "ansr");
+        assert_eq!(request(&server, &create_b).get("ok"), Some(&Json::Bool(true)));
+        assert!(request(&server, create).get("error").unwrap().as_str().unwrap().contains("exists"));
+
+        for _ in 0..20 {
+            answer(&server, "a", 4);
+            answer(&server, "b", 3);
+        }
+        // Half of the tells go to the snapshot, the rest only to the log
+        assert_eq!(request(&server, r#"{"op":"checkpoint","study":"a"}"#).get("saved"), Some(&Json::Number(1.0)));
+        for _ in 0..5 {
+            answer(&server, "a", 4);
+        }
+        let best = request(&server, r#"{"op":"best","study":"a"}"#);
+        let next = request(&server, r#"{"op":"ask","study":"a","count":2}"#);
+        drop(server);
+
+        let server = StudyServer::open(Some(&dir)).unwrap();
+        assert_eq!(request(&server, r#"{"op":"best","study":"a"}"#), best);
+        // Handed-out candidates are not persisted, so they are handed out again
+        assert_eq!(request(&server, r#"{"op":"ask","study":"a","count":2}"#), next);
+        let list = request(&server, r#"{"op":"list"}"#);
+        assert_eq!(list.get("studies").unwrap().as_array().unwrap().len(), 2);
+
+        assert_eq!(request(&server, r#"{"op":"delete","study":"b"}"#).get("ok"), Some(&Json::Bool(true)));
+        assert!(!dir.join("b.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_log_left_by_crashed_checkpoint_is_skipped() {
+        let dir = temp_dir("crash");
+        let server = StudyServer::open(Some(&dir)).unwrap();
+        let create = r#"{"op":"create","study":"a","algorithm":"de","bounds":[[-5,5],[-5,5]],"seed":9007199254740991,"maxiter":2000}"#;
+        assert_eq!(request(&server, create).get("ok"), Some(&Json::Bool(true)));
+        for _ in 0..10 {
+            answer(&server, "a", 3);
+        }
+        let log = fs::read_to_string(dir.join("a.tells")).unwrap();
+        request(&server, r#"{"op":"checkpoint","study":"a"}"#);
+        answer(&server, "a", 3);
+        let best = request(&server, r#"{"op":"best","study":"a"}"#);
+        drop(server);
+        // As if the server died after renaming the snapshot, before emptying the log
+        let after = fs::read_to_string(dir.join("a.tells")).unwrap();
+        fs::write(dir.join("a.tells"), log + &after).unwrap();
+
+        let server = StudyServer::open(Some(&dir)).unwrap();
+        assert_eq!(request(&server, r#"{"op":"best","study":"a"}"#), best);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_server_reports_errors() {
+        let server = StudyServer::open(None).unwrap();
+        let message = |line: &str| request(&server, line).get("error").unwrap().as_str().unwrap().to_string();
+        assert!(message("{").contains("invalid JSON"));
+        assert!(message(r#"{"op":"fly"}"#).contains("unknown op"));
+        assert!(message(r#"{"op":"ask","study":"x"}"#).contains("unknown study"));
+        assert!(message(r#"{"op":"create","study":"../x","algorithm":"de","bounds":[[0,1]],"maxiter":10}"#)
+            .contains("invalid study name"));
+        assert!(message(r#"{"op":"create","study":"x","algorithm":"de","bounds":[[1,0]],"maxiter":10}"#)
+            .contains("invalid bounds"));
+        assert!(message(r#"{"op":"create","study":"x","algorithm":"de","bounds":[[0,1]]}"#).contains("maxiter"));
+        assert!(message(r#"{"op":"create","study":"x","algorithm":"de","bounds":[[0,1]],"maxiter":10,"seed":9007199254740993}"#)
+            .contains("below 2^53"));
+        request(&server, r#"{"op":"create","study":"x","algorithm":"de","bounds":[[0,1]],"maxiter":10}"#);
+        assert!(message(r#"{"op":"tell","study":"x","results":[{"id":99,"value":1}]}"#).contains("not pending"));
+    }
+
+    #[test]
+    fn test_concurrent_studies() {
+        let server = Arc::new(StudyServer::open(None).unwrap());
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let server = server.clone();
+                thread::spawn(move || {
+                    let create = format!(
+                        r#"{{"op":"create","study":"s{i}","algorithm":"ansr","bounds":[[-5,5],[-5,5]],"seed":{i},"maxiter":2000}}"#
+                    );
+                    server.handle(&create);
+                    while answer(&server, &format!("s{i}"), 8) > 0 {}
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        for i in 0..4 {
+            let best = request(&server, &format!(r#"{{"op":"best","study":"s{i}"}}"#));
+            assert_eq!(best.get("done"), Some(&Json::Bool(true)));
+            assert!(best.get("value").unwrap().as_f64().unwrap() < 0.1, "{best}");
+        }
+    }
+}
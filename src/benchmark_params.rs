@@ -1,4 +1,9 @@
-use crate::algorithms::{ans::ANS, ans_sort::AnsSorted, ansr::ANSR, ansr_dpnm::AnsrDpnm, de::DE, shade::SHADE, zero_gradient::ZeroGradient};
+use std::collections::BTreeMap;
+
+use crate::{
+    algorithms::{ans::ANS, ans_sort::AnsSorted, ansr::ANSR, ansr_dpnm::AnsrDpnm, de::DE, shade::SHADE, zero_gradient::ZeroGradient},
+    default_algorithms_params::check_params,
+};
 
 pub struct BenchmarkParams {
     pub ans: ANS,
@@ -187,6 +192,95 @@ pub fn get_params(test_set: &str, dim: usize) -> BenchmarkParams {
         "lmmaes" => default_params(),
         "hard" => default_params(),
         "medium_weierstrass" => default_params(),
+        "lmmaes_rotated" => default_params(),
+        "coupled" => default_params(),
+        "bbob" => default_params(),
+        "cec2017" => default_params(),
+        "cec2022" => default_params(),
+        "expr" => default_params(),
         _ => default_params(),
     }
 }
+
+/// Names of the algorithms in `BenchmarkParams`, in benchmark order.
+pub const BENCHMARK_ALGORITHMS: [&str; 7] = ["ans", "ans_sort", "ansr", "ansr_dpnm", "de", "shade", "zero_gradient"];
+
+impl BenchmarkParams {
+    /// Replaces parameters of `algorithm` by `overrides`, named as in its
+    /// `new_*` constructor. Unknown algorithms or parameters, and values the
+    /// algorithm cannot run with (such as a fractional `popsize`), are errors.
+    pub fn apply_overrides(&mut self, algorithm: &str, overrides: &BTreeMap<String, f32>) -> Result<(), String> {
+        if !BENCHMARK_ALGORITHMS.contains(&algorithm) {
+            return Err(format!("unknown algorithm `{algorithm}`, expected one of {}", BENCHMARK_ALGORITHMS.join(", ")));
+        }
+        check_params(algorithm, overrides)?;
+        for (key, &v) in overrides {
+            match (algorithm, key.as_str()) {
+                ("ans", "popsize") => self.ans.popsize = v as usize,
+                ("ans", "sigma") => self.ans.sigma = v,
+                ("ans", "self_instead_neighbour") => self.ans.self_instead_neighbour = v,
+                ("ans_sort", "popsize") => self.ans_sort.popsize = v as usize,
+                ("ans_sort", "sigma") => self.ans_sort.sigma = v,
+                ("ans_sort", "self_instead_neighbour") => self.ans_sort.self_instead_neighbour = v,
+                ("ansr", "popsize") => self.ansr.popsize = v as usize,
+                ("ansr", "restart_tolerance") => self.ansr.restart_tolerance = v,
+                ("ansr", "sigma") => self.ansr.sigma = v,
+                ("ansr", "self_instead_neighbour") => self.ansr.self_instead_neighbour = v,
+                ("ansr_dpnm", "popsize") => self.ansr_dpnm.popsize = v as usize,
+                ("ansr_dpnm", "restart_tolerance") => self.ansr_dpnm.restart_tolerance = v,
+                ("ansr_dpnm", "sigma") => self.ansr_dpnm.sigma = v,
+                ("ansr_dpnm", "self_instead_neighbour") => self.ansr_dpnm.self_instead_neighbour = v,
+                ("ansr_dpnm", "restart_decay_power") => self.ansr_dpnm.restart_decay_power = v,
+                ("ansr_dpnm", "neighbour_multiplier") => self.ansr_dpnm.neighbour_multiplier = v,
+                ("de", "popsize") => self.de.popsize = v as usize,
+                ("de", "f") => self.de.f = v,
+                ("de", "cr") => self.de.cr = v,
+                ("shade", "popsize") => self.shade.popsize = v as usize,
+                ("shade", "h") => self.shade.h = v as usize,
+                ("shade", "p_best_rate") => self.shade.p_best_rate = v,
+                ("zero_gradient", "init_jump") => self.zero_gradient.init_jump = v,
+                _ => return Err(format!("unknown parameter `{key}` for `{algorithm}`")),
+            }
+        }
+        Ok(())
+    }
+
+    /// All parameters of `algorithm` under the names of `apply_overrides`.
+    pub fn params(&self, algorithm: &str) -> BTreeMap<String, f32> {
+        let params: Vec<(&str, f32)> = match algorithm {
+            "ans" => vec![
+                ("popsize", self.ans.popsize as f32),
+                ("sigma", self.ans.sigma),
+                ("self_instead_neighbour", self.ans.self_instead_neighbour),
+            ],
+            "ans_sort" => vec![
+                ("popsize", self.ans_sort.popsize as f32),
+                ("sigma", self.ans_sort.sigma),
+                ("self_instead_neighbour", self.ans_sort.self_instead_neighbour),
+            ],
+            "ansr" => vec![
+                ("popsize", self.ansr.popsize as f32),
+                ("restart_tolerance", self.ansr.restart_tolerance),
+                ("sigma", self.ansr.sigma),
+                ("self_instead_neighbour", self.ansr.self_instead_neighbour),
+            ],
+            "ansr_dpnm" => vec![
+                ("popsize", self.ansr_dpnm.popsize as f32),
+                ("restart_tolerance", self.ansr_dpnm.restart_tolerance),
+                ("sigma", self.ansr_dpnm.sigma),
+                ("self_instead_neighbour", self.ansr_dpnm.self_instead_neighbour),
+                ("restart_decay_power", self.ansr_dpnm.restart_decay_power),
+                ("neighbour_multiplier", self.ansr_dpnm.neighbour_multiplier),
+            ],
+            "de" => vec![("popsize", self.de.popsize as f32), ("f", self.de.f), ("cr", self.de.cr)],
+            "shade" => vec![
+                ("popsize", self.shade.popsize as f32),
+                ("h", self.shade.h as f32),
+                ("p_best_rate", self.shade.p_best_rate),
+            ],
+            "zero_gradient" => vec![("init_jump", self.zero_gradient.init_jump)],
+            _ => Vec::new(),
+        };
+        params.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
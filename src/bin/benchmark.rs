@@ -1,80 +1,490 @@
-use std::{collections::BTreeMap, fs::File, io::Write};
+use std::{collections::{BTreeMap, BTreeSet}, env, fs::{self, File}, io::Write, process, sync::Mutex};
+
+use indicatif::{ProgressBar, ProgressStyle};
 
 use optimizers::{
     benchmark_params::get_params,
-    functions::{EASY_TEST_FUNCTIONS, HARD_TEST_FUNCTIONS, HARD_DISCRETE_FUNCTIONS, MEDIUM_PERIODIC_FUNCTIONS, LMMAES_TEST_FUNCTIONS, MAIN_TEST_FUNCTIONS, TERRAIN_TEST_FUNCTIONS, MINI_TEST_FUNCTIONS, WEIERSTRASS_TEST_FUNCTIONS, TestFunction},
-    optimizer::Optimizer,
-    runner::{run_multiple_optimizations_detailed, SeedResult},
+    default_algorithms_params::{DEFAULT_MOEAD, DEFAULT_NSGA2},
+    experiment::{suite_problems, AlgorithmRun, Budget, Experiment, SuiteRun},
+    functions::{expression::ExpressionFunction, multi_objective::MultiObjectiveFunction, DTLZ_TEST_FUNCTIONS, ZDT_TEST_FUNCTIONS, Problem},
+    json::Json,
+    metadata::{metadata_path, write_metadata, RunClock},
+    optimizer::MultiObjectiveOptimizer,
+    report::parse_run,
+    runner::{run_multi_objective_optimizations, seed_job, Instance, SeedResult, Throughput},
+    scheduler::{run_jobs, set_thread_count, Job},
+    shard::{merge, open_append, parse_cell, Shard},
+    stats::{friedman_over_functions, holm, holm_against_best, mann_whitney, median, nemenyi_critical_difference, runtime_scores},
 };
 
-fn run_algo<T: Optimizer + Sync>(
-    name: &str,
-    test_set: &str,
-    dim: usize,
-    optimizer: &T,
-    functions: &BTreeMap<String, TestFunction>,
-    maxiter: u64,
-    csv: &mut File,
-) {
-    let results = run_multiple_optimizations_detailed(
-        optimizer, functions, dim, maxiter, 200, 0.01, true, true,
-    );
-    for r in &results {
-        writeln!(csv, "{},{},{},{},{},{},{}", test_set, dim, name, r.function, r.seed, r.f_x, r.nfev).unwrap();
-    }
-    let mut by_func: BTreeMap<String, (u64, u64)> = BTreeMap::new();
-    for r in &results {
-        let entry = by_func.entry(r.function.clone()).or_insert((0, 0));
-        if r.f_x <= 0.01 {
+/// Mean nfev per function over `results` of one algorithm, infinite unless
+/// every run succeeded.
+fn print_algo(name: &str, functions: &BTreeMap<String, &dyn Problem>, dim: usize, results: &[SeedResult]) {
+    let mut by_func: BTreeMap<String, (u64, u64, u64)> = BTreeMap::new();
+    for r in results {
+        let entry = by_func.entry(r.function.clone()).or_insert((0, 0, 0));
+        entry.2 += 1;
+        if r.error <= functions[&r.function].target(dim) {
             entry.0 += r.nfev;
             entry.1 += 1;
         }
     }
     print!("{name}:");
-    for (func, (total_nfev, count)) in &by_func {
-        if *count == 200 {
+    for (func, (total_nfev, count, runs)) in &by_func {
+        if count == runs {
             print!(" {func}={:.2}", *total_nfev as f32 / *count as f32);
         } else {
             print!(" {func}=inf");
         }
     }
+    let throughput = Throughput::of(results);
+    if throughput.runs > 0 {
+        print!(
+            " | {:.2e} evals/s, optimizer overhead {:.0}% ({:.2e} s/eval)",
+            throughput.evals_per_second(),
+            100.0 * throughput.overhead_fraction(),
+            throughput.overhead_per_eval()
+        );
+    }
     println!();
 }
 
-fn run_all(test_set: &str, functions: &BTreeMap<String, TestFunction>, dim: usize, maxiter: u64, csv: &mut File) {
-    let p = get_params(test_set, dim);
-    run_algo("ans", test_set, dim, &p.ans, functions, maxiter, csv);
-    run_algo("ans_sort", test_set, dim, &p.ans_sort, functions, maxiter, csv);
-    run_algo("ansr", test_set, dim, &p.ansr, functions, maxiter, csv);
-    run_algo("ansr_dpnm", test_set, dim, &p.ansr_dpnm, functions, maxiter, csv);
-    run_algo("de", test_set, dim, &p.de, functions, maxiter, csv);
-    run_algo("shade", test_set, dim, &p.shade, functions, maxiter, csv);
-    run_algo("zero_gradient", test_set, dim, &p.zero_gradient, functions, maxiter, csv);
+/// Per function, the algorithm with the best median runtime, starred when
+/// Mann–Whitney tests with Holm's correction find it better than every other
+/// at the 0.05 level; then the Friedman test over functions, listing the
+/// algorithms the best-ranked one significantly beats.
+fn print_winners(functions: &BTreeMap<String, &dyn Problem>, dim: usize, results: &BTreeMap<String, Vec<SeedResult>>) {
+    let solved = |r: &SeedResult| r.error <= functions[&r.function].target(dim);
+    let names: Vec<&String> = results.keys().collect();
+    print!("winners:");
+    for function in functions.keys() {
+        let groups: Vec<Vec<SeedResult>> = results
+            .values()
+            .map(|runs| runs.iter().filter(|r| &r.function == function).cloned().collect())
+            .collect();
+        let slices: Vec<&[SeedResult]> = groups.iter().map(|g| g.as_slice()).collect();
+        let scores = runtime_scores(&slices, solved);
+        let medians: Vec<f64> = scores.iter().map(|s| median(s)).collect();
+        let best = (0..medians.len()).min_by(|&a, &b| medians[a].total_cmp(&medians[b])).unwrap();
+        let p_values: Vec<f64> = (0..scores.len())
+            .filter(|&i| i != best)
+            .map(|i| mann_whitney(&scores[best], &scores[i]).p_value)
+            .collect();
+        let significant = holm(&p_values).iter().all(|&p| p < 0.05);
+        print!(" {function}={}{}", names[best], if significant { "*" } else { "" });
+    }
+    println!(" (* significant, p < 0.05)");
+
+    let test = friedman_over_functions(results, solved);
+    if test.blocks < 2 {
+        return;
+    }
+    print!("friedman: p={:.2e}, mean ranks", test.p_value);
+    for (name, rank) in names.iter().zip(&test.mean_ranks) {
+        print!(" {name}={rank:.2}");
+    }
+    if let Some(cd) = nemenyi_critical_difference(names.len(), test.blocks) {
+        print!(", nemenyi cd={cd:.2}");
+    }
+    let beaten: Vec<&str> = names
+        .iter()
+        .zip(holm_against_best(&test))
+        .filter(|&(_, p)| p < 0.05)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let best = (0..names.len()).min_by(|&a, &b| test.mean_ranks[a].total_cmp(&test.mean_ranks[b])).unwrap();
+    println!(", {} beats (holm): {}", names[best], if beaten.is_empty() { "none".to_string() } else { beaten.join(" ") });
 }
 
-fn main() {
-    let mut csv = File::create("benchmark_results.csv").unwrap();
-    writeln!(csv, "test_set,dim,algorithm,function,seed,f_x,nfev").unwrap();
+/// Runs every algorithm × function × dimension × seed of `runs` missing from
+/// `out` as one queue, writing each run as it finishes, then reports every
+/// suite and dimension. Returns the parameters of every algorithm per suite
+/// and dimension, for the metadata.
+fn run_experiment(algorithms: &[AlgorithmRun], runs: &[(SuiteRun, BTreeMap<String, &dyn Problem>)], mut out: Outputs) -> Json {
+    // Tuned parameters with the overrides, per suite, dimension and algorithm
+    let mut blocks = Vec::new();
+    for (run, problems) in runs {
+        for &dim in &run.dims {
+            for algorithm in algorithms {
+                let mut p = get_params(&run.suite, dim);
+                p.apply_overrides(&algorithm.algorithm, &algorithm.params).unwrap();
+                let key = (run.suite.clone(), dim, algorithm.label.clone());
+                let previous: Vec<SeedResult> = out
+                    .done
+                    .remove(&key)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|r| problems.contains_key(&r.function) && r.seed < run.seeds)
+                    .collect();
+                blocks.push((run, problems, dim, algorithm, p, previous));
+            }
+        }
+    }
+
+    // Objectives are built once per suite, dimension and function, and shared
+    // by the runs of every algorithm and seed
+    let instances: BTreeMap<(&str, usize), Vec<Instance>> = runs
+        .iter()
+        .flat_map(|(run, problems)| {
+            run.dims.iter().map(move |&dim| {
+                let instances = problems.iter().map(|(name, &function)| Instance::new(name, function, dim)).collect();
+                ((run.suite.as_str(), dim), instances)
+            })
+        })
+        .collect();
+
+    let shard = out.shard;
+    let out = Mutex::new(out);
+    let mut jobs: Vec<Job<(usize, SeedResult)>> = Vec::new();
+    for (block, (run, _, dim, algorithm, p, previous)) in blocks.iter().enumerate() {
+        let (dim, maxiter) = (*dim, run.budget.maxiter(*dim));
+        let done: BTreeSet<(&str, u64)> = previous.iter().map(|r| (r.function.as_str(), r.seed)).collect();
+        for instance in &instances[&(run.suite.as_str(), dim)] {
+            let name = instance.name.as_str();
+            for seed in (0..run.seeds).filter(|&seed| shard.contains(seed) && !done.contains(&(name, seed))) {
+                let job = match algorithm.algorithm.as_str() {
+                    "ans" => seed_job(&p.ans, instance, maxiter, seed, run.stop_residual),
+                    "ans_sort" => seed_job(&p.ans_sort, instance, maxiter, seed, run.stop_residual),
+                    "ansr" => seed_job(&p.ansr, instance, maxiter, seed, run.stop_residual),
+                    "ansr_dpnm" => seed_job(&p.ansr_dpnm, instance, maxiter, seed, run.stop_residual),
+                    "de" => seed_job(&p.de, instance, maxiter, seed, run.stop_residual),
+                    "shade" => seed_job(&p.shade, instance, maxiter, seed, run.stop_residual),
+                    "zero_gradient" => seed_job(&p.zero_gradient, instance, maxiter, seed, run.stop_residual),
+                    other => unreachable!("unknown algorithm {other}"),
+                };
+                let (out, test_set, name) = (&out, &run.suite, &algorithm.label);
+                jobs.push(job.map(move |mut r| {
+                    out.lock().unwrap().write(test_set, dim, name, &r);
+                    // Written; only the results are reported
+                    r.trace = Vec::new();
+                    (block, r)
+                }));
+            }
+        }
+    }
+
+    let pb = ProgressBar::new(jobs.len() as u64);
+    pb.set_style(
+        ProgressStyle::with_template("[{elapsed_precise}|{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+            .unwrap()
+            .progress_chars("##-"),
+    );
+    let mut results: Vec<Vec<SeedResult>> = blocks.iter().map(|block| block.5.clone()).collect();
+    for (block, r) in run_jobs(jobs, true, |(_, r)| {
+        pb.set_message(r.function.clone());
+        pb.inc(1);
+    }) {
+        results[block].push(r);
+    }
+    pb.finish_and_clear();
+    let parameters: Vec<Json> = blocks
+        .iter()
+        .map(|(run, _, dim, algorithm, p, _)| {
+            let params = p.params(&algorithm.algorithm).into_iter().map(|(k, v)| (k, Json::from(v)));
+            Json::object([
+                ("suite", Json::from(run.suite.as_str())),
+                ("dim", Json::from(*dim)),
+                ("algorithm", Json::from(algorithm.label.as_str())),
+                ("name", Json::from(algorithm.algorithm.as_str())),
+                ("maxiter", Json::from(run.budget.maxiter(*dim))),
+                ("params", Json::object(params)),
+            ])
+        })
+        .collect();
+
+    let mut blocks = blocks.iter().zip(results).peekable();
+    while let Some(((run, problems, dim, _, _, _), _)) = blocks.peek() {
+        let (run, problems, dim) = (*run, *problems, *dim);
+        println!("\n=== {} test {dim}D ===", run.suite);
+        let mut by_label = BTreeMap::new();
+        while let Some(((_, _, _, algorithm, _, _), mut results)) = blocks.next_if(|((r, _, d, _, _, _), _)| std::ptr::eq(*r, run) && *d == dim) {
+            results.sort_by(|a, b| (&a.function, a.seed).cmp(&(&b.function, b.seed)));
+            print_algo(&algorithm.label, problems, dim, &results);
+            by_label.insert(algorithm.label.clone(), results);
+        }
+        print_winners(problems, dim, &by_label);
+    }
+    Json::Array(parameters)
+}
+
+/// Writes the same metadata record next to each of `paths`.
+fn write_metadata_for(paths: &[&str], fields: Vec<(&str, Json)>, clock: &RunClock) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            let fields = std::iter::once(("file", Json::from(*path))).chain(fields.iter().cloned()).collect();
+            write_metadata(path, fields, clock).unwrap()
+        })
+        .collect()
+}
+
+const CSV_HEADER: &str = "test_set,dim,algorithm,function,seed,f_x,nfev,error,distance,wall_seconds,objective_seconds";
+const TRACE_HEADER: &str = "test_set,dim,algorithm,function,seed,nfev,best_f";
+
+/// Per-seed results and, in long format, their best-so-far traces.
+struct Outputs {
+    results: File,
+    traces: File,
+    shard: Shard,
+    /// Results already in the files, by test set, dimension and algorithm.
+    done: BTreeMap<(String, usize, String), Vec<SeedResult>>,
+}
 
-    for d in [64, 128, 256, 512, 1024] {
-        println!("\n=== easy test {d}D ===");
-        run_all("easy", &EASY_TEST_FUNCTIONS, d, 50_000, &mut csv);
+impl Outputs {
+    /// Empty files.
+    fn create(results_path: &str, traces_path: &str) -> Self {
+        fs::write(results_path, format!("{CSV_HEADER}\n")).unwrap();
+        fs::write(traces_path, format!("{TRACE_HEADER}\n")).unwrap();
+        Self::open(results_path, traces_path, Shard::ALL)
     }
 
-    for d in [64, 128, 256] {
-        println!("\n=== medium_terrain test {d}D ===");
-        run_all("medium_terrain", &TERRAIN_TEST_FUNCTIONS, d, 500_000, &mut csv);
+    /// Appends to the files of `shard`, keeping their complete cells.
+    fn open(results_path: &str, traces_path: &str, shard: Shard) -> Self {
+        let exit = |e: String| -> ! {
+            eprintln!("{e}");
+            process::exit(1);
+        };
+        let mut cells = BTreeSet::new();
+        let mut done: BTreeMap<_, Vec<SeedResult>> = BTreeMap::new();
+        // Every row is read before the file is rewritten with the current header
+        let check = |row: &str| {
+            let run = parse_cell(row).and_then(|(cell, fields)| Some((parse_run(&cell.3, cell.4, &fields)?, cell)));
+            let (run, cell) = run.ok_or_else(|| format!("{results_path}: invalid row {row:?}"))?;
+            done.entry((cell.0.clone(), cell.1, cell.2.clone())).or_default().push(run);
+            cells.insert(cell);
+            Ok(())
+        };
+        let (results, _) = open_append(results_path, CSV_HEADER, None, check).unwrap_or_else(|e| exit(e));
+        let (traces, _) = open_append(traces_path, TRACE_HEADER, Some(&cells), |_| Ok(())).unwrap_or_else(|e| exit(e));
+        let count: usize = done.values().map(Vec::len).sum();
+        if count > 0 {
+            println!("Resuming {results_path}: {count} runs done");
+        }
+        Self { results, traces, shard, done }
+    }
+}
+
+impl Outputs {
+    /// The trace rows of a run, then its results row, which completes it.
+    fn write(&mut self, test_set: &str, dim: usize, name: &str, r: &SeedResult) {
+        for (nfev, best_f) in &r.trace {
+            writeln!(self.traces, "{},{},{},{},{},{},{}", test_set, dim, name, r.function, r.seed, nfev, best_f).unwrap();
+        }
+        let distance = r.distance.map_or(String::new(), |d| d.to_string());
+        writeln!(
+            self.results,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            test_set, dim, name, r.function, r.seed, r.f_x, r.nfev, r.error, distance, r.wall_seconds, r.objective_seconds
+        )
+        .unwrap();
+    }
+}
+
+/// Joins the results and traces of shards `1..=count` and of any earlier
+/// unsharded run into the experiment's files, in a fixed order.
+fn merge_shards(experiment: &Experiment, count: u64) {
+    let clock = RunClock::start();
+    let shards: Vec<Shard> = (1..=count).map(|index| Shard { index, count }).collect();
+    let inputs = |path: &str| -> Vec<String> {
+        std::iter::once(path.to_string()).chain(shards.iter().map(|shard| shard.path(path))).collect()
+    };
+    let merged = merge(&inputs(&experiment.results), &experiment.results, CSV_HEADER, 0, None)
+        .and_then(|cells| merge(&inputs(&experiment.traces), &experiment.traces, TRACE_HEADER, 1, Some(&cells)).map(|_| cells));
+    match merged {
+        Ok(cells) => {
+            // The metadata of each shard as it ran
+            let shard_metadata = shards
+                .iter()
+                .map(|shard| {
+                    let path = metadata_path(&shard.path(&experiment.results));
+                    fs::read_to_string(&path).ok().and_then(|source| Json::parse(&source).ok()).unwrap_or(Json::Null)
+                })
+                .collect();
+            let fields = vec![
+                ("experiment", experiment.to_json()),
+                ("runs", Json::from(cells.len())),
+                ("shards", Json::Array(shard_metadata)),
+            ];
+            let meta = write_metadata_for(&[&experiment.results, &experiment.traces], fields, &clock);
+            println!("Merged {} runs of {count} shards into {}, {} and {}", cells.len(), experiment.results, experiment.traces, meta.join(", "));
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Removes `name <value>` from `args`, returning the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == name)?;
+    let Some(value) = args.get(i + 1).cloned() else {
+        eprintln!("Missing value after {name}");
+        process::exit(1);
+    };
+    args.drain(i..i + 2);
+    Some(value)
+}
+
+/// Benchmark one expression given as `<expression> <min> <max> [dim ...]`.
+fn run_expression(args: &[String]) {
+    let clock = RunClock::start();
+    let usage = || -> ! {
+        eprintln!("Usage: benchmark expr <expression> <min> <max> [dim ...]");
+        std::process::exit(1);
+    };
+    let [source, min, max, dims @ ..] = args else { usage() };
+    let (Ok(min), Ok(max)) = (min.parse(), max.parse()) else { usage() };
+    let function = ExpressionFunction::parse(source, [min, max]).unwrap_or_else(|e| {
+        eprintln!("Invalid expression: {e}");
+        std::process::exit(1);
+    });
+    let dims: Vec<usize> = if dims.is_empty() {
+        vec![16, 32, 64]
+    } else {
+        dims.iter().map(|d| d.parse().unwrap_or_else(|_| usage())).collect()
+    };
+    for &dim in &dims {
+        if let Err(e) = function.check_dims(dim) {
+            eprintln!("Invalid dimension: {e}");
+            std::process::exit(1);
+        }
+    }
+    let mut functions: BTreeMap<String, &dyn Problem> = BTreeMap::new();
+    functions.insert("expr".to_string(), &function);
+    let run = SuiteRun {
+        suite: "expr".to_string(),
+        dims,
+        budget: Budget::Fixed(500_000),
+        seeds: 200,
+        stop_residual: 0.01,
+    };
+    println!("Benchmarking {source}");
+    let experiment = Experiment {
+        results: "benchmark_expr_results.csv".to_string(),
+        traces: "benchmark_expr_traces.csv".to_string(),
+        algorithms: Experiment::default_benchmark().algorithms,
+        suites: vec![run.clone()],
+    };
+    let out = Outputs::create(&experiment.results, &experiment.traces);
+    let parameters = run_experiment(&experiment.algorithms, &[(run, functions)], out);
+    let fields = vec![
+        ("expression", Json::from(source.as_str())),
+        ("bounds", Json::from(vec![min, max])),
+        ("experiment", experiment.to_json()),
+        ("parameters", parameters),
+    ];
+    write_metadata_for(&[&experiment.results, &experiment.traces], fields, &clock);
+    println!("\nResults saved to {} and {}, with metadata", experiment.results, experiment.traces);
+}
+
+fn run_multi_algo<T: MultiObjectiveOptimizer + Sync>(
+    name: &str,
+    test_set: &str,
+    optimizer: &T,
+    problems: &BTreeMap<String, MultiObjectiveFunction>,
+    maxiter: u64,
+    csv: &mut File,
+) {
+    let results = run_multi_objective_optimizations(optimizer, problems, maxiter, 30, true);
+    for r in &results {
+        writeln!(csv, "{},{},{},{},{},{},{},{}", test_set, name, r.function, r.seed, r.nfev, r.front_size, r.hypervolume, r.igd).unwrap();
+    }
+    print!("{name}:");
+    for function in problems.keys() {
+        let igd: Vec<f32> = results.iter().filter(|r| &r.function == function).map(|r| r.igd).collect();
+        print!(" {function}={:.4}", igd.iter().sum::<f32>() / igd.len() as f32);
     }
+    println!(" (mean IGD)");
+}
 
-    for d in [16, 32, 64] {
-        println!("\n=== medium_periodic test {d}D ===");
-        run_all("medium_periodic", &MEDIUM_PERIODIC_FUNCTIONS, d, 500_000, &mut csv);
+/// Benchmark the multi-objective algorithms on the ZDT and DTLZ problems.
+fn run_multi() {
+    let clock = RunClock::start();
+    let mut csv = File::create("benchmark_multi_results.csv").unwrap();
+    writeln!(csv, "test_set,algorithm,function,seed,nfev,front_size,hypervolume,igd").unwrap();
+    for (test_set, problems) in [("zdt", &*ZDT_TEST_FUNCTIONS), ("dtlz", &*DTLZ_TEST_FUNCTIONS)] {
+        println!("\n=== {test_set} test ===");
+        run_multi_algo("nsga2", test_set, &DEFAULT_NSGA2, problems, 50_000, &mut csv);
+        run_multi_algo("moead", test_set, &DEFAULT_MOEAD, problems, 50_000, &mut csv);
     }
+    let algorithms = Json::object([
+        ("nsga2", Json::from(format!("{DEFAULT_NSGA2:?}"))),
+        ("moead", Json::from(format!("{DEFAULT_MOEAD:?}"))),
+    ]);
+    let fields = vec![
+        ("suites", Json::from(vec!["zdt", "dtlz"])),
+        ("maxiter", Json::from(50_000u64)),
+        ("seeds", Json::from(30u64)),
+        ("algorithms", algorithms),
+    ];
+    let meta = write_metadata_for(&["benchmark_multi_results.csv"], fields, &clock);
+    println!("\nResults saved to benchmark_multi_results.csv and {}", meta[0]);
+}
 
-    for d in [16, 32, 64] {
-        println!("\n=== hard_discrete test {d}D ===");
-        run_all("hard_discrete", &HARD_DISCRETE_FUNCTIONS, d, 500_000, &mut csv);
+// Usage: cargo run --bin benchmark -r -- [--config <experiment.json>] [--shard <i>/<n>] [--threads <n>]
+//        cargo run --bin benchmark -r -- merge <n> [--config <experiment.json>]
+//        cargo run --bin benchmark -r -- expr <expression> <min> <max> [dim ...]
+//        cargo run --bin benchmark -r -- multi
+// Without --config, runs the built-in experiment that benchmark.json describes; a
+// <results>.meta.json written by an earlier run also works as --config.
+// Appends to existing output and skips the runs already in it; with --shard,
+// runs every n-th seed into files of its own, which `merge <n>` joins.
+// All runs share one queue on at most --threads threads, largest first.
+fn main() {
+    let clock = RunClock::start();
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(threads) = take_option(&mut args, "--threads") {
+        let threads = threads.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid thread count: {threads}");
+            process::exit(1);
+        });
+        set_thread_count(threads).unwrap();
+    }
+    if args.get(1).map(|s| s.as_str()) == Some("expr") {
+        run_expression(&args[2..]);
+        return;
     }
+    if args.get(1).map(|s| s.as_str()) == Some("multi") {
+        run_multi();
+        return;
+    }
+
+    let experiment = match take_option(&mut args, "--config") {
+        Some(path) => Experiment::load(&path).unwrap_or_else(|e| {
+            eprintln!("Invalid experiment: {e}");
+            process::exit(1);
+        }),
+        None => Experiment::default_benchmark(),
+    };
+    if args.get(1).map(|s| s.as_str()) == Some("merge") {
+        let Some(Ok(count)) = args.get(2).map(|n| n.parse::<u64>()) else {
+            eprintln!("Usage: benchmark merge <n> [--config <experiment.json>]");
+            process::exit(1);
+        };
+        merge_shards(&experiment, count);
+        return;
+    }
+    let shard = take_option(&mut args, "--shard").map_or(Shard::ALL, |s| {
+        Shard::parse(&s).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1);
+        })
+    });
+
+    let (results, traces) = (shard.path(&experiment.results), shard.path(&experiment.traces));
+    let out = Outputs::open(&results, &traces, shard);
+    let resumed: usize = out.done.values().map(Vec::len).sum();
+    let runs: Vec<_> = experiment.suites.iter().map(|run| (run.clone(), suite_problems(&run.suite).unwrap())).collect();
+    let parameters = run_experiment(&experiment.algorithms, &runs, out);
 
-    println!("\nResults saved to benchmark_results.csv");
+    let fields = vec![
+        ("experiment", experiment.to_json()),
+        ("shard", Json::from(format!("{}/{}", shard.index, shard.count))),
+        // Runs from earlier sessions, which the timing does not cover
+        ("resumed_runs", Json::from(resumed)),
+        ("parameters", parameters),
+    ];
+    let meta = write_metadata_for(&[&results, &traces], fields, &clock);
+    println!("\nResults saved to {results} and {traces}, metadata to {}", meta.join(" and "));
 }
@@ -1,26 +1,58 @@
+use std::env;
+
 use optimizers::{
     default_algorithms_params::DEFAULT_ANSR,
     early_stop_callback::EarlyStopCallback,
-    functions::MAIN_TEST_FUNCTIONS,
+    functions::{expression::ExpressionFunction, Objective, Problem, MAIN_TEST_FUNCTIONS},
     optimizer::Optimizer,
     plot::save_video_h264,
     utils::{broadcast_scalar, broadcast_simd, format_best_f_x_history, format_x_history},
 };
 
+// Usage: cargo run --bin plot -r
+//        cargo run --bin plot -r -- <expression> <min> <max>
+// An expression is drawn as its 2D landscape (`n = 2`).
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
     let optimizer = &DEFAULT_ANSR;
-    let function = &MAIN_TEST_FUNCTIONS["megacity"];
     let dimension_count = 16;
     let maxiter = 100_000;
     let stop_residual = 0.01;
-    let func = &broadcast_simd(&function.func);
-    let bounds = &function.bounds.repeat(dimension_count / 2);
+    let expression = match &args[1..] {
+        [] => None,
+        [source, min, max] => Some(ExpressionFunction::parse(source, [min.parse()?, max.parse()?])?),
+        _ => return Err("usage: plot [<expression> <min> <max>]".into()),
+    };
+    if let Some(function) = &expression {
+        // The landscape is drawn over the first two dims
+        function.check_dims(2)?;
+    }
+    let (func, landscape, bounds): (Objective, Box<dyn Fn(&[f32]) -> f32 + Sync>, _) = match &expression {
+        Some(function) => (
+            function.objective(dimension_count),
+            Box::new(|x: &[f32]| function.expression.evaluate(x)),
+            function.bounds(dimension_count),
+        ),
+        None => {
+            let function = &MAIN_TEST_FUNCTIONS["megacity"];
+            let Some(pair) = function.func.pairwise() else {
+                return Err("plotting needs a pairwise function".into());
+            };
+            (
+                Box::new(broadcast_simd(pair)),
+                Box::new(broadcast_scalar(pair)),
+                function.bounds.repeat(dimension_count / 2),
+            )
+        }
+    };
+    let func = &func;
+    let bounds = &bounds;
     let early_stop_callback = EarlyStopCallback::new(func, stop_residual);
     let result = optimizer.find_infimum(func, bounds, maxiter, 42, true, &early_stop_callback);
     println!("f() == {:?}, nfev={}", result.f_x, result.nfev);
     if let Some(history) = result.history {
         save_video_h264(
-            &broadcast_scalar(&function.func),
+            &landscape,
             &format_x_history(&history.x, bounds),
             &format_best_f_x_history(&history.f_x),
             "result.mp4",
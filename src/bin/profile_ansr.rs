@@ -3,11 +3,11 @@ use std::time::Instant;
 use optimizers::{
     algorithms::ansr::ANSR,
     early_stop_callback::EarlyStopCallback,
-    functions::LMMAES_TEST_FUNCTIONS,
-    optimizer::Optimizer,
-    utils::broadcast_simd,
+    functions::{LMMAES_TEST_FUNCTIONS, MAIN_TEST_FUNCTIONS},
+    optimizer::{BatchOptimizer, Optimizer},
+    restart::RestartDetector,
+    utils::{broadcast_simd, broadcast_simd_batch},
 };
-
 fn main() {
     let optimizer = ANSR {
         popsize: 64,
@@ -20,7 +20,11 @@ fn main() {
     let stop_residual = 0.01;
 
     for (name, tf) in LMMAES_TEST_FUNCTIONS.iter() {
-        let func = broadcast_simd(tf.func);
+        let Some(pair) = tf.func.pairwise() else {
+            println!("{:20} skipped: not a pairwise function", name);
+            continue;
+        };
+        let func = broadcast_simd(pair);
         let bounds = tf.bounds.repeat(dim / 2);
         let early_stop = EarlyStopCallback::new(&func, stop_residual);
 
@@ -38,7 +42,11 @@ fn main() {
     // Now profile individual phases on sphere
     println!("\n--- Phase breakdown on sphere 1024D (1 epoch = 64 evals) ---");
     let tf = &LMMAES_TEST_FUNCTIONS["sphere"];
-    let func = broadcast_simd(tf.func);
+    let Some(pair) = tf.func.pairwise() else {
+        eprintln!("sphere is not a pairwise function");
+        std::process::exit(1);
+    };
+    let func = broadcast_simd(pair);
     let bounds = tf.bounds.repeat(dim / 2);
 
     // Time just function evals
@@ -130,4 +138,86 @@ fn main() {
         t_perturb.as_secs_f64() / t_eval.as_secs_f64()
     );
     println!("params (dim): {}", params);
+
+    // Compare evaluation layouts on a small-D suite where pair lanes are mostly idle
+    println!("\n--- Evaluation layout on MAIN 16D (no early stop) ---");
+    let small_dim = 16;
+    for (name, tf) in MAIN_TEST_FUNCTIONS.iter() {
+        let bounds = tf.bounds.repeat(small_dim / 2);
+        let Some(pair) = tf.func.pairwise() else {
+            println!("{:20} skipped: not a pairwise function", name);
+            continue;
+        };
+
+        let func = broadcast_simd(pair);
+        let early_stop = EarlyStopCallback::new(&func, f32::NEG_INFINITY);
+        let start = Instant::now();
+        let single = optimizer.find_infimum(&func, &bounds, maxiter, 0, false, &early_stop);
+        let t_single = start.elapsed();
+
+        let batch_func = broadcast_simd_batch(pair);
+        let start = Instant::now();
+        let batch =
+            optimizer.find_infimum_batch(&batch_func, &bounds, maxiter, 0, false, f32::NEG_INFINITY);
+        let t_batch = start.elapsed();
+
+        println!(
+            "{:20} single={:8.3}s batch={:8.3}s speedup={:.2}x  f_x single={:.4} batch={:.4}",
+            name,
+            t_single.as_secs_f64(),
+            t_batch.as_secs_f64(),
+            t_single.as_secs_f64() / t_batch.as_secs_f64(),
+            single.f_x,
+            batch.f_x
+        );
+    }
+
+    // Restart detection: reference pairwise scan vs sorted scan
+    println!("\n--- Restart detection (pairwise vs sorted) ---");
+    let restart_tolerance = optimizer.restart_tolerance;
+    let close = |min: f32, max: f32| max != 0.0 && (max - min) / max < restart_tolerance;
+    let mut rng = Pcg64Mcg::seed_from_u64(0);
+    for popsize in [64usize, 256, 1024, 4096] {
+        // A few shared basins plus many distinct residuals, as late in a run
+        let basin = Uniform::new(0, 8).unwrap();
+        let spread = Uniform::new(0.0f32, 1.0).unwrap();
+        let residuals: Vec<f32> = (0..popsize)
+            .map(|_| {
+                if spread.sample(&mut rng) < 0.25 {
+                    1.0 + basin.sample(&mut rng) as f32
+                } else {
+                    1.0 + 100.0 * spread.sample(&mut rng)
+                }
+            })
+            .collect();
+        let best = (0..popsize)
+            .min_by(|&a, &b| residuals[a].total_cmp(&residuals[b]))
+            .unwrap();
+        let repeats = usize::max(1, (1 << 24) / (popsize * popsize));
+        let mut detector = RestartDetector::new(popsize);
+
+        let start = Instant::now();
+        let mut pairwise_restarts = 0;
+        for _ in 0..repeats {
+            pairwise_restarts += detector.detect_pairwise(&residuals, best, close).len();
+        }
+        let t_pairwise = start.elapsed();
+
+        let start = Instant::now();
+        let mut sorted_restarts = 0;
+        for _ in 0..repeats {
+            sorted_restarts += detector.detect(&residuals, best, restart_tolerance, close).len();
+        }
+        let t_sorted = start.elapsed();
+
+        assert_eq!(pairwise_restarts, sorted_restarts);
+        println!(
+            "popsize={:5} pairwise={:9.2}us sorted={:9.2}us speedup={:.1}x restarts={}",
+            popsize,
+            t_pairwise.as_secs_f64() / repeats as f64 * 1e6,
+            t_sorted.as_secs_f64() / repeats as f64 * 1e6,
+            t_pairwise.as_secs_f64() / t_sorted.as_secs_f64(),
+            pairwise_restarts / repeats
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, env, error::Error, fs};
+
+use plotters::{coord::Shift, prelude::*};
+
+use optimizers::profiles::{data_profiles, load_runs, log_targets, performance_profiles, runtime_ecdf, Run};
+
+/// Convergence tolerance of the data and performance profiles.
+const TAU: f32 = 1e-3;
+
+/// One panel of step curves over a logarithmic x axis, extended flat to its
+/// right edge.
+fn draw_steps<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    caption: &str,
+    x_label: &str,
+    curves: &BTreeMap<String, Vec<(f32, f32)>>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let xs = curves.values().flatten().map(|&(x, _)| x);
+    // Room to the left of the first step, so that curves rising there show
+    let x_min = xs.clone().fold(f32::INFINITY, f32::min).max(1e-3) / 2.0;
+    let x_max = xs.fold(f32::NEG_INFINITY, f32::max).max(x_min * 10.0);
+    let (x_min, x_max) = if x_min.is_finite() { (x_min, x_max) } else { (1.0, 10.0) };
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, ("sans-serif", 22))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d((x_min..x_max).log_scale(), 0f32..1f32)?;
+    chart
+        .configure_mesh()
+        .x_labels(6)
+        .x_label_formatter(&|x| format!("{x:.1e}"))
+        .x_desc(x_label)
+        .y_desc("fraction solved")
+        .draw()?;
+    for (i, (algorithm, steps)) in curves.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let mut points = vec![(x_min, 0.0)];
+        for &(x, y) in steps {
+            points.push((x, points.last().unwrap().1));
+            points.push((x, y));
+        }
+        points.push((x_max, points.last().unwrap().1));
+        chart
+            .draw_series(LineSeries::new(points, color.stroke_width(2)))?
+            .label(algorithm)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::LowerRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Runtime ECDF, data profile and performance profile side by side.
+fn draw_figure<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, title: &str, runs: &[Run], dims: usize) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 28))?;
+    let panels = root.split_evenly((1, 3));
+
+    let mut by_algorithm: BTreeMap<&str, Vec<&Run>> = BTreeMap::new();
+    for run in runs {
+        by_algorithm.entry(run.algorithm.as_str()).or_default().push(run);
+    }
+    let targets = log_targets(2, -8, 5);
+    let ecdf: BTreeMap<String, Vec<(f32, f32)>> = by_algorithm
+        .iter()
+        .map(|(algorithm, runs)| {
+            let steps = runtime_ecdf(runs, &targets);
+            (algorithm.to_string(), steps.into_iter().map(|(nfev, y)| (nfev as f32 / dims as f32, y)).collect())
+        })
+        .collect();
+    let all: Vec<&Run> = runs.iter().collect();
+    draw_steps(&panels[0], "Runtime ECDF, 51 targets 1e2..1e-8", "evaluations / dimension", &ecdf)?;
+    draw_steps(&panels[1], &format!("Data profile, tau = {TAU:e}"), "simplex gradients", &data_profiles(&all, dims, TAU))?;
+    draw_steps(&panels[2], &format!("Performance profile, tau = {TAU:e}"), "ratio to fastest", &performance_profiles(&all, TAU))?;
+    root.present()?;
+    Ok(())
+}
+
+// Usage: cargo run --bin profiles -r -- [results.csv] [traces.csv] [--svg]
+// Defaults to the output of `benchmark`; writes profiles/<test_set>_<dim>d.png (or .svg).
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let svg = args.iter().any(|a| a == "--svg");
+    let paths: Vec<&String> = args.iter().filter(|a| *a != "--svg").collect();
+    let results = paths.first().map_or("benchmark_results.csv", |p| p.as_str());
+    let traces = paths.get(1).map_or("benchmark_traces.csv", |p| p.as_str());
+
+    let dir = "profiles";
+    fs::create_dir_all(dir)?;
+    for ((test_set, dims), runs) in load_runs(results, traces)? {
+        let title = format!("{test_set} {dims}D");
+        let size = (1800, 600);
+        let path = format!("{dir}/{test_set}_{dims}d.{}", if svg { "svg" } else { "png" });
+        if svg {
+            draw_figure(SVGBackend::new(&path, size).into_drawing_area(), &title, &runs, dims)?;
+        } else {
+            draw_figure(BitMapBackend::new(&path, size).into_drawing_area(), &title, &runs, dims)?;
+        }
+        println!("Saved {path}");
+    }
+    Ok(())
+}
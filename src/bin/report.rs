@@ -0,0 +1,51 @@
+use std::{collections::BTreeMap, env, error::Error, fs};
+
+use optimizers::{
+    experiment::suite_problems,
+    functions::DEFAULT_TARGET,
+    report::{load_results, tables},
+    runner::SeedResult,
+};
+
+/// `report [results.csv] [--markdown tables.md] [--latex dir]`: Markdown
+/// tables to stdout or `--markdown`, a LaTeX table per test set and dimension
+/// into `latex_tables/` or `--latex`.
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let (mut results, mut markdown, mut latex) = ("benchmark_results.csv".to_string(), None, "latex_tables".to_string());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--markdown" => markdown = Some(args.next().ok_or("--markdown needs a path")?),
+            "--latex" => latex = args.next().ok_or("--latex needs a directory")?,
+            _ => results = arg,
+        }
+    }
+
+    let results = load_results(&results)?;
+    // Successful as in `benchmark`: within the target of the function, or the
+    // default one for test sets that are no suite
+    let problems: BTreeMap<&str, _> = results.keys().map(|(test_set, ..)| (test_set.as_str(), suite_problems(test_set))).collect();
+    let solved = |test_set: &str, dim: usize, r: &SeedResult| {
+        let function = problems[test_set].as_ref().and_then(|problems| problems.get(&r.function));
+        r.error <= function.map_or(DEFAULT_TARGET, |f| f.target(dim))
+    };
+
+    fs::create_dir_all(&latex)?;
+    let mut text = String::new();
+    for table in tables(&results, solved) {
+        text.push_str(&format!("{}\n{}\n", table.heading(), table.markdown()));
+        for dim in table.dims() {
+            let path = format!("{latex}/{}_{dim}d.tex", table.test_set);
+            fs::write(&path, table.latex(dim))?;
+            eprintln!("Saved {path}");
+        }
+    }
+    match markdown {
+        Some(path) => {
+            fs::write(&path, text)?;
+            eprintln!("Saved {path}");
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    os::unix::net::UnixListener,
+    path::PathBuf,
+    process,
+    sync::Arc,
+    thread,
+};
+
+use optimizers::ask_tell::StudyServer;
+
+// Ask/tell optimization server, see `optimizers::ask_tell` for the protocol.
+//
+// Usage: server [--tcp ADDR | --unix PATH] [--dir DIR]
+// --tcp ADDR: listen on a TCP address (default 127.0.0.1:7878)
+// --unix PATH: listen on a Unix socket instead
+// --dir DIR: directory the studies are persisted in (default `studies`)
+fn serve<S: Read + Write>(server: &StudyServer, stream: S) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        if !line.trim().is_empty() {
+            let response = server.handle(line.trim());
+            let stream = reader.get_mut();
+            writeln!(stream, "{response}")?;
+            stream.flush()?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+fn spawn<S: Read + Write + Send + 'static>(server: &Arc<StudyServer>, stream: io::Result<S>) {
+    match stream {
+        Ok(stream) => {
+            let server = server.clone();
+            thread::spawn(move || {
+                if let Err(e) = serve(&server, stream) {
+                    eprintln!("Connection closed: {e}");
+                }
+            });
+        }
+        Err(e) => eprintln!("Failed to accept a connection: {e}"),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut tcp = "127.0.0.1:7878".to_string();
+    let mut unix = None;
+    let mut dir = PathBuf::from("studies");
+    for pair in args.chunks(2) {
+        let [flag, value] = pair else {
+            eprintln!("Usage: server [--tcp ADDR | --unix PATH] [--dir DIR]");
+            process::exit(2);
+        };
+        match flag.as_str() {
+            "--tcp" => tcp = value.clone(),
+            "--unix" => unix = Some(PathBuf::from(value)),
+            "--dir" => dir = PathBuf::from(value),
+            _ => {
+                eprintln!("Unknown option {flag}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let server = Arc::new(StudyServer::open(Some(&dir)).unwrap_or_else(|e| {
+        eprintln!("Failed to open studies: {e}");
+        process::exit(1);
+    }));
+
+    match unix {
+        Some(path) => {
+            // A socket file left by a previous run
+            let _ = std::fs::remove_file(&path);
+            let listener = UnixListener::bind(&path).unwrap_or_else(|e| {
+                eprintln!("Failed to bind {}: {e}", path.display());
+                process::exit(1);
+            });
+            println!("Listening on {} with studies in {}", path.display(), dir.display());
+            for stream in listener.incoming() {
+                spawn(&server, stream);
+            }
+        }
+        None => {
+            let listener = TcpListener::bind(&tcp).unwrap_or_else(|e| {
+                eprintln!("Failed to bind {tcp}: {e}");
+                process::exit(1);
+            });
+            println!("Listening on {tcp} with studies in {}", dir.display());
+            for stream in listener.incoming() {
+                spawn(&server, stream);
+            }
+        }
+    }
+}
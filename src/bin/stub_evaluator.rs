@@ -0,0 +1,80 @@
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process, thread,
+    time::Duration,
+};
+
+use optimizers::functions::expression::Expression;
+
+// Stand-in for an external objective, speaking the protocol of
+// `optimizers::external`.
+//
+// Usage: stub_evaluator [expression] [--crash-after N] [--hang-after N] [--nan-above V]
+// expression: objective in dimension order (default `sum(x_i^2)/n`)
+// --crash-after N: exit without answering after N answered batches
+// --hang-after N: stop answering after N answered batches
+// --nan-above V: answer `nan` for candidates whose value exceeds V
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut source = "sum(x_i^2)/n".to_string();
+    let mut crash_after = None;
+    let mut hang_after = None;
+    let mut nan_above = f32::INFINITY;
+    let mut i = 0;
+    while i < args.len() {
+        let value = || -> &str {
+            args.get(i + 1).map(|s| s.as_str()).unwrap_or_else(|| {
+                eprintln!("Missing value for {}", args[i]);
+                process::exit(2);
+            })
+        };
+        match args[i].as_str() {
+            "--crash-after" => crash_after = value().parse::<u64>().ok(),
+            "--hang-after" => hang_after = value().parse::<u64>().ok(),
+            "--nan-above" => nan_above = value().parse().unwrap_or(f32::INFINITY),
+            s => {
+                source = s.to_string();
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    let expression = Expression::parse(&source).unwrap_or_else(|e| {
+        eprintln!("Invalid expression: {e}");
+        process::exit(2);
+    });
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines().map_while(Result::ok);
+    let mut stdout = io::stdout().lock();
+    let mut batches = 0;
+    while let Some(header) = lines.next() {
+        let mut fields = header.split_whitespace().map(|v| v.parse::<usize>());
+        let (Some(Ok(count)), Some(Ok(dims))) = (fields.next(), fields.next()) else {
+            eprintln!("Invalid header: {header}");
+            process::exit(2);
+        };
+        if crash_after == Some(batches) {
+            process::exit(1);
+        }
+        if hang_after == Some(batches) {
+            loop {
+                thread::sleep(Duration::from_secs(3600));
+            }
+        }
+        for _ in 0..count {
+            let line = lines.next().unwrap_or_default();
+            let x: Vec<f32> = line.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+            let value = if x.len() == dims { expression.evaluate(&x) } else { f32::NAN };
+            if value > nan_above {
+                writeln!(stdout, "nan").unwrap();
+            } else {
+                writeln!(stdout, "{value}").unwrap();
+            }
+        }
+        stdout.flush().unwrap();
+        batches += 1;
+    }
+}
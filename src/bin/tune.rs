@@ -1,33 +1,38 @@
 use core::f32;
-use std::{collections::BTreeMap, env, fs::File, io::Write, sync::atomic::Ordering};
+use std::{collections::BTreeMap, env, fs::File, io::Write, sync::{atomic::Ordering, Mutex}};
 
 use atomic_float::AtomicF32;
 use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
 
 use optimizers::{
     algorithms::{ans::new_ans, ans_sort::new_ans_sort, ansr::new_ansr, ansr_dpnm::new_ansr_dpnm, de::new_de, shade::new_shade, zero_gradient::new_zero_gradient},
     default_algorithms_params::{ans_params, ans_sort_params, ansr_params, ansr_dpnm_params, de_params, shade_params, zero_gradient_params},
-    functions::{EASY_TEST_FUNCTIONS, HARD_TEST_FUNCTIONS, HARD_DISCRETE_FUNCTIONS, MEDIUM_PERIODIC_FUNCTIONS, LMMAES_TEST_FUNCTIONS, MAIN_TEST_FUNCTIONS, TERRAIN_TEST_FUNCTIONS, MINI_TEST_FUNCTIONS, WEIERSTRASS_TEST_FUNCTIONS, TestFunction},
+    functions::{expression::ExpressionFunction, EASY_TEST_FUNCTIONS, HARD_TEST_FUNCTIONS, HARD_DISCRETE_FUNCTIONS, MEDIUM_PERIODIC_FUNCTIONS, LMMAES_TEST_FUNCTIONS, MAIN_TEST_FUNCTIONS, TERRAIN_TEST_FUNCTIONS, MINI_TEST_FUNCTIONS, WEIERSTRASS_TEST_FUNCTIONS, BBOB_TEST_FUNCTIONS, COUPLED_TEST_FUNCTIONS, LMMAES_ROTATED_TEST_FUNCTIONS, CEC2017_TEST_FUNCTIONS, CEC2022_TEST_FUNCTIONS, Problem},
+    json::Json,
+    metadata::{write_metadata, RunClock},
     optimizer::Optimizer,
-    runner::run_multiple_optimizaions,
+    runner::{instances, seed_job, summarize_functions, RankingObjective, SeedResult},
+    scheduler::{run_jobs, set_thread_count, Job},
     utils::{all_combinations, f32_to_i64, group_by_key, mean_and_mad, summarize_group},
 };
 
-fn tune<T, F>(
+fn tune<T, F, P>(
     name: &str,
     test_set: &str,
     params_grid: &BTreeMap<String, Vec<f32>>,
     make_optimizer: F,
-    functions: &BTreeMap<String, TestFunction>,
+    functions: &BTreeMap<String, P>,
     dimension_count: usize,
     maxiter: u64,
     seed_count: u64,
     stop_residual: f32,
+    objective: RankingObjective,
 ) where
     T: Optimizer + Sync,
     F: Fn(&BTreeMap<String, f32>) -> T + Sync,
+    P: Problem,
 {
+    let clock = RunClock::start();
     let all_combinations = all_combinations(params_grid);
     let sty = ProgressStyle::with_template(
         "[{elapsed_precise}|{eta_precise}|mean={msg}] {bar:40.cyan/blue} {pos:>7}/{len:7}",
@@ -38,36 +43,70 @@ fn tune<T, F>(
     let pb = ProgressBar::new(all_combinations.len() as u64);
     pb.set_style(sty.clone());
     let global_mean = AtomicF32::new(f32::INFINITY);
-    let mut results: Vec<(i64, BTreeMap<String, f32>, BTreeMap<String, f32>)> = all_combinations
-        .par_iter()
-        .map(|params| {
-            let optimizer = make_optimizer(params);
-            let result = run_multiple_optimizaions(
-                &optimizer,
-                functions,
-                dimension_count,
-                maxiter,
-                seed_count,
-                stop_residual,
-                false,
-                false,
+    // Every run of every combination in one queue; a combination is scored
+    // once its last run is in
+    let optimizers: Vec<T> = all_combinations.iter().map(&make_optimizer).collect();
+    let runs_per_combination = functions.len() * seed_count as usize;
+    let partial: Vec<Mutex<Vec<SeedResult>>> = all_combinations.iter().map(|_| Mutex::new(Vec::new())).collect();
+    let scored: Vec<Mutex<Option<([f32; 3], BTreeMap<String, f32>)>>> = all_combinations.iter().map(|_| Mutex::new(None)).collect();
+    let score = |results: &[SeedResult]| {
+        let summaries = summarize_functions(results, stop_residual);
+        let mut result: BTreeMap<String, f32> =
+            summaries.iter().map(|(name, s)| (name.clone(), objective.function_value(s))).collect();
+        let mean = objective.score(&summaries);
+        result.insert("mean".to_string(), mean);
+        // Ties, such as configurations that all fail somewhere, go to
+        // the one that fails less, then to the one that gets closer
+        let ranking = [
+            mean,
+            RankingObjective::FailureRate.score(&summaries),
+            RankingObjective::MedianError.score(&summaries),
+        ];
+        pb.inc(1);
+        let best = global_mean.fetch_min(mean, Ordering::Relaxed);
+        if pb.position() % 100 == 0 {
+            println!(
+                "[{}/{}] best={}",
+                pb.position(),
+                pb.length().unwrap_or(0),
+                best.min(mean)
             );
-            let mean = result["mean"];
-            pb.inc(1);
-            let best = global_mean.fetch_min(mean, Ordering::Relaxed);
-            if pb.position() % 100 == 0 {
-                println!(
-                    "[{}/{}] best={}",
-                    pb.position(),
-                    pb.length().unwrap_or(0),
-                    best.min(mean)
-                );
-            }
-            pb.set_message(format!("{}", best.min(mean)));
-            (f32_to_i64(mean), params.clone(), result)
+        }
+        pb.set_message(format!("{}", best.min(mean)));
+        (ranking, result)
+    };
+    let instances = instances(functions, dimension_count);
+    let mut jobs: Vec<Job<()>> = Vec::new();
+    for (c, optimizer) in optimizers.iter().enumerate() {
+        for instance in &instances {
+            for seed in 0..seed_count {
+                let (partial, scored, score) = (&partial, &scored, &score);
+                let job = seed_job(optimizer, instance, maxiter, seed, stop_residual);
+                jobs.push(job.map(move |mut r| {
+                    r.trace = Vec::new();
+                    let mut results = partial[c].lock().unwrap();
+                    results.push(r);
+                    if results.len() == runs_per_combination {
+                        *scored[c].lock().unwrap() = Some(score(&results));
+                        results.clear();
+                    }
+                }));
+            }
+        }
+    }
+    run_jobs(jobs, true, |_| {});
+    let mut ranked: Vec<([f32; 3], (i64, BTreeMap<String, f32>, BTreeMap<String, f32>))> = all_combinations
+        .iter()
+        .zip(scored)
+        .map(|(params, scored)| {
+            let (ranking, result) = scored.into_inner().unwrap().unwrap();
+            (ranking, (f32_to_i64(ranking[0]), params.clone(), result))
         })
         .collect();
-    results.sort_by_key(|&(mean, _, _)| mean);
+    ranked.sort_by(|(a, _), (b, _)| {
+        a.iter().zip(b).map(|(x, y)| x.total_cmp(y)).find(|o| o.is_ne()).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let (rankings, results): (Vec<[f32; 3]>, Vec<_>) = ranked.into_iter().unzip();
 
     // Write CSV: tune_results/<test_set>_<dim>D_<algo>.csv
     {
@@ -85,9 +124,10 @@ fn tune<T, F>(
         }
         header.push("mean");
         header.push("mad");
+        header.push("failure_rate");
         writeln!(f, "{}", header.join(",")).unwrap();
         // rows
-        for (_, params, result) in &results {
+        for ((_, params, result), ranking) in results.iter().zip(&rankings) {
             let mut row: Vec<String> = param_keys.iter().map(|k| format!("{}", params[*k])).collect();
             let func_values: Vec<f32> = func_keys.iter().map(|fk| result.get(*fk).copied().unwrap_or(f32::INFINITY)).collect();
             for &v in &func_values {
@@ -101,9 +141,22 @@ fn tune<T, F>(
             };
             row.push(format!("{}", mean));
             row.push(format!("{}", mad));
+            row.push(format!("{}", ranking[1]));
             writeln!(f, "{}", row.join(",")).unwrap();
         }
-        println!("Saved {}", path);
+        let grid = params_grid.iter().map(|(k, values)| (k.clone(), Json::from(values.as_slice())));
+        let fields = vec![
+            ("algorithm", Json::from(name)),
+            ("suite", Json::from(test_set)),
+            ("dim", Json::from(dimension_count)),
+            ("maxiter", Json::from(maxiter)),
+            ("stop_residual", Json::from(stop_residual)),
+            ("seeds", Json::from(seed_count)),
+            ("objective", Json::from(objective.name())),
+            ("params_grid", Json::object(grid)),
+        ];
+        let meta = write_metadata(&path, fields, &clock).unwrap();
+        println!("Saved {} and {}", path, meta);
     }
 
     let group_key = params_grid.keys().next().unwrap();
@@ -112,7 +165,7 @@ fn tune<T, F>(
     let all_means: Vec<f32> = results.iter().map(|(_, _, r)| r["mean"]).collect();
     let (global_mean, global_mad) = mean_and_mad(&all_means);
     let best = &results[0];
-    println!("\n=== {} Summary ===", name);
+    println!("\n=== {} Summary ({}) ===", name, objective.name());
     println!("Total combinations: {}", results.len());
     println!("Global mean: {:.4}, mad: {:.4}", global_mean, global_mad);
     println!("Best:  mean={:.4} params={:?}", best.2["mean"], best.1);
@@ -134,45 +187,85 @@ fn tune<T, F>(
     println!("{:-<90}", "");
 }
 
-fn tune_all(
+fn tune_all<P: Problem>(
     algo: &str,
     test_set: &str,
-    functions: &BTreeMap<String, TestFunction>,
+    functions: &BTreeMap<String, P>,
     dimension_count: usize,
     maxiter: u64,
     seed_count: u64,
     stop_residual: f32,
+    objective: RankingObjective,
 ) {
     if algo == "ans" || algo == "all" {
-        tune("ANS", test_set, &ans_params(dimension_count), |p| new_ans(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("ANS", test_set, &ans_params(dimension_count), |p| new_ans(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "ans_sort" || algo == "all" {
-        tune("ANS Sort", test_set, &ans_sort_params(dimension_count), |p| new_ans_sort(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("ANS Sort", test_set, &ans_sort_params(dimension_count), |p| new_ans_sort(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "ansr" || algo == "all" {
-        tune("ANSR", test_set, &ansr_params(dimension_count), |p| new_ansr(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("ANSR", test_set, &ansr_params(dimension_count), |p| new_ansr(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "ansr_dpnm" || algo == "all" {
-        tune("ANSR DPNM", test_set, &ansr_dpnm_params(dimension_count), |p| new_ansr_dpnm(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("ANSR DPNM", test_set, &ansr_dpnm_params(dimension_count), |p| new_ansr_dpnm(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "de" || algo == "all" {
-        tune("DE", test_set, &de_params(dimension_count), |p| new_de(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("DE", test_set, &de_params(dimension_count), |p| new_de(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "shade" || algo == "all" {
-        tune("SHADE", test_set, &shade_params(dimension_count), |p| new_shade(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("SHADE", test_set, &shade_params(dimension_count), |p| new_shade(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
     if algo == "zero_gradient" || algo == "all" {
-        tune("Zero Gradient", test_set, &zero_gradient_params(), |p| new_zero_gradient(p), functions, dimension_count, maxiter, seed_count, stop_residual);
+        tune("Zero Gradient", test_set, &zero_gradient_params(), |p| new_zero_gradient(p), functions, dimension_count, maxiter, seed_count, stop_residual, objective);
     }
 }
 
-// Usage: cargo run --bin tune -r -- <test_set> [algo]
-// test_set: main | mini | lmmaes
+/// `<expression> <min> <max>` from the command line, or exit with a message.
+fn expression_from_args(args: &[String]) -> ExpressionFunction {
+    let [source, min, max] = args else {
+        eprintln!("Usage: tune expr <expression> <min> <max> [algo] [dim]");
+        std::process::exit(1);
+    };
+    let bound = |s: &String| {
+        s.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid bound: {s}");
+            std::process::exit(1);
+        })
+    };
+    ExpressionFunction::parse(source, [bound(min), bound(max)]).unwrap_or_else(|e| {
+        eprintln!("Invalid expression: {e}");
+        std::process::exit(1);
+    })
+}
+
+// Usage: cargo run --bin tune -r -- [--rank objective] [--threads n] <test_set> [algo]
+//        cargo run --bin tune -r -- [--rank objective] [--threads n] expr <expression> <min> <max> [algo] [dim]
+// test_set: main | mini | lmmaes | lmmaes_rotated | coupled | bbob | cec2017 | cec2022 | expr
 // algo: ans | ansr | ansr_dpnm | de | shade | zero_gradient | all (default)
+// objective: mean_nfev (default) | ert | failure_rate | median_error
+// All runs of all parameter combinations share one queue on at most n threads.
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--threads") {
+        let threads = args.get(i + 1).and_then(|t| t.parse().ok()).unwrap_or_else(|| {
+            eprintln!("--threads needs a thread count");
+            std::process::exit(1);
+        });
+        set_thread_count(threads).unwrap();
+        args.drain(i..i + 2);
+    }
+    let mut objective = RankingObjective::MeanNfev;
+    if let Some(i) = args.iter().position(|a| a == "--rank") {
+        let name = args.get(i + 1).cloned().unwrap_or_default();
+        objective = RankingObjective::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown ranking objective: {name}. Use: {}", RankingObjective::NAMES.join(" | "));
+            std::process::exit(1);
+        });
+        args.drain(i..i + 2);
+    }
     let test_set = args.get(1).map(|s| s.as_str()).unwrap_or("main");
-    let algo = args.get(2).map(|s| s.as_str()).unwrap_or("all");
+    let algo_arg = if test_set == "expr" { 5 } else { 2 };
+    let algo = args.get(algo_arg).map(|s| s.as_str()).unwrap_or("all");
 
     let seed_count = 10;
     let stop_residual = 0.01;
@@ -180,53 +273,95 @@ fn main() {
     match test_set {
         "main" => {
             println!(">>> Test set: MAIN (16D)");
-            tune_all(algo, "main", &MAIN_TEST_FUNCTIONS, 16, 50_000, seed_count, stop_residual);
+            tune_all(algo, "main", &MAIN_TEST_FUNCTIONS, 16, 50_000, seed_count, stop_residual, objective);
         }
         "mini" => {
             println!(">>> Test set: MINI (64D)");
-            tune_all(algo, "mini", &MINI_TEST_FUNCTIONS, 64, 50_000, seed_count, stop_residual);
+            tune_all(algo, "mini", &MINI_TEST_FUNCTIONS, 64, 50_000, seed_count, stop_residual, objective);
         }
         "medium_terrain" => {
             for d in [64, 128, 256] {
                 println!("\n>>> Test set: MEDIUM_TERRAIN ({d}D)");
-                tune_all(algo, "medium_terrain", &TERRAIN_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual);
+                tune_all(algo, "medium_terrain", &TERRAIN_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
             }
         }
         "medium_weierstrass" => {
             for d in [16, 64, 128, 256] {
                 println!("\n>>> Test set: MEDIUM_WEIERSTRASS ({d}D)");
-                tune_all(algo, "medium_weierstrass", &WEIERSTRASS_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual);
+                tune_all(algo, "medium_weierstrass", &WEIERSTRASS_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
             }
         }
         "lmmaes" => {
             for d in [64] {
                 println!("\n>>> Test set: LMMAES ({d}D)");
-                tune_all(algo, "lmmaes", &LMMAES_TEST_FUNCTIONS, d, 50_000, seed_count, stop_residual);
+                tune_all(algo, "lmmaes", &LMMAES_TEST_FUNCTIONS, d, 50_000, seed_count, stop_residual, objective);
             }
         }
         "easy" => {
             for d in [64, 128, 256, 512, 1024] {
                 println!("\n>>> Test set: EASY ({d}D)");
-                tune_all(algo, "easy", &EASY_TEST_FUNCTIONS, d, 50_000, seed_count, stop_residual);
+                tune_all(algo, "easy", &EASY_TEST_FUNCTIONS, d, 50_000, seed_count, stop_residual, objective);
             }
         }
         "hard" => {
             for d in [8, 16] {
                 println!("\n>>> Test set: HARD ({d}D)");
-                tune_all(algo, "hard", &HARD_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual);
+                tune_all(algo, "hard", &HARD_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
             }
         }
         "hard_discrete" => {
             for d in [16, 32, 64] {
                 println!("\n>>> Test set: HARD_DISCRETE ({d}D)");
-                tune_all(algo, "hard_discrete", &HARD_DISCRETE_FUNCTIONS, d, 500_000, seed_count, stop_residual);
+                tune_all(algo, "hard_discrete", &HARD_DISCRETE_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
             }
         }
         "medium_periodic" => {
             for d in [16, 32, 64] {
                 println!("\n>>> Test set: HARD_PERIODIC ({d}D)");
-                tune_all(algo, "medium_periodic", &MEDIUM_PERIODIC_FUNCTIONS, d, 500_000, seed_count, stop_residual);
+                tune_all(algo, "medium_periodic", &MEDIUM_PERIODIC_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
+            }
+        }
+        "lmmaes_rotated" => {
+            for d in [64] {
+                println!("\n>>> Test set: LMMAES_ROTATED ({d}D)");
+                tune_all(algo, "lmmaes_rotated", &*LMMAES_ROTATED_TEST_FUNCTIONS, d, 50_000, seed_count, stop_residual, objective);
+            }
+        }
+        "coupled" => {
+            for d in [64, 128, 256] {
+                println!("\n>>> Test set: COUPLED ({d}D)");
+                tune_all(algo, "coupled", &COUPLED_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
+            }
+        }
+        "bbob" => {
+            for d in [16, 32, 64] {
+                println!("\n>>> Test set: BBOB ({d}D)");
+                tune_all(algo, "bbob", &*BBOB_TEST_FUNCTIONS, d, 500_000, seed_count, stop_residual, objective);
+            }
+        }
+        "cec2017" => {
+            for d in [16, 32, 64] {
+                println!("\n>>> Test set: CEC2017 ({d}D)");
+                tune_all(algo, "cec2017", &*CEC2017_TEST_FUNCTIONS, d, 10_000 * d as u64, seed_count, stop_residual, objective);
+            }
+        }
+        "cec2022" => {
+            for d in [16, 32] {
+                println!("\n>>> Test set: CEC2022 ({d}D)");
+                tune_all(algo, "cec2022", &*CEC2022_TEST_FUNCTIONS, d, 50_000 * d as u64, seed_count, stop_residual, objective);
+            }
+        }
+        "expr" => {
+            let function = expression_from_args(args.get(2..5).unwrap_or(&[]));
+            let dim = args.get(6).and_then(|s| s.parse().ok()).unwrap_or(64);
+            if let Err(e) = function.check_dims(dim) {
+                eprintln!("Invalid dimension: {e}");
+                std::process::exit(1);
             }
+            println!("\n>>> Expression: {} ({dim}D)", function.expression.source());
+            let mut single = BTreeMap::new();
+            single.insert("expr".to_string(), function);
+            tune_all(algo, "expr", &single, dim, 500_000, seed_count, stop_residual, objective);
         }
         "each" => {
             let dim = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(64);
@@ -238,11 +373,11 @@ fn main() {
                 let mut single = BTreeMap::new();
                 single.insert(name.clone(), tf.clone());
                 println!("\n>>> Function: {name} ({dim}D)");
-                tune_all(algo, &format!("each_{name}"), &single, dim, maxiter_each, seed_count, stop_residual);
+                tune_all(algo, &format!("each_{name}"), &single, dim, maxiter_each, seed_count, stop_residual, objective);
             }
         }
         _ => {
-            eprintln!("Unknown test set: {}. Use: main | mini | medium_terrain | medium_weierstrass | easy | hard | lmmaes | each", test_set);
+            eprintln!("Unknown test set: {}. Use: main | mini | medium_terrain | medium_weierstrass | easy | hard | lmmaes | lmmaes_rotated | coupled | bbob | cec2017 | cec2022 | expr | each", test_set);
             std::process::exit(1);
         }
     }
@@ -0,0 +1,255 @@
+//! C API of the `cdylib`; `include/optimizers.h` is generated from this file
+//! by `build.rs`.
+//!
+//! Every argument is checked up front: names, parameter values (see
+//! `check_params`), bounds, `dims` and `maxiter`. A panic that still escapes an
+//! optimizer is caught at the boundary and reported like any other failure, so
+//! it never unwinds into, or aborts, the host.
+//!
+//! Optimizers are created by name (see `ask_tell::ALGORITHMS`) with either no
+//! parameters, for the defaults, or all of them. The objective is a C function
+//! called once per candidate, in dimension order and within bounds, from the
+//! thread that runs `opt_find_infimum`. A NaN return marks a failed
+//! evaluation, scored as worst. Functions that can fail return null or a
+//! negative status and leave a message for `opt_last_error`.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ffi::{c_char, c_void, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::{
+    ask_tell::Algorithm,
+    evaluator::PopulationEvaluator,
+    rng::{stream_rng, DefaultRng},
+    utils::split_bounds,
+};
+
+/// Version of this API, raised on incompatible changes.
+pub const OPT_API_VERSION: u32 = 1;
+
+/// Status returned on success.
+pub const OPT_OK: i32 = 0;
+/// Status returned on failure; see `opt_last_error`.
+pub const OPT_ERROR: i32 = -1;
+
+/// Objective value of the point `x` with `dims` coordinates.
+pub type OptObjective = Option<unsafe extern "C" fn(x: *const f32, dims: usize, user_data: *mut c_void) -> f32>;
+
+/// An optimizer with its parameters, opaque to C.
+pub struct OptOptimizer {
+    algorithm: Algorithm,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptResult {
+    /// Best value found.
+    pub f_x: f32,
+    /// Number of objective calls.
+    pub nfev: u64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Runs `f`, turning a panic into an error message.
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = match payload.downcast::<String>() {
+            Ok(s) => *s,
+            Err(payload) => payload.downcast::<&str>().map(|s| s.to_string()).unwrap_or_default(),
+        };
+        Err(format!("optimizer panicked: {message}"))
+    })
+}
+
+struct CallbackEvaluator {
+    objective: unsafe extern "C" fn(*const f32, usize, *mut c_void) -> f32,
+    user_data: *mut c_void,
+    range_min: Vec<f32>,
+    range_max: Vec<f32>,
+    point: Vec<f32>,
+}
+
+impl PopulationEvaluator for CallbackEvaluator {
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        let dims = self.point.len();
+        for (position, f) in positions.chunks_exact(dims).zip(fitness.iter_mut()) {
+            let ranges = self.range_min.iter().zip(&self.range_max);
+            for ((x, &p), (lo, hi)) in self.point.iter_mut().zip(position).zip(ranges) {
+                *x = lo + p * (hi - lo);
+            }
+            let value = unsafe { (self.objective)(self.point.as_ptr(), dims, self.user_data) };
+            *f = if value.is_nan() { f32::INFINITY } else { value };
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn opt_api_version() -> u32 {
+    OPT_API_VERSION
+}
+
+/// Message of the last failure on the calling thread, valid until the next
+/// failing call on it. Empty if nothing failed.
+#[unsafe(no_mangle)]
+pub extern "C" fn opt_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Creates an optimizer, or returns null on an unknown name, missing
+/// parameters or values the algorithm cannot run with. Free it with
+/// `opt_optimizer_free`.
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string. `param_names` and `param_values`
+/// must point to `param_count` NUL-terminated strings and floats; they may be
+/// null when `param_count` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn opt_optimizer_new(
+    name: *const c_char,
+    param_names: *const *const c_char,
+    param_values: *const f32,
+    param_count: usize,
+) -> *mut OptOptimizer {
+    if name.is_null() || (param_count > 0 && (param_names.is_null() || param_values.is_null())) {
+        set_error("null argument");
+        return ptr::null_mut();
+    }
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+        set_error("name is not UTF-8");
+        return ptr::null_mut();
+    };
+    let params = if param_count == 0 {
+        None
+    } else {
+        let names = unsafe { slice::from_raw_parts(param_names, param_count) };
+        let values = unsafe { slice::from_raw_parts(param_values, param_count) };
+        let mut params = BTreeMap::new();
+        for (&key, &value) in names.iter().zip(values) {
+            if key.is_null() {
+                set_error("null parameter name");
+                return ptr::null_mut();
+            }
+            let key = unsafe { CStr::from_ptr(key) }.to_string_lossy().into_owned();
+            params.insert(key, value);
+        }
+        Some(params)
+    };
+    // The dimension only shapes the tuning grid, not its parameter names
+    match catch_panic(|| Algorithm::new(name, params.as_ref(), 2)) {
+        Ok(algorithm) => Box::into_raw(Box::new(OptOptimizer { algorithm })),
+        Err(message) => {
+            set_error(message);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `optimizer` must come from `opt_optimizer_new` and not be freed yet, or be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn opt_optimizer_free(optimizer: *mut OptOptimizer) {
+    if !optimizer.is_null() {
+        drop(unsafe { Box::from_raw(optimizer) });
+    }
+}
+
+/// Minimizes `objective` within `bounds`, laid out as `dims` pairs of
+/// `min, max`, using at most about `maxiter` evaluations and stopping early
+/// once a value is at or below `stop_residual` (pass `-INFINITY` to use the
+/// whole budget). On success writes the best point to `x` and returns `OPT_OK`.
+///
+/// # Safety
+///
+/// `optimizer` must be valid, `bounds` must point to `2 * dims` floats, `x`
+/// to room for `dims` floats and `result` to an `OptResult`. `user_data` is
+/// passed to `objective` untouched.
+#[allow(clippy::too_many_arguments)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn opt_find_infimum(
+    optimizer: *const OptOptimizer,
+    objective: OptObjective,
+    user_data: *mut c_void,
+    bounds: *const f32,
+    dims: usize,
+    maxiter: u64,
+    seed: u64,
+    stop_residual: f32,
+    x: *mut f32,
+    result: *mut OptResult,
+) -> i32 {
+    let Some(objective) = objective else {
+        set_error("null objective");
+        return OPT_ERROR;
+    };
+    if optimizer.is_null() || bounds.is_null() || x.is_null() || result.is_null() {
+        set_error("null argument");
+        return OPT_ERROR;
+    }
+    if dims == 0 || maxiter == 0 {
+        set_error("dims and maxiter must be positive");
+        return OPT_ERROR;
+    }
+    let bounds: Vec<[f32; 2]> = unsafe { slice::from_raw_parts(bounds, 2 * dims) }
+        .chunks_exact(2)
+        .map(|b| [b[0], b[1]])
+        .collect();
+    if let Some(b) = bounds.iter().find(|b| !b[0].is_finite() || !b[1].is_finite() || b[0] >= b[1]) {
+        set_error(format!("invalid bounds [{}, {}]", b[0], b[1]));
+        return OPT_ERROR;
+    }
+    let (range_min, range_max) = split_bounds(&bounds);
+    let mut evaluator = CallbackEvaluator {
+        objective,
+        user_data,
+        range_min,
+        range_max,
+        point: vec![0.0; dims],
+    };
+    let algorithm = unsafe { &(*optimizer).algorithm };
+    let run = catch_panic(|| {
+        let mut rng: DefaultRng = stream_rng(seed, 0);
+        Ok(algorithm.find_infimum_population(&mut evaluator, &bounds, maxiter, &mut rng, stop_residual))
+    });
+    let found = match run {
+        Ok(found) => found,
+        Err(message) => {
+            set_error(message);
+            return OPT_ERROR;
+        }
+    };
+    unsafe {
+        slice::from_raw_parts_mut(x, dims).copy_from_slice(&found.x);
+        *result = OptResult {
+            f_x: found.f_x,
+            nfev: found.nfev,
+        };
+    }
+    OPT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| Ok(1)), Ok(1));
+        assert_eq!(catch_panic(|| Err::<i32, _>("bad".to_string())), Err("bad".to_string()));
+        let caught = catch_panic::<i32>(|| panic!("sigma {} is invalid", -1.0));
+        assert_eq!(caught, Err("optimizer panicked: sigma -1 is invalid".to_string()));
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use crate::algorithms::{ans::ANS, ans_sort::AnsSorted, ansr::ANSR, ansr_dpnm::AnsrDpnm, de::DE, shade::SHADE, zero_gradient::ZeroGradient};
+use crate::algorithms::{ans::ANS, ans_sort::AnsSorted, ansr::ANSR, ansr_dpnm::AnsrDpnm, de::DE, moead::MOEAD, nsga2::NSGA2, shade::SHADE, zero_gradient::ZeroGradient};
 
 pub(crate) fn frange(start: f32, step: f32, end: f32) -> Vec<f32> {
     let n = ((end - start) / step).round() as usize + 1;
@@ -115,6 +115,42 @@ pub fn shade_params(dimension_count: usize) -> BTreeMap<String, Vec<f32>> {
     m
 }
 
+pub static DEFAULT_NSGA2: NSGA2 = NSGA2 {
+    popsize: 100,
+    crossover_rate: 0.9,
+    crossover_eta: 15.0,
+    mutation_eta: 20.0,
+};
+
+pub fn nsga2_params(_dimension_count: usize) -> BTreeMap<String, Vec<f32>> {
+    let mut m = BTreeMap::new();
+    m.insert("popsize".to_string(), vec![50.0, 100.0, 200.0]);
+    m.insert("crossover_rate".to_string(), frange(0.6, 0.1, 1.0));
+    m.insert("crossover_eta".to_string(), frange(5.0, 5.0, 30.0));
+    m.insert("mutation_eta".to_string(), frange(5.0, 5.0, 30.0));
+    m
+}
+
+pub static DEFAULT_MOEAD: MOEAD = MOEAD {
+    popsize: 100,
+    neighbourhood_size: 20,
+    neighbourhood_rate: 0.9,
+    max_replacements: 2,
+    f: 0.5,
+    cr: 1.0,
+};
+
+pub fn moead_params(_dimension_count: usize) -> BTreeMap<String, Vec<f32>> {
+    let mut m = BTreeMap::new();
+    m.insert("popsize".to_string(), vec![50.0, 100.0, 200.0]);
+    m.insert("neighbourhood_size".to_string(), frange(10.0, 5.0, 30.0));
+    m.insert("neighbourhood_rate".to_string(), frange(0.6, 0.1, 1.0));
+    m.insert("max_replacements".to_string(), frange(1.0, 1.0, 4.0));
+    m.insert("f".to_string(), frange(0.3, 0.1, 0.9));
+    m.insert("cr".to_string(), frange(0.5, 0.25, 1.0));
+    m
+}
+
 pub static DEFAULT_ZERO_GRADIENT: ZeroGradient = ZeroGradient { init_jump: 0.1 };
 
 pub fn zero_gradient_params() -> BTreeMap<String, Vec<f32>> {
@@ -126,6 +162,37 @@ pub fn zero_gradient_params() -> BTreeMap<String, Vec<f32>> {
     m
 }
 
+/// Checks parameter values of `algorithm` from outside the crate against what
+/// its search needs: finite numbers, whole population and memory sizes, enough
+/// individuals to pick distinct neighbours or donors from, a non-negative
+/// `sigma` and a `p_best_rate` of at most 1.
+pub fn check_params(algorithm: &str, params: &BTreeMap<String, f32>) -> Result<(), String> {
+    for (key, &v) in params {
+        if !v.is_finite() {
+            return Err(format!("parameter `{key}` of `{algorithm}` must be finite"));
+        }
+        let min_integer = match (algorithm, key.as_str()) {
+            ("de" | "shade", "popsize") => Some(4.0),
+            (_, "popsize") => Some(2.0),
+            ("shade", "h") => Some(1.0),
+            _ => None,
+        };
+        // Integers above 2^24 are not exact in an f32
+        if let Some(min) = min_integer
+            && (v.fract() != 0.0 || v < min || v > (1 << 24) as f32)
+        {
+            return Err(format!("`{key}` of `{algorithm}` must be an integer from {min} to 2^24, got {v}"));
+        }
+        if key == "sigma" && v < 0.0 {
+            return Err(format!("`sigma` of `{algorithm}` must not be negative, got {v}"));
+        }
+        if (algorithm, key.as_str()) == ("shade", "p_best_rate") && !(v > 0.0 && v <= 1.0) {
+            return Err(format!("`p_best_rate` of `shade` must be in (0, 1], got {v}"));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,4 +297,31 @@ mod tests {
         assert_eq!(*sin.first().unwrap(), 0.0);
         assert_eq!(*sin.last().unwrap(), 1.0);
     }
+
+    #[test]
+    fn test_check_params() {
+        let grids = [
+            ("ans", ans_params(16)),
+            ("ans_sort", ans_sort_params(16)),
+            ("ansr", ansr_params(16)),
+            ("ansr_dpnm", ansr_dpnm_params(16)),
+            ("de", de_params(16)),
+            ("shade", shade_params(16)),
+        ];
+        for (algorithm, grid) in &grids {
+            for (key, values) in grid {
+                for &v in values {
+                    assert!(check_params(algorithm, &BTreeMap::from([(key.clone(), v)])).is_ok(), "{algorithm} {key}={v}");
+                }
+            }
+        }
+        let check = |algorithm: &str, key: &str, v: f32| check_params(algorithm, &BTreeMap::from([(key.to_string(), v)]));
+        assert!(check("ansr", "popsize", 1.0).unwrap_err().contains("integer from 2"));
+        assert!(check("de", "popsize", 3.0).is_err());
+        assert!(check("shade", "popsize", 56.5).is_err());
+        assert!(check("shade", "h", 0.0).is_err());
+        assert!(check("shade", "p_best_rate", 1.5).is_err());
+        assert!(check("ans", "sigma", -0.1).is_err());
+        assert!(check("de", "f", f32::NAN).unwrap_err().contains("finite"));
+    }
 }
@@ -0,0 +1,732 @@
+use rand_distr::{Distribution, Uniform};
+use simd_vector::Vec8;
+
+use crate::{
+    external::{ExternalError, ExternalObjective},
+    optimizer::{
+        FailurePolicy, FallibleResult, NoiseHandling, NoisyResult, ObjectiveError,
+        OptimizerResult, Resampling,
+    },
+    rng::{stream_rng, DefaultRng},
+    utils::{fit_in_bounds_simd, split_bounds, BatchBoundsSimd, BoundsSimd},
+};
+
+/// Sub-stream of the run seed that `FailurePolicy::Resample` draws from, so
+/// resampling never shifts the optimizer's own random sequence.
+pub const RESAMPLE_STREAM: u64 = u64::MAX;
+
+/// Evaluates a flat population of unit-cube candidates (`count * dims` values)
+/// into `fitness`, one value per candidate. Implementations pick the SIMD layout
+/// and never report NaN: a NaN objective value is scored as `f32::INFINITY`.
+/// An evaluator may replace candidates in `positions` (see `FailurePolicy::Resample`).
+pub trait PopulationEvaluator {
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]);
+
+    /// True once the evaluator gave up on the run; the search should stop.
+    fn aborted(&self) -> bool {
+        false
+    }
+}
+
+fn worst_if_nan(value: f32) -> f32 {
+    if value.is_nan() { f32::INFINITY } else { value }
+}
+
+/// One candidate per objective call; lanes hold coordinate pairs (`broadcast_simd`).
+pub struct SingleEvaluator<'a, F> {
+    func: &'a F,
+    bounds_simd: BoundsSimd,
+    simd_buf: Vec<Vec8>,
+    dims: usize,
+}
+
+impl<'a, F> SingleEvaluator<'a, F>
+where
+    F: Fn(&[Vec8]) -> f32,
+{
+    pub fn new(func: &'a F, range_min: &[f32], range_max: &[f32]) -> Self {
+        let bounds_simd = BoundsSimd::new(range_min, range_max);
+        let simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        Self {
+            func,
+            bounds_simd,
+            simd_buf,
+            dims: range_min.len(),
+        }
+    }
+}
+
+impl<F> PopulationEvaluator for SingleEvaluator<'_, F>
+where
+    F: Fn(&[Vec8]) -> f32,
+{
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        let dims = self.dims;
+        for (p, f) in fitness.iter_mut().enumerate() {
+            self.bounds_simd
+                .transform_into(&positions[p * dims..(p + 1) * dims], &mut self.simd_buf);
+            *f = worst_if_nan((self.func)(&self.simd_buf));
+        }
+    }
+}
+
+/// Objective vectors of candidates in the unit cube, for the multi-objective
+/// optimizers; `objectives` holds `objective_count` values per candidate.
+pub struct MultiObjectiveEvaluator<'a, F> {
+    func: &'a F,
+    objective_count: usize,
+    range_min: &'a [f32],
+    range_max: &'a [f32],
+    point: Vec<f32>,
+}
+
+impl<'a, F> MultiObjectiveEvaluator<'a, F>
+where
+    F: Fn(&[f32], &mut [f32]),
+{
+    pub fn new(func: &'a F, objective_count: usize, range_min: &'a [f32], range_max: &'a [f32]) -> Self {
+        Self {
+            func,
+            objective_count,
+            range_min,
+            range_max,
+            point: vec![0.0; range_min.len()],
+        }
+    }
+
+    pub fn evaluate(&mut self, positions: &[f32], objectives: &mut [f32]) {
+        let (dims, m) = (self.point.len(), self.objective_count);
+        for (position, f) in positions.chunks_exact(dims).zip(objectives.chunks_exact_mut(m)) {
+            let ranges = self.range_min.iter().zip(self.range_max);
+            for ((x, &p), (lo, hi)) in self.point.iter_mut().zip(position).zip(ranges) {
+                *x = lo + p * (hi - lo);
+            }
+            (self.func)(&self.point, f);
+            for v in f.iter_mut() {
+                *v = worst_if_nan(*v);
+            }
+        }
+    }
+}
+
+/// Eight candidates per objective call; lanes hold candidates (`broadcast_simd_batch`).
+pub struct BatchEvaluator<'a, F> {
+    func: &'a F,
+    bounds_simd: BatchBoundsSimd,
+    simd_buf: Vec<Vec8>,
+    dims: usize,
+}
+
+impl<'a, F> BatchEvaluator<'a, F>
+where
+    F: Fn(&[Vec8]) -> Vec8,
+{
+    pub fn new(func: &'a F, range_min: &[f32], range_max: &[f32]) -> Self {
+        let bounds_simd = BatchBoundsSimd::new(range_min, range_max);
+        let simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        Self {
+            func,
+            bounds_simd,
+            simd_buf,
+            dims: range_min.len(),
+        }
+    }
+}
+
+impl<F> PopulationEvaluator for BatchEvaluator<'_, F>
+where
+    F: Fn(&[Vec8]) -> Vec8,
+{
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        let dims = self.dims;
+        for (chunk, f_chunk) in fitness.chunks_mut(8).enumerate() {
+            let start = chunk * 8 * dims;
+            let count = f_chunk.len();
+            self.bounds_simd.transform_batch_into(
+                &positions[start..start + count * dims],
+                count,
+                &mut self.simd_buf,
+            );
+            let values = (self.func)(&self.simd_buf);
+            for (f, value) in f_chunk.iter_mut().zip(&values.0[..count]) {
+                *f = worst_if_nan(*value);
+            }
+        }
+    }
+}
+
+/// One candidate per call of a fallible objective (`Result<f32, E>`), applying
+/// a `FailurePolicy` to `Err` and NaN results. Resampled points are drawn from
+/// `rng`, which should be a sub-stream separate from the optimizer's own.
+pub struct FallibleEvaluator<'a, F, E> {
+    func: &'a F,
+    bounds_simd: BoundsSimd,
+    simd_buf: Vec<Vec8>,
+    dims: usize,
+    policy: FailurePolicy,
+    rng: DefaultRng,
+    uniform: Uniform<f32>,
+    calls: u64,
+    failures: u64,
+    error: Option<ObjectiveError<E>>,
+}
+
+impl<'a, F, E> FallibleEvaluator<'a, F, E>
+where
+    F: Fn(&[Vec8]) -> Result<f32, E>,
+{
+    pub fn new(
+        func: &'a F,
+        range_min: &[f32],
+        range_max: &[f32],
+        policy: FailurePolicy,
+        rng: DefaultRng,
+    ) -> Self {
+        let bounds_simd = BoundsSimd::new(range_min, range_max);
+        let simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        Self {
+            func,
+            bounds_simd,
+            simd_buf,
+            dims: range_min.len(),
+            policy,
+            rng,
+            uniform: Uniform::new_inclusive(0.0, 1.0).unwrap(),
+            calls: 0,
+            failures: 0,
+            error: None,
+        }
+    }
+
+    /// Objective calls made so far, including retries and resamples.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    pub fn into_error(self) -> Option<ObjectiveError<E>> {
+        self.error
+    }
+}
+
+impl<F, E> PopulationEvaluator for FallibleEvaluator<'_, F, E>
+where
+    F: Fn(&[Vec8]) -> Result<f32, E>,
+{
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        let dims = self.dims;
+        for (p, f) in fitness.iter_mut().enumerate() {
+            *f = f32::INFINITY;
+            if self.error.is_some() {
+                continue;
+            }
+            let position = &mut positions[p * dims..(p + 1) * dims];
+            let mut attempt = 0;
+            loop {
+                self.bounds_simd.transform_into(position, &mut self.simd_buf);
+                self.calls += 1;
+                let error = match (self.func)(&self.simd_buf) {
+                    Ok(value) if !value.is_nan() => {
+                        *f = value;
+                        break;
+                    }
+                    Ok(_) => ObjectiveError::NaN,
+                    Err(e) => ObjectiveError::Failed(e),
+                };
+                self.failures += 1;
+                attempt += 1;
+                match self.policy {
+                    FailurePolicy::Retry { attempts } if attempt <= attempts => {}
+                    FailurePolicy::Resample { attempts } if attempt <= attempts => {
+                        for v in position.iter_mut() {
+                            *v = self.uniform.sample(&mut self.rng);
+                        }
+                    }
+                    FailurePolicy::Abort => {
+                        self.error = Some(error);
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    fn aborted(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// One candidate per call of a noisy objective; each candidate's fitness is the
+/// mean of its samples (see `Resampling`). Stops the run once `budget` objective
+/// calls have been made; candidates left without a sample score as worst.
+pub struct NoisyEvaluator<'a, F> {
+    func: &'a F,
+    bounds_simd: BoundsSimd,
+    simd_buf: Vec<Vec8>,
+    dims: usize,
+    resampling: Resampling,
+    budget: u64,
+    calls: u64,
+    sums: Vec<f32>,
+    counts: Vec<u32>,
+    order: Vec<usize>,
+}
+
+impl<'a, F> NoisyEvaluator<'a, F>
+where
+    F: Fn(&[Vec8]) -> f32,
+{
+    pub fn new(
+        func: &'a F,
+        range_min: &[f32],
+        range_max: &[f32],
+        resampling: Resampling,
+        budget: u64,
+    ) -> Self {
+        let bounds_simd = BoundsSimd::new(range_min, range_max);
+        let simd_buf = vec![Vec8::ZERO; bounds_simd.output_len()];
+        Self {
+            func,
+            bounds_simd,
+            simd_buf,
+            dims: range_min.len(),
+            resampling,
+            budget,
+            calls: 0,
+            sums: Vec::new(),
+            counts: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// Add samples to candidate `p` until it has `target`, within the budget.
+    fn sample(&mut self, positions: &[f32], p: usize, target: u32) {
+        let dims = self.dims;
+        if self.counts[p] >= target || self.calls >= self.budget {
+            return;
+        }
+        self.bounds_simd
+            .transform_into(&positions[p * dims..(p + 1) * dims], &mut self.simd_buf);
+        while self.counts[p] < target && self.calls < self.budget {
+            self.sums[p] += (self.func)(&self.simd_buf);
+            self.counts[p] += 1;
+            self.calls += 1;
+        }
+    }
+
+    fn mean(&self, p: usize) -> f32 {
+        if self.counts[p] == 0 {
+            return f32::INFINITY;
+        }
+        worst_if_nan(self.sums[p] / self.counts[p] as f32)
+    }
+}
+
+impl<F> PopulationEvaluator for NoisyEvaluator<'_, F>
+where
+    F: Fn(&[Vec8]) -> f32,
+{
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        let count = fitness.len();
+        self.sums.clear();
+        self.sums.resize(count, 0.0);
+        self.counts.clear();
+        self.counts.resize(count, 0);
+        let initial = match self.resampling {
+            Resampling::Fixed { samples } => samples.max(1),
+            Resampling::AdaptiveElite { .. } => 1,
+        };
+        for p in 0..count {
+            self.sample(positions, p, initial);
+        }
+        if let Resampling::AdaptiveElite { elite_fraction, max_samples } = self.resampling {
+            let elite = usize::min(count, f32::ceil(elite_fraction * count as f32) as usize);
+            self.order.clear();
+            self.order.extend(0..count);
+            let means: Vec<f32> = (0..count).map(|p| self.mean(p)).collect();
+            self.order.sort_by(|&a, &b| means[a].total_cmp(&means[b]));
+            for k in 0..elite {
+                let p = self.order[k];
+                self.sample(positions, p, max_samples);
+            }
+        }
+        for (p, f) in fitness.iter_mut().enumerate() {
+            *f = self.mean(p);
+        }
+    }
+
+    fn aborted(&self) -> bool {
+        self.calls >= self.budget
+    }
+}
+
+/// Whole populations per call of an `ExternalObjective`. A batch the evaluator
+/// could not answer aborts the run; NaN values count as failures.
+pub struct ExternalEvaluator<'a> {
+    objective: &'a mut ExternalObjective,
+    range_min: Vec<f32>,
+    range_max: Vec<f32>,
+    points: Vec<f32>,
+    failures: u64,
+    error: Option<ObjectiveError<ExternalError>>,
+}
+
+impl<'a> ExternalEvaluator<'a> {
+    pub fn new(objective: &'a mut ExternalObjective, range_min: &[f32], range_max: &[f32]) -> Self {
+        Self {
+            objective,
+            range_min: range_min.to_vec(),
+            range_max: range_max.to_vec(),
+            points: Vec::new(),
+            failures: 0,
+            error: None,
+        }
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    pub fn into_error(self) -> Option<ObjectiveError<ExternalError>> {
+        self.error
+    }
+}
+
+impl PopulationEvaluator for ExternalEvaluator<'_> {
+    fn evaluate(&mut self, positions: &mut [f32], fitness: &mut [f32]) {
+        fitness.fill(f32::INFINITY);
+        if self.error.is_some() {
+            return;
+        }
+        let dims = self.range_min.len();
+        if dims != self.objective.dims() {
+            let error = ExternalError::Dims {
+                dims: self.objective.dims(),
+                coordinates: dims,
+            };
+            self.error = Some(ObjectiveError::Failed(error));
+            return;
+        }
+        self.points.clear();
+        for position in positions.chunks_exact(dims) {
+            self.points.extend(
+                position
+                    .iter()
+                    .zip(self.range_min.iter().zip(&self.range_max))
+                    .map(|(p, (lo, hi))| lo + p * (hi - lo)),
+            );
+        }
+        match self.objective.evaluate_batch(&self.points) {
+            Ok(values) => {
+                for (f, value) in fitness.iter_mut().zip(values) {
+                    if value.is_nan() {
+                        self.failures += 1;
+                    }
+                    *f = worst_if_nan(value);
+                }
+            }
+            Err(e) => self.error = Some(ObjectiveError::Failed(e)),
+        }
+    }
+
+    fn aborted(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Shared body of `NoisyOptimizer::find_infimum_noisy_with_rng`: runs `search`
+/// with a `NoisyEvaluator` limited to `maxiter` calls, then estimates the final
+/// best from `final_samples` fresh evaluations.
+pub fn noisy_search<F, R, S>(
+    func: &F,
+    bounds: &[[f32; 2]],
+    maxiter: u64,
+    rng: &mut R,
+    handling: NoiseHandling,
+    search: S,
+) -> NoisyResult
+where
+    F: Fn(&[Vec8]) -> f32,
+    S: FnOnce(&mut NoisyEvaluator<'_, F>, &mut R) -> OptimizerResult,
+{
+    let (range_min, range_max) = split_bounds(bounds);
+    let mut evaluator =
+        NoisyEvaluator::new(func, &range_min, &range_max, handling.resampling, maxiter);
+    let mut result = search(&mut evaluator, rng);
+    result.nfev = evaluator.calls();
+
+    // result.x is already in bounds; map it through the identity box
+    let zeros = vec![0.0; bounds.len()];
+    let ones = vec![1.0; bounds.len()];
+    let x = fit_in_bounds_simd(&result.x, &zeros, &ones);
+    let n = handling.final_samples.max(1);
+    let values: Vec<f32> = (0..n).map(|_| func(&x)).collect();
+    let mean_f_x = values.iter().sum::<f32>() / n as f32;
+    let std_error = if n > 1 {
+        let var = values.iter().map(|v| (v - mean_f_x) * (v - mean_f_x)).sum::<f32>()
+            / (n - 1) as f32;
+        f32::sqrt(var / n as f32)
+    } else {
+        f32::INFINITY
+    };
+    NoisyResult {
+        result,
+        mean_f_x,
+        std_error,
+    }
+}
+
+/// Shared body of `FallibleOptimizer`: runs `search` with `rng` and a
+/// `FallibleEvaluator` that resamples from `resample_rng`.
+pub fn fallible_search<F, E, R, S>(
+    func: &F,
+    bounds: &[[f32; 2]],
+    rng: &mut R,
+    resample_rng: DefaultRng,
+    policy: FailurePolicy,
+    search: S,
+) -> FallibleResult<E>
+where
+    F: Fn(&[Vec8]) -> Result<f32, E>,
+    S: FnOnce(&mut FallibleEvaluator<'_, F, E>, &mut R) -> OptimizerResult,
+{
+    let (range_min, range_max) = split_bounds(bounds);
+    let mut evaluator = FallibleEvaluator::new(func, &range_min, &range_max, policy, resample_rng);
+    let mut result = search(&mut evaluator, rng);
+    result.nfev = evaluator.calls();
+    FallibleResult {
+        result,
+        failures: evaluator.failures(),
+        error: evaluator.into_error(),
+    }
+}
+
+/// Shared body of `ExternalOptimizer::find_infimum_external`: runs `search`
+/// with an `ExternalEvaluator` and the run's main RNG.
+pub fn external_search<S>(
+    objective: &mut ExternalObjective,
+    bounds: &[[f32; 2]],
+    seed: u64,
+    search: S,
+) -> FallibleResult<ExternalError>
+where
+    S: FnOnce(&mut ExternalEvaluator<'_>, &mut DefaultRng) -> OptimizerResult,
+{
+    let (range_min, range_max) = split_bounds(bounds);
+    let calls_before = objective.calls();
+    let mut evaluator = ExternalEvaluator::new(objective, &range_min, &range_max);
+    let mut rng: DefaultRng = stream_rng(seed, 0);
+    let mut result = search(&mut evaluator, &mut rng);
+    let failures = evaluator.failures();
+    let error = evaluator.into_error();
+    result.nfev = objective.calls() - calls_before;
+    FallibleResult {
+        result,
+        failures,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        functions::{hilly, HILLY_BOUNDS},
+        utils::{broadcast_simd, broadcast_simd_batch},
+    };
+
+    #[test]
+    fn test_batch_matches_single() {
+        let dims = 16;
+        let count = 11;
+        let bounds = HILLY_BOUNDS.repeat(dims / 2);
+        let range_min: Vec<f32> = bounds.iter().map(|b| b[0]).collect();
+        let range_max: Vec<f32> = bounds.iter().map(|b| b[1]).collect();
+        let mut positions: Vec<f32> = (0..count * dims)
+            .map(|i| (i * 37 % 101) as f32 / 100.0)
+            .collect();
+        let single_func = broadcast_simd(hilly);
+        let batch_func = broadcast_simd_batch(hilly);
+        let mut single_f = vec![0.0; count];
+        let mut batch_f = vec![0.0; count];
+        SingleEvaluator::new(&single_func, &range_min, &range_max)
+            .evaluate(&mut positions, &mut single_f);
+        BatchEvaluator::new(&batch_func, &range_min, &range_max)
+            .evaluate(&mut positions, &mut batch_f);
+        for (s, b) in single_f.iter().zip(&batch_f) {
+            assert!((s - b).abs() < 1e-5, "single={} batch={}", s, b);
+        }
+    }
+
+    #[test]
+    fn test_batch_supports_small_dims() {
+        let range_min = vec![-5.0; 2];
+        let range_max = vec![5.0; 2];
+        let func = broadcast_simd_batch(|x: Vec8, y: Vec8| x * x + y * y);
+        let mut positions = vec![0.5, 0.5, 1.0, 1.0, 0.0, 0.5];
+        let mut fitness = vec![0.0; 3];
+        BatchEvaluator::new(&func, &range_min, &range_max).evaluate(&mut positions, &mut fitness);
+        assert_eq!(fitness, vec![0.0, 50.0, 25.0]);
+    }
+
+    // Single-layout evaluators need 16 dims; only dims 0 and 1 (lane 0 of the
+    // first pair) vary
+    fn candidates(points: &[[f32; 2]]) -> Vec<f32> {
+        let mut positions = vec![0.0; points.len() * 16];
+        for (p, [a, b]) in points.iter().enumerate() {
+            positions[p * 16] = *a;
+            positions[p * 16 + 1] = *b;
+        }
+        positions
+    }
+
+    #[test]
+    fn test_nan_is_scored_as_worst() {
+        let range_min = vec![0.0; 16];
+        let range_max = vec![1.0; 16];
+        let func = |x: &[Vec8]| if x[0].0[0] > 0.5 { f32::NAN } else { 1.0 };
+        let mut positions = candidates(&[[0.25, 0.25], [0.75, 0.75]]);
+        let mut fitness = vec![0.0; 2];
+        SingleEvaluator::new(&func, &range_min, &range_max).evaluate(&mut positions, &mut fitness);
+        assert_eq!(fitness, vec![1.0, f32::INFINITY]);
+    }
+
+    fn fallible(
+        policy: FailurePolicy,
+        positions: &mut [f32],
+    ) -> (Vec<f32>, u64, u64, Option<ObjectiveError<&'static str>>) {
+        let range_min = vec![0.0; 16];
+        let range_max = vec![1.0; 16];
+        // Fails on the right half of the square, NaN on the top edge
+        let func = |x: &[Vec8]| -> Result<f32, &'static str> {
+            let (a, b) = (x[0].0[0], x[1].0[0]);
+            if b == 1.0 {
+                Ok(f32::NAN)
+            } else if a > 0.5 {
+                Err("crashed")
+            } else {
+                Ok(a + b)
+            }
+        };
+        let mut fitness = vec![0.0; positions.len() / 16];
+        let mut evaluator = FallibleEvaluator::new(
+            &func,
+            &range_min,
+            &range_max,
+            policy,
+            stream_rng(0, RESAMPLE_STREAM),
+        );
+        evaluator.evaluate(positions, &mut fitness);
+        let (calls, failures) = (evaluator.calls(), evaluator.failures());
+        (fitness, calls, failures, evaluator.into_error())
+    }
+
+    #[test]
+    fn test_fallible_worst_and_retry() {
+        let mut positions = candidates(&[[0.25, 0.25], [0.75, 0.25], [0.25, 1.0]]);
+        let (fitness, calls, failures, error) = fallible(FailurePolicy::Worst, &mut positions);
+        assert_eq!(fitness, vec![0.5, f32::INFINITY, f32::INFINITY]);
+        assert_eq!((calls, failures), (3, 2));
+        assert!(error.is_none());
+
+        let mut positions = candidates(&[[0.75, 0.25], [0.25, 0.25]]);
+        let (fitness, calls, failures, _) =
+            fallible(FailurePolicy::Retry { attempts: 2 }, &mut positions);
+        assert_eq!(fitness, vec![f32::INFINITY, 0.5]);
+        assert_eq!((calls, failures), (4, 3));
+    }
+
+    #[test]
+    fn test_fallible_resample_replaces_point() {
+        let mut positions = candidates(&[[0.75, 0.25]]);
+        let (fitness, _, failures, error) =
+            fallible(FailurePolicy::Resample { attempts: 100 }, &mut positions);
+        assert!(positions[0] <= 0.5);
+        assert_eq!(fitness[0], positions[0] + positions[1]);
+        assert!(failures >= 1);
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_fallible_abort_stops_evaluating() {
+        let mut positions = candidates(&[[0.25, 0.25], [0.25, 1.0], [0.25, 0.25]]);
+        let (fitness, calls, _, error) = fallible(FailurePolicy::Abort, &mut positions);
+        assert_eq!(fitness, vec![0.5, f32::INFINITY, f32::INFINITY]);
+        assert_eq!(calls, 2);
+        assert_eq!(error, Some(ObjectiveError::NaN));
+    }
+
+    fn counting(calls: &std::cell::Cell<u32>) -> impl Fn(&[Vec8]) -> f32 + '_ {
+        // Deterministic "noise": alternates +-1 around the first coordinate
+        move |x: &[Vec8]| {
+            calls.set(calls.get() + 1);
+            x[0].0[0] + if calls.get() % 2 == 0 { 1.0 } else { -1.0 }
+        }
+    }
+
+    #[test]
+    fn test_noisy_fixed_resampling_averages() {
+        let calls = std::cell::Cell::new(0);
+        let func = counting(&calls);
+        let (range_min, range_max) = (vec![0.0; 16], vec![1.0; 16]);
+        let mut evaluator = NoisyEvaluator::new(
+            &func,
+            &range_min,
+            &range_max,
+            Resampling::Fixed { samples: 2 },
+            100,
+        );
+        let mut positions = candidates(&[[0.25, 0.0], [0.5, 0.0]]);
+        let mut fitness = vec![0.0; 2];
+        evaluator.evaluate(&mut positions, &mut fitness);
+        assert_eq!(fitness, vec![0.25, 0.5]);
+        assert_eq!(evaluator.calls(), 4);
+        assert!(!evaluator.aborted());
+    }
+
+    #[test]
+    fn test_noisy_adaptive_resamples_elite_only() {
+        let calls = std::cell::Cell::new(0);
+        let func = counting(&calls);
+        let (range_min, range_max) = (vec![0.0; 16], vec![1.0; 16]);
+        let resampling = Resampling::AdaptiveElite {
+            elite_fraction: 0.25,
+            max_samples: 4,
+        };
+        let mut evaluator = NoisyEvaluator::new(&func, &range_min, &range_max, resampling, 100);
+        let mut positions = candidates(&[[0.5, 0.0], [0.75, 0.0], [0.25, 0.0], [1.0, 0.0]]);
+        let mut fitness = vec![0.0; 4];
+        evaluator.evaluate(&mut positions, &mut fitness);
+        // Single samples: 0.5-1, 0.75+1, 0.25-1, 1.0+1; the elite (index 2)
+        // gets 0.25-1, 0.25+1, 0.25-1 on top
+        assert_eq!(evaluator.calls(), 7);
+        assert_eq!(fitness[2], -0.25);
+        assert_eq!(fitness[..2], [-0.5, 1.75]);
+    }
+
+    #[test]
+    fn test_noisy_budget_stops_run() {
+        let func = |x: &[Vec8]| x[0].0[0];
+        let (range_min, range_max) = (vec![0.0; 16], vec![1.0; 16]);
+        let mut evaluator = NoisyEvaluator::new(
+            &func,
+            &range_min,
+            &range_max,
+            Resampling::Fixed { samples: 3 },
+            4,
+        );
+        let mut positions = candidates(&[[0.5, 0.0], [0.25, 0.0], [0.75, 0.0]]);
+        let mut fitness = vec![0.0; 3];
+        evaluator.evaluate(&mut positions, &mut fitness);
+        assert_eq!(fitness, vec![0.5, 0.25, f32::INFINITY]);
+        assert!(evaluator.aborted());
+    }
+}
@@ -0,0 +1,358 @@
+//! Benchmark experiment descriptions, read from JSON so that a new experiment
+//! needs no recompile. `benchmark.json` at the crate root is the default run:
+//!
+//! ```json
+//! {
+//!   "output": {"results": "benchmark_results.csv", "traces": "benchmark_traces.csv"},
+//!   "seeds": 200,
+//!   "stop_residual": 0.01,
+//!   "algorithms": ["ans", {"name": "ansr", "label": "ansr_wide", "params": {"sigma": 0.2}}],
+//!   "suites": [
+//!     {"suite": "easy", "dims": [64, 128], "maxiter": 50000},
+//!     {"suite": "cec2017", "dims": [16, 32], "maxiter_per_dim": 10000, "seeds": 51}
+//!   ]
+//! }
+//! ```
+//!
+//! `seeds` and `stop_residual` at the top are defaults that a suite may
+//! override. An algorithm is a name from `BENCHMARK_ALGORITHMS` or an object
+//! with a `label` for the output and `params` overriding the tuned ones of
+//! `benchmark_params::get_params`.
+
+use std::{collections::BTreeMap, fs};
+
+use crate::{
+    benchmark_params::{get_params, BENCHMARK_ALGORITHMS},
+    functions::{
+        Problem, BBOB_TEST_FUNCTIONS, CEC2017_TEST_FUNCTIONS, CEC2022_TEST_FUNCTIONS, COUPLED_TEST_FUNCTIONS, EASY_TEST_FUNCTIONS,
+        HARD_DISCRETE_FUNCTIONS, HARD_TEST_FUNCTIONS, LMMAES_ROTATED_TEST_FUNCTIONS, LMMAES_TEST_FUNCTIONS, MAIN_TEST_FUNCTIONS,
+        MEDIUM_PERIODIC_FUNCTIONS, MINI_TEST_FUNCTIONS, TERRAIN_TEST_FUNCTIONS, WEIERSTRASS_TEST_FUNCTIONS,
+    },
+    json::Json,
+};
+
+/// Suites the `benchmark` binary can run.
+pub const SUITES: [&str; 14] = [
+    "easy",
+    "main",
+    "mini",
+    "lmmaes",
+    "hard",
+    "hard_discrete",
+    "medium_terrain",
+    "medium_periodic",
+    "medium_weierstrass",
+    "lmmaes_rotated",
+    "coupled",
+    "bbob",
+    "cec2017",
+    "cec2022",
+];
+
+/// Problems of one of `SUITES` behind `dyn Problem`, so that suites of
+/// different types share one queue; none for other names.
+pub fn suite_problems(suite: &str) -> Option<BTreeMap<String, &'static dyn Problem>> {
+    fn erase<P: Problem>(functions: &'static BTreeMap<String, P>) -> BTreeMap<String, &'static dyn Problem> {
+        functions.iter().map(|(name, f)| (name.clone(), f as &dyn Problem)).collect()
+    }
+    Some(match suite {
+        "easy" => erase(&EASY_TEST_FUNCTIONS),
+        "main" => erase(&MAIN_TEST_FUNCTIONS),
+        "mini" => erase(&MINI_TEST_FUNCTIONS),
+        "lmmaes" => erase(&LMMAES_TEST_FUNCTIONS),
+        "hard" => erase(&HARD_TEST_FUNCTIONS),
+        "hard_discrete" => erase(&HARD_DISCRETE_FUNCTIONS),
+        "medium_terrain" => erase(&TERRAIN_TEST_FUNCTIONS),
+        "medium_periodic" => erase(&MEDIUM_PERIODIC_FUNCTIONS),
+        "medium_weierstrass" => erase(&WEIERSTRASS_TEST_FUNCTIONS),
+        "lmmaes_rotated" => erase(&LMMAES_ROTATED_TEST_FUNCTIONS),
+        "coupled" => erase(&COUPLED_TEST_FUNCTIONS),
+        "bbob" => erase(&BBOB_TEST_FUNCTIONS),
+        "cec2017" => erase(&CEC2017_TEST_FUNCTIONS),
+        "cec2022" => erase(&CEC2022_TEST_FUNCTIONS),
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Fixed(u64),
+    PerDim(u64),
+}
+
+impl Budget {
+    pub fn maxiter(&self, dim: usize) -> u64 {
+        match self {
+            Budget::Fixed(maxiter) => *maxiter,
+            Budget::PerDim(per_dim) => per_dim * dim as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuiteRun {
+    pub suite: String,
+    pub dims: Vec<usize>,
+    pub budget: Budget,
+    pub seeds: u64,
+    pub stop_residual: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgorithmRun {
+    /// Name in the output, the algorithm name unless given.
+    pub label: String,
+    pub algorithm: String,
+    pub params: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Experiment {
+    pub results: String,
+    pub traces: String,
+    pub algorithms: Vec<AlgorithmRun>,
+    pub suites: Vec<SuiteRun>,
+}
+
+impl Experiment {
+    /// The full benchmark of the README.
+    pub fn default_benchmark() -> Experiment {
+        let suite = |suite: &str, dims: &[usize], budget: Budget| SuiteRun {
+            suite: suite.to_string(),
+            dims: dims.to_vec(),
+            budget,
+            seeds: 200,
+            stop_residual: 0.01,
+        };
+        Experiment {
+            results: "benchmark_results.csv".to_string(),
+            traces: "benchmark_traces.csv".to_string(),
+            algorithms: default_algorithms(),
+            suites: vec![
+                suite("easy", &[64, 128, 256, 512, 1024], Budget::Fixed(50_000)),
+                suite("medium_terrain", &[64, 128, 256], Budget::Fixed(500_000)),
+                suite("medium_periodic", &[16, 32, 64], Budget::Fixed(500_000)),
+                suite("hard_discrete", &[16, 32, 64], Budget::Fixed(500_000)),
+                suite("coupled", &[64, 128, 256], Budget::Fixed(500_000)),
+                suite("lmmaes_rotated", &[64, 128, 256], Budget::Fixed(500_000)),
+                suite("bbob", &[16, 32, 64], Budget::Fixed(500_000)),
+                suite("cec2017", &[16, 32, 64], Budget::PerDim(10_000)),
+                suite("cec2022", &[16, 32], Budget::PerDim(50_000)),
+            ],
+        }
+    }
+
+    /// Reads an experiment file, or the `experiment` of the metadata stored
+    /// with earlier results, to run it again.
+    pub fn load(path: &str) -> Result<Experiment, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let json = Json::parse(&source).map_err(|e| format!("{path}: {e}"))?;
+        Experiment::from_json(json.get("experiment").unwrap_or(&json)).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn from_json(json: &Json) -> Result<Experiment, String> {
+        let output = json.get("output");
+        let path = |key: &str, default: &str| match output.and_then(|o| o.get(key)) {
+            None => Ok(default.to_string()),
+            Some(p) => p.as_str().map(str::to_string).ok_or(format!("`output.{key}` must be a string")),
+        };
+        let seeds = match json.get("seeds") {
+            None => 200,
+            Some(s) => s.as_u64().ok_or("`seeds` must be a non-negative integer")?,
+        };
+        let stop_residual = match json.get("stop_residual") {
+            None => 0.01,
+            Some(s) => s.as_f64().ok_or("`stop_residual` must be a number")? as f32,
+        };
+        let algorithms = match json.get("algorithms") {
+            None => default_algorithms(),
+            Some(a) => a
+                .as_array()
+                .ok_or("`algorithms` must be an array")?
+                .iter()
+                .map(algorithm_from_json)
+                .collect::<Result<_, _>>()?,
+        };
+        let mut labels: Vec<&str> = Vec::new();
+        for a in &algorithms {
+            if labels.contains(&a.label.as_str()) {
+                return Err(format!("duplicate algorithm label `{}`", a.label));
+            }
+            labels.push(&a.label);
+        }
+        let suites = json
+            .get("suites")
+            .and_then(Json::as_array)
+            .ok_or("`suites` must be an array")?
+            .iter()
+            .map(|s| suite_from_json(s, seeds, stop_residual))
+            .collect::<Result<_, _>>()?;
+        Ok(Experiment {
+            results: path("results", "benchmark_results.csv")?,
+            traces: path("traces", "benchmark_traces.csv")?,
+            algorithms,
+            suites,
+        })
+    }
+
+    /// The experiment with every default filled in, as stored in the metadata
+    /// of the results.
+    pub fn to_json(&self) -> Json {
+        let algorithms = self.algorithms.iter().map(|a| {
+            let params = a.params.iter().map(|(k, &v)| (k.clone(), Json::from(v)));
+            Json::object([
+                ("name", Json::from(a.algorithm.as_str())),
+                ("label", Json::from(a.label.as_str())),
+                ("params", Json::object(params)),
+            ])
+        });
+        let suites = self.suites.iter().map(|s| {
+            let budget = match s.budget {
+                Budget::Fixed(maxiter) => ("maxiter", Json::from(maxiter)),
+                Budget::PerDim(per_dim) => ("maxiter_per_dim", Json::from(per_dim)),
+            };
+            Json::object([
+                ("suite", Json::from(s.suite.as_str())),
+                ("dims", Json::Array(s.dims.iter().map(|&d| Json::from(d)).collect())),
+                budget,
+                ("seeds", Json::from(s.seeds)),
+                ("stop_residual", Json::from(s.stop_residual)),
+            ])
+        });
+        Json::object([
+            (
+                "output",
+                Json::object([
+                    ("results", Json::from(self.results.as_str())),
+                    ("traces", Json::from(self.traces.as_str())),
+                ]),
+            ),
+            ("algorithms", Json::Array(algorithms.collect())),
+            ("suites", Json::Array(suites.collect())),
+        ])
+    }
+}
+
+fn default_algorithms() -> Vec<AlgorithmRun> {
+    BENCHMARK_ALGORITHMS
+        .iter()
+        .map(|name| AlgorithmRun {
+            label: name.to_string(),
+            algorithm: name.to_string(),
+            params: BTreeMap::new(),
+        })
+        .collect()
+}
+
+fn algorithm_from_json(json: &Json) -> Result<AlgorithmRun, String> {
+    if let Some(name) = json.as_str() {
+        return algorithm_from_json(&Json::object([("name", Json::from(name))]));
+    }
+    let algorithm = json
+        .get("name")
+        .and_then(Json::as_str)
+        .ok_or("an algorithm must be a name or an object with a `name`")?
+        .to_string();
+    let label = match json.get("label") {
+        None => algorithm.clone(),
+        Some(l) => l.as_str().ok_or("`label` must be a string")?.to_string(),
+    };
+    let mut params = BTreeMap::new();
+    if let Some(members) = json.get("params") {
+        for (k, v) in members.as_object().ok_or("`params` must be an object")? {
+            let v = v.as_f64().ok_or_else(|| format!("parameter `{k}` must be a number"))?;
+            params.insert(k.clone(), v as f32);
+        }
+    }
+    // Rejects unknown algorithms and parameters before anything runs
+    get_params("", 2).apply_overrides(&algorithm, &params)?;
+    Ok(AlgorithmRun { label, algorithm, params })
+}
+
+fn suite_from_json(json: &Json, seeds: u64, stop_residual: f32) -> Result<SuiteRun, String> {
+    let suite = json.get("suite").and_then(Json::as_str).ok_or("a suite needs a `suite` name")?;
+    if !SUITES.contains(&suite) {
+        return Err(format!("unknown suite `{suite}`, expected one of {}", SUITES.join(", ")));
+    }
+    let dims = json
+        .get("dims")
+        .and_then(Json::as_array)
+        .and_then(|dims| dims.iter().map(|d| d.as_u64().filter(|&d| d > 0).map(|d| d as usize)).collect::<Option<Vec<_>>>())
+        .ok_or_else(|| format!("`{suite}`: `dims` must be an array of positive integers"))?;
+    let budget = match (json.get("maxiter"), json.get("maxiter_per_dim")) {
+        (Some(m), None) => Budget::Fixed(m.as_u64().ok_or_else(|| format!("`{suite}`: `maxiter` must be an integer"))?),
+        (None, Some(m)) => Budget::PerDim(m.as_u64().ok_or_else(|| format!("`{suite}`: `maxiter_per_dim` must be an integer"))?),
+        _ => return Err(format!("`{suite}`: give either `maxiter` or `maxiter_per_dim`")),
+    };
+    let seeds = match json.get("seeds") {
+        None => seeds,
+        Some(s) => s.as_u64().ok_or_else(|| format!("`{suite}`: `seeds` must be a non-negative integer"))?,
+    };
+    let stop_residual = match json.get("stop_residual") {
+        None => stop_residual,
+        Some(s) => s.as_f64().ok_or_else(|| format!("`{suite}`: `stop_residual` must be a number"))? as f32,
+    };
+    Ok(SuiteRun {
+        suite: suite.to_string(),
+        dims,
+        budget,
+        seeds,
+        stop_residual,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experiment_from_json() {
+        let json = Json::parse(
+            r#"{"output": {"results": "r.csv"}, "seeds": 5,
+                "algorithms": ["de", {"name": "ansr", "label": "ansr_wide", "params": {"sigma": 0.2}}],
+                "suites": [{"suite": "bbob", "dims": [16], "maxiter": 1000},
+                           {"suite": "cec2017", "dims": [16, 32], "maxiter_per_dim": 100, "seeds": 2, "stop_residual": 1}]}"#,
+        )
+        .unwrap();
+        let experiment = Experiment::from_json(&json).unwrap();
+        assert_eq!(experiment.results, "r.csv");
+        assert_eq!(experiment.traces, "benchmark_traces.csv");
+        assert_eq!(experiment.algorithms[1].label, "ansr_wide");
+        assert_eq!(experiment.algorithms[1].params["sigma"], 0.2);
+        assert_eq!(experiment.suites[0].seeds, 5);
+        assert_eq!(experiment.suites[0].stop_residual, 0.01);
+        assert_eq!(experiment.suites[1].budget.maxiter(32), 3200);
+        assert_eq!(experiment.suites[1].stop_residual, 1.0);
+        // The stored form reads back the same
+        assert_eq!(Experiment::from_json(&experiment.to_json()).unwrap(), experiment);
+    }
+
+    #[test]
+    fn test_experiment_errors() {
+        let error = |source: &str| Experiment::from_json(&Json::parse(source).unwrap()).unwrap_err();
+        assert!(error(r#"{"suites": [{"suite": "nope", "dims": [2], "maxiter": 1}]}"#).contains("unknown suite"));
+        assert!(error(r#"{"suites": [{"suite": "bbob", "dims": [2]}]}"#).contains("maxiter"));
+        assert!(error(r#"{"algorithms": [{"name": "de", "params": {"sigma": 1}}], "suites": []}"#).contains("`sigma`"));
+        assert!(error(r#"{"algorithms": ["de", "de"], "suites": []}"#).contains("duplicate"));
+        assert!(error(r#"{"algorithms": [{"name": "ansr", "params": {"popsize": 0}}], "suites": []}"#).contains("`popsize`"));
+        assert!(error(r#"{"algorithms": [{"name": "shade", "params": {"popsize": 32.5}}], "suites": []}"#).contains("integer"));
+        assert!(error(r#"{"algorithms": [{"name": "shade", "params": {"h": -1}}], "suites": []}"#).contains("`h`"));
+    }
+
+    #[test]
+    fn test_params_round_trip_through_overrides() {
+        for algorithm in BENCHMARK_ALGORITHMS {
+            let tuned = get_params("cec2017", 32).params(algorithm);
+            assert!(!tuned.is_empty());
+            let mut params = get_params("", 2);
+            params.apply_overrides(algorithm, &tuned).unwrap();
+            assert_eq!(params.params(algorithm), tuned, "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn test_default_config_file_matches_default_benchmark() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/benchmark.json");
+        assert_eq!(Experiment::load(path).unwrap(), Experiment::default_benchmark());
+    }
+}
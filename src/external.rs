@@ -0,0 +1,241 @@
+//! Objectives evaluated by an external program.
+//!
+//! The command is launched once and kept running. For every population it
+//! receives on stdin a header line `<count> <dims>` followed by `count` lines
+//! of `dims` space-separated coordinates (dimension order, within bounds), and
+//! answers on stdout with `count` lines holding one value each. A value of
+//! `nan` marks a failed candidate, which is scored as worst. Closing stdin
+//! asks the program to exit. Its stderr is passed through.
+//!
+//! If the program exits, stops answering within `ExternalConfig::timeout` or
+//! prints something that is not a number, it is killed, restarted and sent the
+//! same batch again, at most `max_restarts` times in a row.
+
+use std::{
+    fmt,
+    io::{BufRead, BufReader, BufWriter, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Time a program gets to exit after its stdin is closed before it is killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExternalConfig {
+    /// Limit for answering one batch.
+    pub timeout: Duration,
+    /// Restarts allowed for one batch before the run is aborted.
+    pub max_restarts: u32,
+}
+
+impl ExternalConfig {
+    pub const DEFAULT: ExternalConfig = ExternalConfig {
+        timeout: Duration::from_secs(60),
+        max_restarts: 3,
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalError {
+    /// The command could not be started.
+    Spawn(String),
+    Timeout,
+    /// The program exited or closed its pipes.
+    Crashed,
+    /// A reply line was not a number.
+    Protocol(String),
+    /// An objective for points without coordinates, or points that do not
+    /// match its dimension count.
+    Dims { dims: usize, coordinates: usize },
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalError::Spawn(e) => write!(f, "failed to start evaluator: {e}"),
+            ExternalError::Timeout => write!(f, "evaluator timed out"),
+            ExternalError::Crashed => write!(f, "evaluator exited"),
+            ExternalError::Protocol(line) => write!(f, "invalid evaluator reply `{line}`"),
+            ExternalError::Dims { dims: 0, .. } => write!(f, "points need at least one dim"),
+            ExternalError::Dims { dims, coordinates } => write!(f, "{coordinates} coordinates for points of {dims} dims"),
+        }
+    }
+}
+
+impl std::error::Error for ExternalError {}
+
+struct Process {
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+    lines: Receiver<String>,
+}
+
+impl Process {
+    fn spawn(command: &[String]) -> Result<Self, ExternalError> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| ExternalError::Spawn("empty command".to_string()))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| ExternalError::Spawn(format!("{program}: {e}")))?;
+        let stdin = BufWriter::new(child.stdin.take().unwrap());
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        // Read on a thread so that replies can be awaited with a timeout
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Process {
+            child,
+            stdin: Some(stdin),
+            lines,
+        })
+    }
+
+    fn send(&mut self, points: &[f32], dims: usize) -> std::io::Result<()> {
+        let stdin = self.stdin.as_mut().unwrap();
+        writeln!(stdin, "{} {}", points.len() / dims, dims)?;
+        for point in points.chunks_exact(dims) {
+            let mut values = point.iter();
+            if let Some(v) = values.next() {
+                write!(stdin, "{v}")?;
+            }
+            for v in values {
+                write!(stdin, " {v}")?;
+            }
+            writeln!(stdin)?;
+        }
+        stdin.flush()
+    }
+
+    fn receive(&self, count: usize, timeout: Duration) -> Result<Vec<f32>, ExternalError> {
+        let deadline = Instant::now() + timeout;
+        let mut values = Vec::with_capacity(count);
+        while values.len() < count {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = self.lines.recv_timeout(remaining).map_err(|e| match e {
+                RecvTimeoutError::Timeout => ExternalError::Timeout,
+                RecvTimeoutError::Disconnected => ExternalError::Crashed,
+            })?;
+            let line = line.trim();
+            values.push(line.parse().map_err(|_| ExternalError::Protocol(line.to_string()))?);
+        }
+        Ok(values)
+    }
+
+    fn kill(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // Closing stdin is the shutdown request
+        self.stdin = None;
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+/// A running external evaluator; see the module docs for the protocol.
+pub struct ExternalObjective {
+    command: Vec<String>,
+    dims: usize,
+    config: ExternalConfig,
+    process: Option<Process>,
+    calls: u64,
+    restarts: u64,
+}
+
+impl ExternalObjective {
+    /// Start `command` (program followed by its arguments) for points of
+    /// `dims` coordinates.
+    pub fn new(command: &[String], dims: usize, config: ExternalConfig) -> Result<Self, ExternalError> {
+        if dims == 0 {
+            return Err(ExternalError::Dims { dims, coordinates: 0 });
+        }
+        Ok(ExternalObjective {
+            command: command.to_vec(),
+            dims,
+            config,
+            process: Some(Process::spawn(command)?),
+            calls: 0,
+            restarts: 0,
+        })
+    }
+
+    /// Values of the points in `points` (`dims` coordinates each, dimension
+    /// order). Failed candidates are NaN.
+    pub fn evaluate_batch(&mut self, points: &[f32]) -> Result<Vec<f32>, ExternalError> {
+        let dims = self.dims;
+        if !points.len().is_multiple_of(dims) {
+            return Err(ExternalError::Dims { dims, coordinates: points.len() });
+        }
+        let count = points.len() / dims;
+        let mut attempt = 0;
+        loop {
+            let mut process = match self.process.take() {
+                Some(process) => process,
+                None => Process::spawn(&self.command)?,
+            };
+            self.calls += count as u64;
+            let result = process
+                .send(points, dims)
+                .map_err(|_| ExternalError::Crashed)
+                .and_then(|_| process.receive(count, self.config.timeout));
+            match result {
+                Ok(values) => {
+                    self.process = Some(process);
+                    return Ok(values);
+                }
+                Err(error) => {
+                    process.kill();
+                    attempt += 1;
+                    if attempt > self.config.max_restarts {
+                        return Err(error);
+                    }
+                    self.restarts += 1;
+                }
+            }
+        }
+    }
+
+    /// Value of a single point.
+    pub fn evaluate(&mut self, x: &[f32]) -> Result<f32, ExternalError> {
+        if x.len() != self.dims {
+            return Err(ExternalError::Dims { dims: self.dims, coordinates: x.len() });
+        }
+        Ok(self.evaluate_batch(x)?[0])
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Candidates sent so far, including re-sent batches.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+}
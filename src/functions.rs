@@ -4,12 +4,144 @@ use once_cell::sync::Lazy;
 use simd_vector::Vec8;
 use simd_vector::fast::FastMath;
 
-use crate::utils::Vec8Ext;
+use crate::utils::{broadcast_simd, pack_pair_layout, Vec8Ext};
+
+pub mod bbob;
+pub mod cec;
+pub mod coupled;
+pub mod expression;
+pub mod multi_objective;
+pub mod transform;
+
+pub use bbob::BBOB_TEST_FUNCTIONS;
+pub use cec::{CEC2017_TEST_FUNCTIONS, CEC2022_TEST_FUNCTIONS};
+pub use coupled::COUPLED_TEST_FUNCTIONS;
+pub use multi_objective::{DTLZ_TEST_FUNCTIONS, ZDT_TEST_FUNCTIONS};
+pub use transform::LMMAES_ROTATED_TEST_FUNCTIONS;
+
+/// Objective of a `Problem` at a fixed dimension.
+pub type Objective<'a> = Box<dyn Fn(&[Vec8]) -> f32 + Sync + 'a>;
+
+/// A benchmark problem the runner can instantiate for any supported dimension.
+pub trait Problem: Sync {
+    /// Objective over the optimizer's SIMD pair layout (see `fit_in_bounds_simd`).
+    fn objective(&self, dims: usize) -> Objective<'_>;
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]>;
+
+    /// Known optimal value; success is judged on `f_x - f_opt`.
+    fn f_opt(&self, _dims: usize) -> f32 {
+        0.0
+    }
+
+    /// A known minimiser, in dimension order, if there is one.
+    fn x_opt(&self, _dims: usize) -> Option<Vec<f32>> {
+        None
+    }
+
+    /// Recommended precision: a run has solved the problem once
+    /// `f_x - f_opt <= target`.
+    fn target(&self, _dims: usize) -> f32 {
+        DEFAULT_TARGET
+    }
+
+    /// Evaluate the objective at `x_opt` and check that it is within
+    /// `target` of `f_opt`. Problems without a known minimiser pass.
+    fn check_optimum(&self, dims: usize) -> Result<(), String> {
+        let Some(x_opt) = self.x_opt(dims) else {
+            return Ok(());
+        };
+        let value = (self.objective(dims))(&pack_pair_layout(&x_opt));
+        let (f_opt, target) = (self.f_opt(dims), self.target(dims));
+        if (value - f_opt).abs() <= target {
+            Ok(())
+        } else {
+            Err(format!("f(x_opt) = {value}, expected {f_opt} within {target}"))
+        }
+    }
+}
+
+/// Target precision used when a problem does not recommend one.
+pub const DEFAULT_TARGET: f32 = 0.01;
+
+/// Run `check_optimum` on every function of a test set, returning the
+/// failures as `"name: reason"`.
+pub fn check_optima<P: Problem>(functions: &BTreeMap<String, P>, dims: usize) -> Vec<String> {
+    functions
+        .iter()
+        .filter_map(|(name, p)| p.check_optimum(dims).err().map(|e| format!("{name}: {e}")))
+        .collect()
+}
+
+/// Known global minimum of a `TestFunction` on one coordinate pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Optimum {
+    /// Minimiser of the pair, repeated over the dimensions.
+    pub x: [f32; 2],
+    pub f: f32,
+    /// Recommended target precision.
+    pub target: f32,
+}
+
+/// Objective of a `TestFunction`.
+#[derive(Clone, Copy)]
+pub enum TestFn {
+    /// 2D function applied to independent coordinate pairs and averaged by
+    /// `broadcast_simd`, so the objective is separable across pairs.
+    Pairwise(fn(Vec8, Vec8) -> Vec8),
+    /// Function of the full vector, in the pair layout of `fit_in_bounds_simd`.
+    Full(fn(&[Vec8]) -> f32),
+}
+
+impl TestFn {
+    pub fn evaluate(&self, x: &[Vec8]) -> f32 {
+        match *self {
+            TestFn::Pairwise(func) => broadcast_simd(func)(x),
+            TestFn::Full(func) => func(x),
+        }
+    }
+
+    /// The 2D function of a `Pairwise` objective, for the per-pair helpers
+    /// (`broadcast_simd_batch`, `broadcast_scalar`, plotting).
+    pub fn pairwise(&self) -> Option<fn(Vec8, Vec8) -> Vec8> {
+        match *self {
+            TestFn::Pairwise(func) => Some(func),
+            TestFn::Full(_) => None,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct TestFunction {
-    pub func: fn(Vec8, Vec8) -> Vec8,
+    pub func: TestFn,
+    /// Bounds of one coordinate pair, repeated over the dimensions.
     pub bounds: [[f32; 2]; 2],
+    pub optimum: Option<Optimum>,
+}
+
+impl Problem for TestFunction {
+    fn objective(&self, _dims: usize) -> Objective<'_> {
+        match self.func {
+            TestFn::Pairwise(func) => Box::new(broadcast_simd(func)),
+            TestFn::Full(func) => Box::new(func),
+        }
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        self.bounds.repeat(dims / 2)
+    }
+
+    fn f_opt(&self, _dims: usize) -> f32 {
+        self.optimum.map_or(0.0, |o| o.f)
+    }
+
+    fn x_opt(&self, dims: usize) -> Option<Vec<f32>> {
+        self.optimum.map(|o| o.x.repeat(dims / 2))
+    }
+
+    fn target(&self, _dims: usize) -> f32 {
+        self.optimum.map_or(DEFAULT_TARGET, |o| o.target)
+    }
 }
 
 fn scale(v: Vec8, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> Vec8 {
@@ -19,6 +151,7 @@ fn scale(v: Vec8, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> Vec8
 }
 
 pub const SHIFTED_SPHERE_BOUNDS: [[f32; 2]; 2] = [[-10.0, 10.0], [-10.0, 10.0]];
+pub const SHIFTED_SPHERE_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-PI, -PI], f: 0.0, target: 0.01 });
 
 pub fn shifted_sphere(x: Vec8, y: Vec8) -> Vec8 {
     let x = x + PI;
@@ -96,6 +229,7 @@ pub fn weierstrass(x: Vec8) -> Vec8 {
 }
 
 pub const SHIFTED_WEIERSTRASS_BOUNDS: [[f32; 2]; 2] = [[-10.0, 10.0], [-10.0, 10.0]];
+pub const SHIFTED_WEIERSTRASS_OPTIMUM: Option<Optimum> = Some(Optimum { x: [1.0 - PI, 1.0 - PI], f: 0.0, target: 0.01 });
 
 pub fn shifted_weierstrass(x: Vec8, y: Vec8) -> Vec8 {
     let x = x + PI;
@@ -105,6 +239,7 @@ pub fn shifted_weierstrass(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const HILLY_BOUNDS: [[f32; 2]; 2] = [[-3.0, 3.0], [-3.0, 3.0]];
+pub const HILLY_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-1.480_905_4, 0.625_411_2], f: 0.0, target: 0.01 });
 
 pub fn hilly(x: Vec8, y: Vec8) -> Vec8 {
     let result = 20.0 + x.square() + y.square()
@@ -121,6 +256,7 @@ pub fn hilly(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const FOREST_BOUNDS: [[f32; 2]; 2] = [[-43.50, -39.0], [-47.35, -40.0]];
+pub const FOREST_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-40.840_706, -41.982_296], f: 0.0, target: 0.01 });
 
 pub fn forest(x: Vec8, y: Vec8) -> Vec8 {
     let a = ((x - 1.13).abs() + (y - 2.0).abs()).sqrt().sin();
@@ -136,6 +272,7 @@ pub fn forest(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const MEGACITY_BOUNDS: [[f32; 2]; 2] = [[-10.0, -2.0], [-10.5, 10.0]];
+pub const MEGACITY_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-3.135_754_6, 2.006_136_4], f: 0.0, target: 0.01 });
 
 pub fn megacity(x: Vec8, y: Vec8) -> Vec8 {
     let a = ((x - 1.13).abs() + (y - 2.0).abs()).sqrt().sin();
@@ -150,6 +287,7 @@ pub fn megacity(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const SHUBERT_BOUNDS: [[f32; 2]; 2] = [[-10.0, 10.0], [-10.0, 10.0]];
+pub const SHUBERT_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-1.425_13, -0.800_32], f: 0.0, target: 0.01 });
 
 pub fn shubert(x: Vec8, y: Vec8) -> Vec8 {
     let mut sum_x = Vec8::splat(0.0);
@@ -165,6 +303,7 @@ pub fn shubert(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const SPHERE_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const SPHERE_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
 
 pub fn sphere(x: Vec8, y: Vec8) -> Vec8 {
     let result = x.square() + y.square();
@@ -172,6 +311,7 @@ pub fn sphere(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const ELLIPSOID_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const ELLIPSOID_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
 
 pub fn ellipsoid(x: Vec8, y: Vec8) -> Vec8 {
     let result = x.square() + 1_000_000.0 * y.square();
@@ -179,6 +319,7 @@ pub fn ellipsoid(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const ROSENBROCK_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const ROSENBROCK_OPTIMUM: Option<Optimum> = Some(Optimum { x: [1.0, 1.0], f: 0.0, target: 0.01 });
 
 pub fn rosenbrock(x: Vec8, y: Vec8) -> Vec8 {
     let result = 100.0 * (x.square() - y).square() + (x - 1.0).square();
@@ -186,6 +327,7 @@ pub fn rosenbrock(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const DISCUS_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const DISCUS_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
 
 pub fn discus(x: Vec8, y: Vec8) -> Vec8 {
     let result = 1_000_000.0 * x.square() + y.square();
@@ -193,6 +335,7 @@ pub fn discus(x: Vec8, y: Vec8) -> Vec8 {
 }
 
 pub const DIFFERENT_POWERS_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const DIFFERENT_POWERS_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
 
 pub fn different_powers(x: Vec8, y: Vec8) -> Vec8 {
     let y_sq = y.square();
@@ -205,8 +348,9 @@ pub static WEIERSTRASS_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = La
     m.insert(
         "shifted_weierstrass".to_string(),
         TestFunction {
-            func: shifted_weierstrass,
+            func: TestFn::Pairwise(shifted_weierstrass),
             bounds: SHIFTED_WEIERSTRASS_BOUNDS,
+            optimum: SHIFTED_WEIERSTRASS_OPTIMUM,
         },
     );
     m
@@ -215,34 +359,34 @@ pub static WEIERSTRASS_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = La
 /// Easy unimodal functions — small popsize, high sigma
 pub static EASY_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new(|| {
     let mut m = BTreeMap::new();
-    m.insert("shifted_sphere".to_string(), TestFunction { func: shifted_sphere, bounds: SHIFTED_SPHERE_BOUNDS });
-    m.insert("sphere".to_string(), TestFunction { func: sphere, bounds: SPHERE_BOUNDS });
-    m.insert("ellipsoid".to_string(), TestFunction { func: ellipsoid, bounds: ELLIPSOID_BOUNDS });
-    m.insert("discus".to_string(), TestFunction { func: discus, bounds: DISCUS_BOUNDS });
-    m.insert("different_powers".to_string(), TestFunction { func: different_powers, bounds: DIFFERENT_POWERS_BOUNDS });
-    m.insert("rosenbrock".to_string(), TestFunction { func: rosenbrock, bounds: ROSENBROCK_BOUNDS });
+    m.insert("shifted_sphere".to_string(), TestFunction { func: TestFn::Pairwise(shifted_sphere), bounds: SHIFTED_SPHERE_BOUNDS, optimum: SHIFTED_SPHERE_OPTIMUM });
+    m.insert("sphere".to_string(), TestFunction { func: TestFn::Pairwise(sphere), bounds: SPHERE_BOUNDS, optimum: SPHERE_OPTIMUM });
+    m.insert("ellipsoid".to_string(), TestFunction { func: TestFn::Pairwise(ellipsoid), bounds: ELLIPSOID_BOUNDS, optimum: ELLIPSOID_OPTIMUM });
+    m.insert("discus".to_string(), TestFunction { func: TestFn::Pairwise(discus), bounds: DISCUS_BOUNDS, optimum: DISCUS_OPTIMUM });
+    m.insert("different_powers".to_string(), TestFunction { func: TestFn::Pairwise(different_powers), bounds: DIFFERENT_POWERS_BOUNDS, optimum: DIFFERENT_POWERS_OPTIMUM });
+    m.insert("rosenbrock".to_string(), TestFunction { func: TestFn::Pairwise(rosenbrock), bounds: ROSENBROCK_BOUNDS, optimum: ROSENBROCK_OPTIMUM });
     m
 });
 
 /// Hard multimodal functions — large popsize, lower sigma
 pub static HARD_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new(|| {
     let mut m = BTreeMap::new();
-    m.insert("forest".to_string(), TestFunction { func: forest, bounds: FOREST_BOUNDS });
-    m.insert("shifted_weierstrass".to_string(), TestFunction { func: shifted_weierstrass, bounds: SHIFTED_WEIERSTRASS_BOUNDS });
-    m.insert("hilly".to_string(), TestFunction { func: hilly, bounds: HILLY_BOUNDS });
-    m.insert("megacity".to_string(), TestFunction { func: megacity, bounds: MEGACITY_BOUNDS });
+    m.insert("forest".to_string(), TestFunction { func: TestFn::Pairwise(forest), bounds: FOREST_BOUNDS, optimum: FOREST_OPTIMUM });
+    m.insert("shifted_weierstrass".to_string(), TestFunction { func: TestFn::Pairwise(shifted_weierstrass), bounds: SHIFTED_WEIERSTRASS_BOUNDS, optimum: SHIFTED_WEIERSTRASS_OPTIMUM });
+    m.insert("hilly".to_string(), TestFunction { func: TestFn::Pairwise(hilly), bounds: HILLY_BOUNDS, optimum: HILLY_OPTIMUM });
+    m.insert("megacity".to_string(), TestFunction { func: TestFn::Pairwise(megacity), bounds: MEGACITY_BOUNDS, optimum: MEGACITY_OPTIMUM });
     m
 });
 
 pub static HARD_DISCRETE_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new(|| {
     let mut m = BTreeMap::new();
-    m.insert("megacity".to_string(), TestFunction { func: megacity, bounds: MEGACITY_BOUNDS });
+    m.insert("megacity".to_string(), TestFunction { func: TestFn::Pairwise(megacity), bounds: MEGACITY_BOUNDS, optimum: MEGACITY_OPTIMUM });
     m
 });
 
 pub static MEDIUM_PERIODIC_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new(|| {
     let mut m = BTreeMap::new();
-    m.insert("shubert".to_string(), TestFunction { func: shubert, bounds: SHUBERT_BOUNDS });
+    m.insert("shubert".to_string(), TestFunction { func: TestFn::Pairwise(shubert), bounds: SHUBERT_BOUNDS, optimum: SHUBERT_OPTIMUM });
     m
 });
 
@@ -251,22 +395,25 @@ pub static MINI_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new
     m.insert(
         "shifted_sphere".to_string(),
         TestFunction {
-            func: shifted_sphere,
+            func: TestFn::Pairwise(shifted_sphere),
             bounds: SHIFTED_SPHERE_BOUNDS,
+            optimum: SHIFTED_SPHERE_OPTIMUM,
         },
     );
     m.insert(
         "hilly".to_string(),
         TestFunction {
-            func: hilly,
+            func: TestFn::Pairwise(hilly),
             bounds: HILLY_BOUNDS,
+            optimum: HILLY_OPTIMUM,
         },
     );
     m.insert(
         "forest".to_string(),
         TestFunction {
-            func: forest,
+            func: TestFn::Pairwise(forest),
             bounds: FOREST_BOUNDS,
+            optimum: FOREST_OPTIMUM,
         },
     );
     m
@@ -277,15 +424,17 @@ pub static TERRAIN_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::
     m.insert(
         "hilly".to_string(),
         TestFunction {
-            func: hilly,
+            func: TestFn::Pairwise(hilly),
             bounds: HILLY_BOUNDS,
+            optimum: HILLY_OPTIMUM,
         },
     );
     m.insert(
         "forest".to_string(),
         TestFunction {
-            func: forest,
+            func: TestFn::Pairwise(forest),
             bounds: FOREST_BOUNDS,
+            optimum: FOREST_OPTIMUM,
         },
     );
     m
@@ -296,36 +445,41 @@ pub static MAIN_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new
     m.insert(
         "shifted_sphere".to_string(),
         TestFunction {
-            func: shifted_sphere,
+            func: TestFn::Pairwise(shifted_sphere),
             bounds: SHIFTED_SPHERE_BOUNDS,
+            optimum: SHIFTED_SPHERE_OPTIMUM,
         },
     );
     m.insert(
         "shifted_weierstrass".to_string(),
         TestFunction {
-            func: shifted_weierstrass,
+            func: TestFn::Pairwise(shifted_weierstrass),
             bounds: SHIFTED_WEIERSTRASS_BOUNDS,
+            optimum: SHIFTED_WEIERSTRASS_OPTIMUM,
         },
     );
     m.insert(
         "hilly".to_string(),
         TestFunction {
-            func: hilly,
+            func: TestFn::Pairwise(hilly),
             bounds: HILLY_BOUNDS,
+            optimum: HILLY_OPTIMUM,
         },
     );
     m.insert(
         "forest".to_string(),
         TestFunction {
-            func: forest,
+            func: TestFn::Pairwise(forest),
             bounds: FOREST_BOUNDS,
+            optimum: FOREST_OPTIMUM,
         },
     );
     m.insert(
         "megacity".to_string(),
         TestFunction {
-            func: megacity,
+            func: TestFn::Pairwise(megacity),
             bounds: MEGACITY_BOUNDS,
+            optimum: MEGACITY_OPTIMUM,
         },
     );
     m
@@ -336,36 +490,41 @@ pub static LMMAES_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::n
     m.insert(
         "sphere".to_string(),
         TestFunction {
-            func: sphere,
+            func: TestFn::Pairwise(sphere),
             bounds: SPHERE_BOUNDS,
+            optimum: SPHERE_OPTIMUM,
         },
     );
     m.insert(
         "ellipsoid".to_string(),
         TestFunction {
-            func: ellipsoid,
+            func: TestFn::Pairwise(ellipsoid),
             bounds: ELLIPSOID_BOUNDS,
+            optimum: ELLIPSOID_OPTIMUM,
         },
     );
     m.insert(
         "rosenbrock".to_string(),
         TestFunction {
-            func: rosenbrock,
+            func: TestFn::Pairwise(rosenbrock),
             bounds: ROSENBROCK_BOUNDS,
+            optimum: ROSENBROCK_OPTIMUM,
         },
     );
     m.insert(
         "discus".to_string(),
         TestFunction {
-            func: discus,
+            func: TestFn::Pairwise(discus),
             bounds: DISCUS_BOUNDS,
+            optimum: DISCUS_OPTIMUM,
         },
     );
     m.insert(
         "different_powers".to_string(),
         TestFunction {
-            func: different_powers,
+            func: TestFn::Pairwise(different_powers),
             bounds: DIFFERENT_POWERS_BOUNDS,
+            optimum: DIFFERENT_POWERS_OPTIMUM,
         },
     );
     m
@@ -374,6 +533,8 @@ pub static LMMAES_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::n
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rng::{stream_rng, DefaultRng};
+    use rand_distr::{Distribution, Uniform};
 
     #[test]
     fn test_known_extrema() {
@@ -434,6 +595,51 @@ mod tests {
         assert!((dp_max - 1.0).abs() < 1e-3, "Different Powers max not 1");
     }
 
+    #[test]
+    fn test_declared_optima() {
+        for dims in [16, 32] {
+            let mut failures = Vec::new();
+            for set in [
+                &*WEIERSTRASS_TEST_FUNCTIONS,
+                &*EASY_TEST_FUNCTIONS,
+                &*HARD_TEST_FUNCTIONS,
+                &*HARD_DISCRETE_FUNCTIONS,
+                &*MEDIUM_PERIODIC_FUNCTIONS,
+                &*MINI_TEST_FUNCTIONS,
+                &*TERRAIN_TEST_FUNCTIONS,
+                &*MAIN_TEST_FUNCTIONS,
+                &*LMMAES_TEST_FUNCTIONS,
+                &*COUPLED_TEST_FUNCTIONS,
+            ] {
+                failures.extend(check_optima(set, dims));
+            }
+            failures.extend(check_optima(&LMMAES_ROTATED_TEST_FUNCTIONS, dims));
+            failures.extend(check_optima(&BBOB_TEST_FUNCTIONS, dims));
+            failures.extend(check_optima(&CEC2017_TEST_FUNCTIONS, dims));
+            failures.extend(check_optima(&CEC2022_TEST_FUNCTIONS, dims));
+            assert!(failures.is_empty(), "{dims}D: {failures:?}");
+        }
+    }
+
+    #[test]
+    fn test_declared_optima_are_global() {
+        let mut rng: DefaultRng = stream_rng(0, 0);
+        let sets = [&*EASY_TEST_FUNCTIONS, &*HARD_TEST_FUNCTIONS, &*MEDIUM_PERIODIC_FUNCTIONS];
+        for (name, tf) in sets.into_iter().flatten() {
+            let optimum = tf.optimum.unwrap();
+            let objective = tf.objective(16);
+            for _ in 0..2000 {
+                let x: Vec<f32> = tf
+                    .bounds(16)
+                    .iter()
+                    .map(|b| Uniform::new_inclusive(b[0], b[1]).unwrap().sample(&mut rng))
+                    .collect();
+                let value = objective(&pack_pair_layout(&x));
+                assert!(value >= optimum.f - optimum.target, "{name}: {value} at {x:?}");
+            }
+        }
+    }
+
     #[test]
     fn test_mini_test_functions() {
         let funcs = &*MINI_TEST_FUNCTIONS;
@@ -442,7 +648,7 @@ mod tests {
         assert!(funcs.contains_key("hilly"));
         assert!(funcs.contains_key("forest"));
         for (_, tf) in funcs {
-            let _ = (tf.func)(Vec8::splat(0.0), Vec8::splat(0.0));
+            let _ = tf.func.evaluate(&[Vec8::splat(0.0); 2]);
         }
     }
 
@@ -453,7 +659,7 @@ mod tests {
         assert!(funcs.contains_key("hilly"));
         assert!(funcs.contains_key("forest"));
         for (_, tf) in funcs {
-            let _ = (tf.func)(Vec8::splat(0.0), Vec8::splat(0.0));
+            let _ = tf.func.evaluate(&[Vec8::splat(0.0); 2]);
         }
     }
 
@@ -467,7 +673,7 @@ mod tests {
         assert!(funcs.contains_key("forest"));
         assert!(funcs.contains_key("megacity"));
         for (_, tf) in funcs {
-            let _ = (tf.func)(Vec8::splat(0.0), Vec8::splat(0.0));
+            let _ = tf.func.evaluate(&[Vec8::splat(0.0); 2]);
         }
     }
 
@@ -481,14 +687,14 @@ mod tests {
         assert!(funcs.contains_key("discus"));
         assert!(funcs.contains_key("different_powers"));
         for (_, tf) in funcs {
-            let _ = (tf.func)(Vec8::splat(0.0), Vec8::splat(0.0));
+            let _ = tf.func.evaluate(&[Vec8::splat(0.0); 2]);
         }
     }
 
     #[test]
     fn test_test_function_clone() {
         let tf = MINI_TEST_FUNCTIONS.get("hilly").unwrap().clone();
-        let result = (tf.func)(Vec8::splat(0.0), Vec8::splat(0.0));
-        assert!(result[0].is_finite());
+        let result = tf.func.evaluate(&[Vec8::splat(0.0); 2]);
+        assert!(result.is_finite());
     }
 }
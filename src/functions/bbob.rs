@@ -0,0 +1,709 @@
+use std::{collections::BTreeMap, f32::consts::PI};
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal, Uniform};
+use simd_vector::Vec8;
+use simd_vector::fast::FastMath;
+
+use super::{Objective, Problem};
+use crate::{
+    rng::{stream_rng, DefaultRng},
+    utils::unpack_pair_layout,
+};
+
+/// Names of the BBOB noiseless functions f1..f24, in order.
+pub const BBOB_NAMES: [&str; 24] = [
+    "sphere",
+    "ellipsoid",
+    "rastrigin",
+    "buche_rastrigin",
+    "linear_slope",
+    "attractive_sector",
+    "step_ellipsoid",
+    "rosenbrock",
+    "rosenbrock_rotated",
+    "ellipsoid_rotated",
+    "discus",
+    "bent_cigar",
+    "sharp_ridge",
+    "different_powers",
+    "rastrigin_rotated",
+    "weierstrass",
+    "schaffers_f7",
+    "schaffers_f7_ill",
+    "griewank_rosenbrock",
+    "schwefel",
+    "gallagher_101",
+    "gallagher_21",
+    "katsuura",
+    "lunacek",
+];
+
+/// BBOB noiseless function `id` (1..=24), instance `instance`.
+///
+/// An instance fixes the random parts of the definition: the optimum `x_opt`,
+/// the offset `f_opt` and the rotations `R`, `Q`. They are drawn from a
+/// deterministic stream of `(id, instance, dims)`, so the same instance is
+/// reproduced on every run; they are not the numbers of the COCO C code, but
+/// follow the same distributions. Domain is `[-5, 5]^D`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BbobFunction {
+    pub id: usize,
+    pub instance: u64,
+}
+
+impl BbobFunction {
+    pub fn instantiate(&self, dims: usize) -> BbobInstance {
+        BbobInstance::new(self.id, self.instance, dims)
+    }
+}
+
+impl Problem for BbobFunction {
+    fn objective(&self, dims: usize) -> Objective<'_> {
+        let instance = self.instantiate(dims);
+        Box::new(move |x: &[Vec8]| {
+            let mut v = vec![0.0; x.len() * 8];
+            unpack_pair_layout(x, &mut v);
+            instance.evaluate(&v)
+        })
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        vec![[-5.0, 5.0]; dims]
+    }
+
+    fn f_opt(&self, dims: usize) -> f32 {
+        instance_f_opt(self.id, self.instance, dims)
+    }
+
+    fn x_opt(&self, dims: usize) -> Option<Vec<f32>> {
+        Some(self.instantiate(dims).x_opt().to_vec())
+    }
+}
+
+/// All 24 functions of one instance, keyed `f01_sphere` .. `f24_lunacek`.
+pub fn bbob_suite(instance: u64) -> BTreeMap<String, BbobFunction> {
+    BBOB_NAMES
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            (
+                format!("f{:02}_{}", i + 1, name),
+                BbobFunction { id: i + 1, instance },
+            )
+        })
+        .collect()
+}
+
+pub static BBOB_TEST_FUNCTIONS: Lazy<BTreeMap<String, BbobFunction>> = Lazy::new(|| bbob_suite(1));
+
+/// Dense orthogonal matrix, rows packed in `Vec8` chunks (`dims` multiple of 8).
+#[derive(Clone)]
+pub(super) struct Rotation {
+    dims: usize,
+    rows: Vec<Vec8>,
+}
+
+impl Rotation {
+    /// Gram-Schmidt on a standard normal matrix (as COCO), computed in f64.
+    pub(super) fn random(dims: usize, rng: &mut DefaultRng) -> Self {
+        let mut m: Vec<Vec<f64>> = (0..dims)
+            .map(|_| (0..dims).map(|_| StandardNormal.sample(rng)).collect())
+            .collect();
+        for i in 0..dims {
+            for j in 0..i {
+                let (done, rest) = m.split_at_mut(i);
+                let dot: f64 = rest[0].iter().zip(&done[j]).map(|(a, b)| a * b).sum();
+                for (a, b) in rest[0].iter_mut().zip(&done[j]) {
+                    *a -= dot * b;
+                }
+            }
+            let norm = f64::sqrt(m[i].iter().map(|v| v * v).sum());
+            m[i].iter_mut().for_each(|v| *v /= norm);
+        }
+        let rows = m
+            .iter()
+            .flat_map(|row| row.chunks_exact(8).map(|c| pack8(c.iter().map(|v| *v as f32))))
+            .collect();
+        Self { dims, rows }
+    }
+
+    pub(super) fn apply(&self, x: &[f32], out: &mut [f32]) {
+        let packed: Vec<Vec8> = x.chunks_exact(8).map(|c| pack8(c.iter().copied())).collect();
+        let stride = self.dims / 8;
+        for (i, o) in out.iter_mut().enumerate() {
+            let row = &self.rows[i * stride..(i + 1) * stride];
+            let mut acc = Vec8::ZERO;
+            for (r, v) in row.iter().zip(&packed) {
+                acc = r.mul_add(*v, acc);
+            }
+            *o = acc.sum();
+        }
+    }
+
+    pub(super) fn apply_transposed(&self, x: &[f32], out: &mut [f32]) {
+        let stride = self.dims / 8;
+        let mut acc = vec![Vec8::ZERO; stride];
+        for (i, xi) in x.iter().enumerate() {
+            let row = &self.rows[i * stride..(i + 1) * stride];
+            let s = Vec8::splat(*xi);
+            for (a, r) in acc.iter_mut().zip(row) {
+                *a = r.mul_add(s, *a);
+            }
+        }
+        for (o, v) in out.chunks_exact_mut(8).zip(&acc) {
+            o.copy_from_slice(&v.0);
+        }
+    }
+}
+
+fn pack8<I: Iterator<Item = f32>>(values: I) -> Vec8 {
+    let mut v = Vec8::ZERO;
+    for (lane, x) in v.0.iter_mut().zip(values) {
+        *lane = x;
+    }
+    v
+}
+
+/// Oscillation transform T_osz.
+fn t_osz(x: f32) -> f32 {
+    if x == 0.0 {
+        return 0.0;
+    }
+    let x_hat = f32::ln(x.abs());
+    let (c1, c2) = if x > 0.0 { (10.0, 7.9) } else { (5.5, 3.1) };
+    x.signum() * f32::exp(x_hat + 0.049 * (f32::sin(c1 * x_hat) + f32::sin(c2 * x_hat)))
+}
+
+/// Asymmetry transform T_asy^beta, in place.
+fn t_asy(z: &mut [f32], beta: f32) {
+    let d = z.len();
+    for (i, v) in z.iter_mut().enumerate() {
+        if *v > 0.0 {
+            *v = v.powf(1.0 + beta * ratio(i, d) * v.sqrt());
+        }
+    }
+}
+
+/// `(i - 1) / (D - 1)` of the BBOB definitions, for 0-based `i`.
+fn ratio(i: usize, d: usize) -> f32 {
+    i as f32 / (d - 1) as f32
+}
+
+/// Diagonal of Lambda^alpha.
+fn lambda(alpha: f32, d: usize) -> Vec<f32> {
+    (0..d).map(|i| alpha.powf(0.5 * ratio(i, d))).collect()
+}
+
+/// Boundary penalty f_pen.
+fn f_pen(x: &[f32]) -> f32 {
+    x.iter()
+        .map(|v| {
+            let excess = v.abs() - 5.0;
+            if excess > 0.0 { excess * excess } else { 0.0 }
+        })
+        .sum()
+}
+
+fn rastrigin_sum(z: &[f32]) -> f32 {
+    let cos_sum: f32 = z.iter().map(|v| f32::cos(2.0 * PI * v)).sum();
+    10.0 * (z.len() as f32 - cos_sum) + z.iter().map(|v| v * v).sum::<f32>()
+}
+
+fn instance_rng(id: usize, instance: u64, dims: usize, purpose: u64) -> DefaultRng {
+    let seed = (id as u64) << 48 ^ instance << 16 ^ purpose;
+    stream_rng(seed, dims as u64)
+}
+
+/// COCO's f_opt distribution: 100 * N1 / N2 rounded to 0.01, clipped to +-1000.
+fn instance_f_opt(id: usize, instance: u64, dims: usize) -> f32 {
+    let mut rng = instance_rng(id, instance, dims, 0);
+    let n1: f64 = StandardNormal.sample(&mut rng);
+    let n2: f64 = StandardNormal.sample(&mut rng);
+    let f = f64::round(100.0 * 100.0 * n1 / n2.abs().max(1e-12)) / 100.0;
+    f.clamp(-1000.0, 1000.0) as f32
+}
+
+struct Peak {
+    weight: f32,
+    /// R y_i, so the peak distance needs one rotation of x per evaluation.
+    rotated_center: Vec<f32>,
+    /// Diagonal of C_i.
+    conditioning: Vec<f32>,
+}
+
+/// A BBOB function with all instance data drawn for one dimension.
+pub struct BbobInstance {
+    id: usize,
+    dims: usize,
+    x_opt: Vec<f32>,
+    f_opt: f32,
+    r: Rotation,
+    q: Rotation,
+    signs: Vec<f32>,
+    peaks: Vec<Peak>,
+}
+
+impl BbobInstance {
+    pub fn new(id: usize, instance: u64, dims: usize) -> Self {
+        assert!((1..=24).contains(&id), "BBOB function id must be in 1..=24");
+        assert!(dims >= 8 && dims.is_multiple_of(8), "BBOB dimension must be a multiple of 8");
+        let f_opt = instance_f_opt(id, instance, dims);
+        let mut rng = instance_rng(id, instance, dims, 1);
+        let uniform = Uniform::new_inclusive(-4.0f32, 4.0).unwrap();
+        let mut x_opt: Vec<f32> = (0..dims).map(|_| uniform.sample(&mut rng)).collect();
+        let signs: Vec<f32> = (0..dims)
+            .map(|_| if rng.next_u32() & 1 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let r = Rotation::random(dims, &mut rng);
+        let q = Rotation::random(dims, &mut rng);
+        let mut peaks = Vec::new();
+        let scale = f32::max(1.0, (dims as f32).sqrt() / 8.0);
+        match id {
+            4 => {
+                // Odd coordinates (1-based) of the optimum are positive
+                for v in x_opt.iter_mut().step_by(2) {
+                    *v = v.abs();
+                }
+            }
+            5 => x_opt = signs.iter().map(|s| 5.0 * s).collect(),
+            8 => x_opt.iter_mut().for_each(|v| *v *= 0.75),
+            9 | 19 => {
+                let target = vec![0.5 / scale; dims];
+                r.apply_transposed(&target, &mut x_opt);
+            }
+            20 => x_opt = signs.iter().map(|s| 0.5 * 4.209_687_5 * s).collect(),
+            21 | 22 => {
+                peaks = gallagher_peaks(id, dims, &r, &mut rng);
+                x_opt = gallagher_optimum(&peaks[0], &r);
+            }
+            24 => x_opt = signs.iter().map(|s| 0.5 * LUNACEK_MU0 * s).collect(),
+            _ => {}
+        }
+        Self {
+            id,
+            dims,
+            x_opt,
+            f_opt,
+            r,
+            q,
+            signs,
+            peaks,
+        }
+    }
+
+    pub fn x_opt(&self) -> &[f32] {
+        &self.x_opt
+    }
+
+    pub fn f_opt(&self) -> f32 {
+        self.f_opt
+    }
+
+    /// Value at `x` (coordinates in dimension order).
+    pub fn evaluate(&self, x: &[f32]) -> f32 {
+        let d = self.dims;
+        let mut shifted: Vec<f32> = x.iter().zip(&self.x_opt).map(|(a, b)| a - b).collect();
+        let mut z = vec![0.0; d];
+        let mut tmp = vec![0.0; d];
+        let value = match self.id {
+            1 => shifted.iter().map(|v| v * v).sum(),
+            2 => shifted
+                .iter()
+                .enumerate()
+                .map(|(i, v)| 10f32.powf(6.0 * ratio(i, d)) * t_osz(*v).powi(2))
+                .sum(),
+            3 => {
+                shifted.iter_mut().for_each(|v| *v = t_osz(*v));
+                t_asy(&mut shifted, 0.2);
+                let l = lambda(10.0, d);
+                shifted.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+                rastrigin_sum(&shifted)
+            }
+            4 => {
+                for (i, v) in shifted.iter_mut().enumerate() {
+                    let t = t_osz(*v);
+                    let mut s = 10f32.powf(0.5 * ratio(i, d));
+                    if t > 0.0 && i % 2 == 0 {
+                        s *= 10.0;
+                    }
+                    *v = s * t;
+                }
+                rastrigin_sum(&shifted) + 100.0 * f_pen(x)
+            }
+            5 => x
+                .iter()
+                .zip(&self.x_opt)
+                .enumerate()
+                .map(|(i, (xi, opt))| {
+                    let s = opt.signum() * 10f32.powf(ratio(i, d));
+                    let zi = if opt * xi < 25.0 { *xi } else { *opt };
+                    5.0 * s.abs() - s * zi
+                })
+                .sum(),
+            6 => {
+                self.q_lambda_r(&shifted, 10.0, &mut z, &mut tmp);
+                let sum: f32 = z
+                    .iter()
+                    .zip(&self.x_opt)
+                    .map(|(zi, opt)| {
+                        let s = if zi * opt > 0.0 { 100.0 } else { 1.0 };
+                        (s * zi).powi(2)
+                    })
+                    .sum();
+                t_osz(sum).powf(0.9)
+            }
+            7 => {
+                self.r.apply(&shifted, &mut tmp);
+                let l = lambda(10.0, d);
+                tmp.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+                let first = tmp[0].abs() / 1e4;
+                let rounded: Vec<f32> = tmp
+                    .iter()
+                    .map(|v| {
+                        if v.abs() > 0.5 {
+                            f32::floor(0.5 + v)
+                        } else {
+                            f32::floor(0.5 + 10.0 * v) / 10.0
+                        }
+                    })
+                    .collect();
+                self.q.apply(&rounded, &mut z);
+                let sum: f32 = z
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| 10f32.powf(2.0 * ratio(i, d)) * v * v)
+                    .sum();
+                0.1 * f32::max(first, sum) + f_pen(x)
+            }
+            8 => {
+                let c = self.rosenbrock_scale();
+                shifted.iter_mut().for_each(|v| *v = c * *v + 1.0);
+                rosenbrock_sum(&shifted)
+            }
+            9 => {
+                self.r.apply(x, &mut z);
+                let c = self.rosenbrock_scale();
+                z.iter_mut().for_each(|v| *v = c * *v + 0.5);
+                rosenbrock_sum(&z)
+            }
+            10 => {
+                self.r.apply(&shifted, &mut z);
+                z.iter()
+                    .enumerate()
+                    .map(|(i, v)| 10f32.powf(6.0 * ratio(i, d)) * t_osz(*v).powi(2))
+                    .sum()
+            }
+            11 => {
+                self.r.apply(&shifted, &mut z);
+                let t: Vec<f32> = z.iter().map(|v| t_osz(*v)).collect();
+                1e6 * t[0] * t[0] + t[1..].iter().map(|v| v * v).sum::<f32>()
+            }
+            12 => {
+                self.r.apply(&shifted, &mut tmp);
+                t_asy(&mut tmp, 0.5);
+                self.r.apply(&tmp, &mut z);
+                z[0] * z[0] + 1e6 * z[1..].iter().map(|v| v * v).sum::<f32>()
+            }
+            13 => {
+                self.q_lambda_r(&shifted, 10.0, &mut z, &mut tmp);
+                z[0] * z[0] + 100.0 * f32::sqrt(z[1..].iter().map(|v| v * v).sum::<f32>())
+            }
+            14 => {
+                self.r.apply(&shifted, &mut z);
+                let sum: f32 = z
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| v.abs().powf(2.0 + 4.0 * ratio(i, d)))
+                    .sum();
+                sum.sqrt()
+            }
+            15 => {
+                self.r.apply(&shifted, &mut tmp);
+                tmp.iter_mut().for_each(|v| *v = t_osz(*v));
+                t_asy(&mut tmp, 0.2);
+                self.q.apply(&tmp, &mut z);
+                let l = lambda(10.0, d);
+                z.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+                self.r.apply(&z, &mut tmp);
+                rastrigin_sum(&tmp)
+            }
+            16 => {
+                self.r.apply(&shifted, &mut tmp);
+                tmp.iter_mut().for_each(|v| *v = t_osz(*v));
+                self.q.apply(&tmp, &mut z);
+                let l = lambda(0.01, d);
+                z.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+                self.r.apply(&z, &mut tmp);
+                let f0: f32 = (0..12).map(|k| 0.5f32.powi(k) * f32::cos(PI * 3f32.powi(k))).sum();
+                let sum: f32 = tmp
+                    .iter()
+                    .map(|v| {
+                        (0..12)
+                            .map(|k| 0.5f32.powi(k) * f32::cos(2.0 * PI * 3f32.powi(k) * (v + 0.5)))
+                            .sum::<f32>()
+                    })
+                    .sum();
+                10.0 * (sum / d as f32 - f0).powi(3) + 10.0 / d as f32 * f_pen(x)
+            }
+            17 | 18 => {
+                let alpha = if self.id == 17 { 10.0 } else { 1000.0 };
+                self.r.apply(&shifted, &mut tmp);
+                t_asy(&mut tmp, 0.5);
+                self.q.apply(&tmp, &mut z);
+                let l = lambda(alpha, d);
+                z.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+                let sum: f32 = z
+                    .windows(2)
+                    .map(|w| {
+                        let s = f32::sqrt(w[0] * w[0] + w[1] * w[1]);
+                        s.sqrt() + s.sqrt() * f32::sin(50.0 * s.powf(0.2)).powi(2)
+                    })
+                    .sum();
+                (sum / (d - 1) as f32).powi(2) + 10.0 * f_pen(x)
+            }
+            19 => {
+                self.r.apply(x, &mut z);
+                let c = self.rosenbrock_scale();
+                z.iter_mut().for_each(|v| *v = c * *v + 0.5);
+                let sum: f32 = z
+                    .windows(2)
+                    .map(|w| {
+                        let s = 100.0 * (w[0] * w[0] - w[1]).powi(2) + (w[0] - 1.0).powi(2);
+                        s / 4000.0 - f32::cos(s)
+                    })
+                    .sum();
+                10.0 * sum / (d - 1) as f32 + 10.0
+            }
+            20 => self.schwefel(x),
+            21 | 22 => {
+                self.r.apply(x, &mut z);
+                let best = self
+                    .peaks
+                    .iter()
+                    .map(|p| {
+                        let dist: f32 = z
+                            .iter()
+                            .zip(&p.rotated_center)
+                            .zip(&p.conditioning)
+                            .map(|((zi, ci), c)| c * (zi - ci) * (zi - ci))
+                            .sum();
+                        p.weight * f32::exp(-dist / (2.0 * d as f32))
+                    })
+                    .fold(f32::NEG_INFINITY, f32::max);
+                t_osz(10.0 - best).powi(2) + f_pen(x)
+            }
+            23 => {
+                self.q_lambda_r(&shifted, 100.0, &mut z, &mut tmp);
+                let df = d as f64;
+                let product: f64 = z
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let v = *v as f64;
+                        let sum: f64 = (1..=32)
+                            .map(|j| {
+                                let p = f64::powi(2.0, j);
+                                (p * v - f64::round(p * v)).abs() / p
+                            })
+                            .sum();
+                        f64::powf(1.0 + (i + 1) as f64 * sum, 10.0 / df.powf(1.2))
+                    })
+                    .product();
+                (10.0 / (df * df) * (product - 1.0)) as f32 + f_pen(x)
+            }
+            24 => self.lunacek(x, &mut z, &mut tmp),
+            _ => unreachable!(),
+        };
+        value + self.f_opt
+    }
+
+    fn rosenbrock_scale(&self) -> f32 {
+        f32::max(1.0, (self.dims as f32).sqrt() / 8.0)
+    }
+
+    /// z = Q Lambda^alpha R x.
+    fn q_lambda_r(&self, x: &[f32], alpha: f32, z: &mut [f32], tmp: &mut [f32]) {
+        self.r.apply(x, tmp);
+        let l = lambda(alpha, self.dims);
+        tmp.iter_mut().zip(&l).for_each(|(v, l)| *v *= l);
+        self.q.apply(tmp, z);
+    }
+
+    fn schwefel(&self, x: &[f32]) -> f32 {
+        let d = self.dims;
+        let x_hat: Vec<f32> = x.iter().zip(&self.signs).map(|(v, s)| 2.0 * s * v).collect();
+        let two_opt: Vec<f32> = self.x_opt.iter().map(|v| 2.0 * v.abs()).collect();
+        let mut z_hat = x_hat.clone();
+        for i in 1..d {
+            z_hat[i] += 0.25 * (x_hat[i - 1] - two_opt[i - 1]);
+        }
+        let l = lambda(10.0, d);
+        let z: Vec<f32> = (0..d)
+            .map(|i| 100.0 * (l[i] * (z_hat[i] - two_opt[i]) + two_opt[i]))
+            .collect();
+        let sum: f32 = z.iter().map(|v| v * f32::sin(v.abs().sqrt())).sum();
+        let scaled: Vec<f32> = z.iter().map(|v| v / 100.0).collect();
+        -sum / (100.0 * d as f32) + 4.189_829 + 100.0 * f_pen(&scaled)
+    }
+
+    fn lunacek(&self, x: &[f32], z: &mut [f32], tmp: &mut [f32]) -> f32 {
+        let d = self.dims as f32;
+        let s = 1.0 - 1.0 / (2.0 * f32::sqrt(d + 20.0) - 8.2);
+        let mu1 = -f32::sqrt((LUNACEK_MU0 * LUNACEK_MU0 - 1.0) / s);
+        let x_hat: Vec<f32> = x.iter().zip(&self.signs).map(|(v, s)| 2.0 * s * v).collect();
+        let near: f32 = x_hat.iter().map(|v| (v - LUNACEK_MU0).powi(2)).sum();
+        let far: f32 = x_hat.iter().map(|v| (v - mu1).powi(2)).sum();
+        let centered: Vec<f32> = x_hat.iter().map(|v| v - LUNACEK_MU0).collect();
+        self.q_lambda_r(&centered, 100.0, z, tmp);
+        let cos_sum: f32 = z.iter().map(|v| f32::cos(2.0 * PI * v)).sum();
+        f32::min(near, d + s * far) + 10.0 * (d - cos_sum) + 1e4 * f_pen(x)
+    }
+}
+
+const LUNACEK_MU0: f32 = 2.5;
+
+fn rosenbrock_sum(z: &[f32]) -> f32 {
+    z.windows(2)
+        .map(|w| 100.0 * (w[0] * w[0] - w[1]).powi(2) + (w[0] - 1.0).powi(2))
+        .sum()
+}
+
+/// Peaks of f21 (101 peaks) / f22 (21 peaks); peak 0 is the global optimum.
+fn gallagher_peaks(id: usize, dims: usize, r: &Rotation, rng: &mut DefaultRng) -> Vec<Peak> {
+    let (count, global_alpha, global_range, local_range) = if id == 21 {
+        (101, 1000.0f32, 4.0f32, 4.9f32)
+    } else {
+        (21, 1000.0f32 * 1000.0, 3.92f32, 4.9f32)
+    };
+    // Condition numbers 1000^(2j / (count - 2)) of the local peaks, shuffled
+    let mut alphas: Vec<f32> = (0..count - 1)
+        .map(|j| 1000f32.powf(2.0 * j as f32 / (count - 2) as f32))
+        .collect();
+    shuffle(&mut alphas, rng);
+    let mut peaks = Vec::with_capacity(count);
+    for i in 0..count {
+        let (weight, alpha, range) = if i == 0 {
+            (10.0, global_alpha, global_range)
+        } else {
+            (1.1 + 8.0 * (i - 1) as f32 / (count - 2) as f32, alphas[i - 1], local_range)
+        };
+        let uniform = Uniform::new_inclusive(-range, range).unwrap();
+        let center: Vec<f32> = (0..dims).map(|_| uniform.sample(rng)).collect();
+        let mut rotated_center = vec![0.0; dims];
+        r.apply(&center, &mut rotated_center);
+        let mut conditioning: Vec<f32> = lambda(alpha, dims)
+            .iter()
+            .map(|l| l / alpha.powf(0.25))
+            .collect();
+        shuffle(&mut conditioning, rng);
+        peaks.push(Peak {
+            weight,
+            rotated_center,
+            conditioning,
+        });
+    }
+    peaks
+}
+
+/// x_opt = R^T (R y_0).
+fn gallagher_optimum(peak: &Peak, r: &Rotation) -> Vec<f32> {
+    let mut x = vec![0.0; r.dims];
+    r.apply_transposed(&peak.rotated_center, &mut x);
+    x
+}
+
+pub(super) fn shuffle<T>(values: &mut [T], rng: &mut DefaultRng) {
+    for i in (1..values.len()).rev() {
+        let j = Uniform::new_inclusive(0, i).unwrap().sample(rng);
+        values.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fit_in_bounds_simd;
+
+    #[test]
+    fn test_optimum_values() {
+        for dims in [8, 16, 32] {
+            for id in 1..=24 {
+                let f = BbobInstance::new(id, 1, dims);
+                let at_opt = f.evaluate(f.x_opt());
+                let tol = 1e-3 * f32::max(1.0, f.f_opt().abs());
+                assert!(
+                    (at_opt - f.f_opt()).abs() <= tol,
+                    "f{} {}D: f(x_opt)={} f_opt={}",
+                    id,
+                    dims,
+                    at_opt,
+                    f.f_opt()
+                );
+                assert!(f.x_opt().iter().all(|v| v.abs() <= 5.0), "f{} x_opt out of bounds", id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimum_is_not_beaten_by_random_points() {
+        let mut rng: DefaultRng = stream_rng(99, 0);
+        let uniform = Uniform::new_inclusive(-5.0f32, 5.0).unwrap();
+        for id in 1..=24 {
+            let f = BbobInstance::new(id, 2, 16);
+            for _ in 0..200 {
+                let x: Vec<f32> = (0..16).map(|_| uniform.sample(&mut rng)).collect();
+                assert!(f.evaluate(&x) >= f.f_opt() - 1e-3, "f{} below f_opt", id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_instances_are_deterministic_and_distinct() {
+        let a = BbobInstance::new(10, 1, 16);
+        let b = BbobInstance::new(10, 1, 16);
+        let c = BbobInstance::new(10, 2, 16);
+        assert_eq!(a.x_opt(), b.x_opt());
+        assert_eq!(a.f_opt(), b.f_opt());
+        assert_ne!(a.x_opt(), c.x_opt());
+    }
+
+    #[test]
+    fn test_rotation_is_orthogonal() {
+        let mut rng: DefaultRng = stream_rng(1, 0);
+        let r = Rotation::random(16, &mut rng);
+        let x: Vec<f32> = (0..16).map(|i| i as f32 - 7.5).collect();
+        let mut y = vec![0.0; 16];
+        let mut back = vec![0.0; 16];
+        r.apply(&x, &mut y);
+        r.apply_transposed(&y, &mut back);
+        let norm_x: f32 = x.iter().map(|v| v * v).sum();
+        let norm_y: f32 = y.iter().map(|v| v * v).sum();
+        assert!((norm_x - norm_y).abs() < 1e-3 * norm_x);
+        for (a, b) in x.iter().zip(&back) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_problem_objective_uses_dimension_order() {
+        let function = BBOB_TEST_FUNCTIONS["f14_different_powers"];
+        let dims = 32;
+        let instance = function.instantiate(dims);
+        let x_opt = instance.x_opt().to_vec();
+        let zeros = vec![0.0; dims];
+        let ones = vec![1.0; dims];
+        let objective = function.objective(dims);
+        let at_opt = objective(&fit_in_bounds_simd(&x_opt, &zeros, &ones));
+        assert!((at_opt - function.f_opt(dims)).abs() < 1e-3);
+        assert_eq!(function.bounds(dims).len(), dims);
+    }
+
+    #[test]
+    fn test_suite_names() {
+        assert_eq!(BBOB_TEST_FUNCTIONS.len(), 24);
+        assert_eq!(BBOB_TEST_FUNCTIONS["f01_sphere"].id, 1);
+        assert_eq!(BBOB_TEST_FUNCTIONS["f24_lunacek"].id, 24);
+    }
+}
@@ -0,0 +1,721 @@
+use std::{collections::BTreeMap, f64::consts::{E, PI}};
+
+use once_cell::sync::Lazy;
+use rand_distr::{Distribution, Uniform};
+use simd_vector::Vec8;
+
+use super::{
+    bbob::{shuffle, Rotation},
+    Objective, Problem,
+};
+use crate::{
+    rng::{stream_rng, DefaultRng},
+    utils::unpack_pair_layout,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CecSuite {
+    Cec2017,
+    Cec2022,
+}
+
+/// CEC competition function `id` of `suite`.
+///
+/// Shift vectors (uniform in `[-80, 80]^D`), rotations and hybrid permutations
+/// are generated in-crate from a deterministic stream of `(suite, id, dims)`
+/// instead of being read from the competition data files, so any dimension
+/// that is a multiple of 8 is available. Domain is `[-100, 100]^D` and the
+/// optimum value is the suite's `F*` (`100 * id` for CEC2017).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CecFunction {
+    pub suite: CecSuite,
+    pub id: usize,
+}
+
+impl CecFunction {
+    pub fn instantiate(&self, dims: usize) -> CecInstance {
+        CecInstance::new(self.suite, self.id, dims)
+    }
+}
+
+impl Problem for CecFunction {
+    fn objective(&self, dims: usize) -> Objective<'_> {
+        let instance = self.instantiate(dims);
+        Box::new(move |x: &[Vec8]| {
+            let mut v = vec![0.0; x.len() * 8];
+            unpack_pair_layout(x, &mut v);
+            instance.evaluate(&v)
+        })
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        vec![[-100.0, 100.0]; dims]
+    }
+
+    fn f_opt(&self, _dims: usize) -> f32 {
+        f_star(self.suite, self.id)
+    }
+
+    fn x_opt(&self, dims: usize) -> Option<Vec<f32>> {
+        Some(self.instantiate(dims).x_opt().to_vec())
+    }
+}
+
+pub const CEC2017_NAMES: [&str; 30] = [
+    "bent_cigar",
+    "sum_diff_pow",
+    "zakharov",
+    "rosenbrock",
+    "rastrigin",
+    "schaffer_f6",
+    "lunacek",
+    "noncont_rastrigin",
+    "levy",
+    "schwefel",
+    "hybrid_1",
+    "hybrid_2",
+    "hybrid_3",
+    "hybrid_4",
+    "hybrid_5",
+    "hybrid_6",
+    "hybrid_7",
+    "hybrid_8",
+    "hybrid_9",
+    "hybrid_10",
+    "composition_1",
+    "composition_2",
+    "composition_3",
+    "composition_4",
+    "composition_5",
+    "composition_6",
+    "composition_7",
+    "composition_8",
+    "composition_9",
+    "composition_10",
+];
+
+pub const CEC2022_NAMES: [&str; 12] = [
+    "zakharov",
+    "rosenbrock",
+    "schaffer_f6",
+    "noncont_rastrigin",
+    "levy",
+    "hybrid_1",
+    "hybrid_2",
+    "hybrid_3",
+    "composition_1",
+    "composition_2",
+    "composition_3",
+    "composition_4",
+];
+
+fn suite_functions(suite: CecSuite, names: &[&str]) -> BTreeMap<String, CecFunction> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (format!("f{:02}_{}", i + 1, name), CecFunction { suite, id: i + 1 }))
+        .collect()
+}
+
+pub static CEC2017_TEST_FUNCTIONS: Lazy<BTreeMap<String, CecFunction>> =
+    Lazy::new(|| suite_functions(CecSuite::Cec2017, &CEC2017_NAMES));
+
+pub static CEC2022_TEST_FUNCTIONS: Lazy<BTreeMap<String, CecFunction>> =
+    Lazy::new(|| suite_functions(CecSuite::Cec2022, &CEC2022_NAMES));
+
+const CEC2022_F_STAR: [f32; 12] = [
+    300.0, 400.0, 600.0, 800.0, 900.0, 1800.0, 2000.0, 2200.0, 2300.0, 2400.0, 2600.0, 2700.0,
+];
+
+fn f_star(suite: CecSuite, id: usize) -> f32 {
+    match suite {
+        CecSuite::Cec2017 => 100.0 * id as f32,
+        CecSuite::Cec2022 => CEC2022_F_STAR[id - 1],
+    }
+}
+
+/// Basic functions shared by the hybrid and composition functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Basic {
+    BentCigar,
+    SumDiffPow,
+    Zakharov,
+    Rosenbrock,
+    Rastrigin,
+    NonContRastrigin,
+    SchafferF6,
+    SchafferF7,
+    Lunacek,
+    Levy,
+    Schwefel,
+    Elliptic,
+    Discus,
+    Ackley,
+    Weierstrass,
+    Griewank,
+    Katsuura,
+    HappyCat,
+    HgBat,
+    GriewankRosenbrock,
+}
+
+use Basic::*;
+
+/// Basic functions with the share of the dimensions each one receives.
+type Hybrid = &'static [(Basic, f64)];
+
+#[derive(Clone, Copy)]
+enum Component {
+    Basic(Basic),
+    Hybrid(Hybrid),
+}
+
+/// Component, sigma, lambda, bias.
+type Composition = &'static [(Component, f64, f64, f64)];
+
+enum Definition {
+    Single(Component),
+    Composition(Composition),
+}
+
+const CEC2017_HYBRIDS: [Hybrid; 10] = [
+    &[(Zakharov, 0.2), (Rosenbrock, 0.4), (Rastrigin, 0.4)],
+    &[(Elliptic, 0.3), (Schwefel, 0.3), (BentCigar, 0.4)],
+    &[(BentCigar, 0.3), (Rosenbrock, 0.3), (Lunacek, 0.4)],
+    &[(Elliptic, 0.2), (Ackley, 0.2), (SchafferF7, 0.2), (Rastrigin, 0.4)],
+    &[(BentCigar, 0.2), (HgBat, 0.2), (Rastrigin, 0.3), (Rosenbrock, 0.3)],
+    &[(SchafferF6, 0.2), (HgBat, 0.2), (Rosenbrock, 0.3), (Schwefel, 0.3)],
+    &[
+        (Katsuura, 0.1),
+        (Ackley, 0.2),
+        (GriewankRosenbrock, 0.2),
+        (Schwefel, 0.2),
+        (Rastrigin, 0.3),
+    ],
+    &[(Elliptic, 0.2), (Ackley, 0.2), (Rastrigin, 0.2), (HgBat, 0.2), (Discus, 0.2)],
+    &[
+        (BentCigar, 0.2),
+        (Rastrigin, 0.2),
+        (GriewankRosenbrock, 0.2),
+        (Weierstrass, 0.2),
+        (SchafferF6, 0.2),
+    ],
+    &[
+        (HappyCat, 0.1),
+        (Katsuura, 0.1),
+        (Ackley, 0.2),
+        (Rastrigin, 0.2),
+        (Schwefel, 0.2),
+        (SchafferF7, 0.2),
+    ],
+];
+
+const fn b(basic: Basic, sigma: f64, lambda: f64, bias: f64) -> (Component, f64, f64, f64) {
+    (Component::Basic(basic), sigma, lambda, bias)
+}
+
+const fn h(hybrid: usize, sigma: f64, bias: f64) -> (Component, f64, f64, f64) {
+    (Component::Hybrid(CEC2017_HYBRIDS[hybrid - 11]), sigma, 1.0, bias)
+}
+
+const CEC2017_COMPOSITIONS: [Composition; 10] = [
+    &[b(Rosenbrock, 10.0, 1.0, 0.0), b(Elliptic, 20.0, 1e-6, 100.0), b(Rastrigin, 30.0, 1.0, 200.0)],
+    &[b(Rastrigin, 10.0, 1.0, 0.0), b(Griewank, 20.0, 10.0, 100.0), b(Schwefel, 30.0, 1.0, 200.0)],
+    &[
+        b(Rosenbrock, 10.0, 1.0, 0.0),
+        b(Ackley, 20.0, 10.0, 100.0),
+        b(Schwefel, 30.0, 1.0, 200.0),
+        b(Rastrigin, 40.0, 1.0, 300.0),
+    ],
+    &[
+        b(Ackley, 10.0, 1.0, 0.0),
+        b(Elliptic, 20.0, 1e-6, 100.0),
+        b(Griewank, 30.0, 10.0, 200.0),
+        b(Rastrigin, 40.0, 1.0, 300.0),
+    ],
+    &[
+        b(Rastrigin, 10.0, 10.0, 0.0),
+        b(HappyCat, 20.0, 1.0, 100.0),
+        b(Ackley, 30.0, 10.0, 200.0),
+        b(Discus, 40.0, 1e-6, 300.0),
+        b(Rosenbrock, 50.0, 1.0, 400.0),
+    ],
+    &[
+        b(SchafferF6, 10.0, 1e-26, 0.0),
+        b(Schwefel, 20.0, 10.0, 100.0),
+        b(Griewank, 20.0, 1e-6, 200.0),
+        b(Rosenbrock, 30.0, 10.0, 300.0),
+        b(Rastrigin, 40.0, 5e-4, 400.0),
+    ],
+    &[
+        b(HgBat, 10.0, 10.0, 0.0),
+        b(Rastrigin, 20.0, 10.0, 100.0),
+        b(Schwefel, 30.0, 2.5, 200.0),
+        b(BentCigar, 40.0, 1e-26, 300.0),
+        b(Elliptic, 50.0, 1e-6, 400.0),
+        b(SchafferF6, 60.0, 5e-4, 500.0),
+    ],
+    &[
+        b(Ackley, 10.0, 10.0, 0.0),
+        b(Griewank, 20.0, 10.0, 100.0),
+        b(Discus, 30.0, 1e-6, 200.0),
+        b(Rosenbrock, 40.0, 1.0, 300.0),
+        b(HappyCat, 50.0, 1.0, 400.0),
+        b(SchafferF6, 60.0, 5e-4, 500.0),
+    ],
+    &[h(15, 10.0, 0.0), h(16, 30.0, 100.0), h(17, 50.0, 200.0)],
+    &[h(15, 10.0, 0.0), h(18, 30.0, 100.0), h(19, 50.0, 200.0)],
+];
+
+const CEC2022_HYBRIDS: [Hybrid; 3] = [
+    &[(BentCigar, 0.4), (HgBat, 0.4), (Rastrigin, 0.2)],
+    &[
+        (HgBat, 0.1),
+        (Katsuura, 0.2),
+        (Ackley, 0.2),
+        (Rastrigin, 0.2),
+        (Schwefel, 0.1),
+        (SchafferF7, 0.2),
+    ],
+    &[
+        (Katsuura, 0.3),
+        (HappyCat, 0.2),
+        (GriewankRosenbrock, 0.2),
+        (Schwefel, 0.1),
+        (Ackley, 0.2),
+    ],
+];
+
+const CEC2022_COMPOSITIONS: [Composition; 4] = [
+    &[
+        b(Rosenbrock, 10.0, 1.0, 0.0),
+        b(Elliptic, 20.0, 1e-6, 200.0),
+        b(BentCigar, 30.0, 1e-26, 300.0),
+        b(Discus, 40.0, 1e-6, 100.0),
+        b(Elliptic, 50.0, 1e-6, 400.0),
+    ],
+    &[b(Schwefel, 20.0, 1.0, 0.0), b(Rastrigin, 10.0, 1.0, 200.0), b(HgBat, 10.0, 1.0, 100.0)],
+    &[
+        b(SchafferF6, 20.0, 5e-4, 0.0),
+        b(Schwefel, 20.0, 1.0, 200.0),
+        b(Griewank, 30.0, 10.0, 300.0),
+        b(Rosenbrock, 30.0, 1.0, 400.0),
+        b(Rastrigin, 20.0, 10.0, 200.0),
+    ],
+    &[
+        b(HgBat, 10.0, 10.0, 0.0),
+        b(Rastrigin, 20.0, 10.0, 300.0),
+        b(Schwefel, 30.0, 2.5, 500.0),
+        b(BentCigar, 40.0, 1e-26, 100.0),
+        b(Elliptic, 50.0, 1e-6, 400.0),
+        b(SchafferF6, 60.0, 5e-4, 200.0),
+    ],
+];
+
+fn definition(suite: CecSuite, id: usize) -> Definition {
+    let single = |basic| Definition::Single(Component::Basic(basic));
+    match suite {
+        CecSuite::Cec2017 => match id {
+            1 => single(BentCigar),
+            2 => single(SumDiffPow),
+            3 => single(Zakharov),
+            4 => single(Rosenbrock),
+            5 => single(Rastrigin),
+            6 => single(SchafferF6),
+            7 => single(Lunacek),
+            8 => single(NonContRastrigin),
+            9 => single(Levy),
+            10 => single(Schwefel),
+            11..=20 => Definition::Single(Component::Hybrid(CEC2017_HYBRIDS[id - 11])),
+            21..=30 => Definition::Composition(CEC2017_COMPOSITIONS[id - 21]),
+            _ => panic!("CEC2017 function id must be in 1..=30"),
+        },
+        CecSuite::Cec2022 => match id {
+            1 => single(Zakharov),
+            2 => single(Rosenbrock),
+            3 => single(SchafferF6),
+            4 => single(NonContRastrigin),
+            5 => single(Levy),
+            6..=8 => Definition::Single(Component::Hybrid(CEC2022_HYBRIDS[id - 6])),
+            9..=12 => Definition::Composition(CEC2022_COMPOSITIONS[id - 9]),
+            _ => panic!("CEC2022 function id must be in 1..=12"),
+        },
+    }
+}
+
+impl Basic {
+    /// Search-range scaling applied to `x - o` before the function, as in the
+    /// competition code (e.g. 5.12 / 100 maps the domain onto Rastrigin's).
+    fn scale(self) -> f64 {
+        match self {
+            Rosenbrock => 2.048 / 100.0,
+            Rastrigin | NonContRastrigin => 5.12 / 100.0,
+            Schwefel => 1000.0 / 100.0,
+            Griewank => 600.0 / 100.0,
+            Weierstrass => 0.5 / 100.0,
+            Lunacek => 10.0 / 100.0,
+            Katsuura | HappyCat | HgBat | GriewankRosenbrock => 5.0 / 100.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Value at the scaled, shifted and rotated point `z`; 0 at `z = 0`.
+    fn evaluate(self, z: &[f64]) -> f64 {
+        let n = z.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let nf = n as f64;
+        let sq_sum = || z.iter().map(|v| v * v).sum::<f64>();
+        match self {
+            BentCigar => z[0] * z[0] + 1e6 * z[1..].iter().map(|v| v * v).sum::<f64>(),
+            SumDiffPow => z.iter().enumerate().map(|(i, v)| v.abs().powi(i as i32 + 2)).sum(),
+            Zakharov => {
+                let s: f64 = z.iter().enumerate().map(|(i, v)| 0.5 * (i + 1) as f64 * v).sum();
+                sq_sum() + s * s + s.powi(4)
+            }
+            Rosenbrock => z
+                .windows(2)
+                .map(|w| {
+                    let (a, b) = (w[0] + 1.0, w[1] + 1.0);
+                    100.0 * (a * a - b).powi(2) + (a - 1.0).powi(2)
+                })
+                .sum(),
+            Rastrigin | NonContRastrigin => z
+                .iter()
+                .map(|v| v * v - 10.0 * f64::cos(2.0 * PI * v) + 10.0)
+                .sum(),
+            SchafferF6 => (0..n)
+                .map(|i| {
+                    let s = z[i] * z[i] + z[(i + 1) % n] * z[(i + 1) % n];
+                    0.5 + (s.sqrt().sin().powi(2) - 0.5) / (1.0 + 0.001 * s).powi(2)
+                })
+                .sum(),
+            SchafferF7 => {
+                let sum: f64 = z
+                    .windows(2)
+                    .map(|w| {
+                        let s = f64::sqrt(w[0] * w[0] + w[1] * w[1]);
+                        s.sqrt() + s.sqrt() * f64::sin(50.0 * s.powf(0.2)).powi(2)
+                    })
+                    .sum();
+                (sum / (nf - 1.0).max(1.0)).powi(2)
+            }
+            Lunacek => {
+                let (mu0, d) = (2.5, 1.0);
+                let s = 1.0 - 1.0 / (2.0 * f64::sqrt(nf + 20.0) - 8.2);
+                let mu1 = -f64::sqrt((mu0 * mu0 - d) / s);
+                let near: f64 = z.iter().map(|v| (2.0 * v).powi(2)).sum();
+                let far: f64 = z.iter().map(|v| (2.0 * v + mu0 - mu1).powi(2)).sum();
+                let cos_sum: f64 = z.iter().map(|v| f64::cos(2.0 * PI * 2.0 * v)).sum();
+                f64::min(near, d * nf + s * far) + 10.0 * (nf - cos_sum)
+            }
+            Levy => {
+                let w: Vec<f64> = z.iter().map(|v| 1.0 + v / 4.0).collect();
+                let middle: f64 = w[..n - 1]
+                    .iter()
+                    .map(|wi| (wi - 1.0).powi(2) * (1.0 + 10.0 * f64::sin(PI * wi + 1.0).powi(2)))
+                    .sum();
+                let last = w[n - 1];
+                f64::sin(PI * w[0]).powi(2)
+                    + middle
+                    + (last - 1.0).powi(2) * (1.0 + f64::sin(2.0 * PI * last).powi(2))
+            }
+            Schwefel => {
+                let sum: f64 = z
+                    .iter()
+                    .map(|v| {
+                        let zi = v + 420.968_746_227_503_6;
+                        if zi > 500.0 {
+                            let m = 500.0 - zi % 500.0;
+                            m * m.abs().sqrt().sin() - (zi - 500.0).powi(2) / (10_000.0 * nf)
+                        } else if zi < -500.0 {
+                            let m = zi.abs() % 500.0 - 500.0;
+                            m * m.abs().sqrt().sin() - (zi + 500.0).powi(2) / (10_000.0 * nf)
+                        } else {
+                            zi * zi.abs().sqrt().sin()
+                        }
+                    })
+                    .sum();
+                418.982_887_272_433_8 * nf - sum
+            }
+            Elliptic => z
+                .iter()
+                .enumerate()
+                .map(|(i, v)| 10f64.powf(6.0 * i as f64 / (nf - 1.0).max(1.0)) * v * v)
+                .sum(),
+            Discus => 1e6 * z[0] * z[0] + z[1..].iter().map(|v| v * v).sum::<f64>(),
+            Ackley => {
+                let cos_sum: f64 = z.iter().map(|v| f64::cos(2.0 * PI * v)).sum();
+                -20.0 * f64::exp(-0.2 * (sq_sum() / nf).sqrt()) - f64::exp(cos_sum / nf) + 20.0 + E
+            }
+            Weierstrass => {
+                let term = |v: f64| -> f64 {
+                    (0..=20)
+                        .map(|k| 0.5f64.powi(k) * f64::cos(2.0 * PI * 3f64.powi(k) * (v + 0.5)))
+                        .sum()
+                };
+                z.iter().map(|v| term(*v)).sum::<f64>() - nf * term(0.0)
+            }
+            Griewank => {
+                let product: f64 = z
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| f64::cos(v / ((i + 1) as f64).sqrt()))
+                    .product();
+                sq_sum() / 4000.0 - product + 1.0
+            }
+            Katsuura => {
+                let product: f64 = z
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let sum: f64 = (1..=32)
+                            .map(|j| {
+                                let p = f64::powi(2.0, j);
+                                (p * v - f64::round(p * v)).abs() / p
+                            })
+                            .sum();
+                        f64::powf(1.0 + (i + 1) as f64 * sum, 10.0 / nf.powf(1.2))
+                    })
+                    .product();
+                10.0 / (nf * nf) * (product - 1.0)
+            }
+            HappyCat | HgBat => {
+                let r2: f64 = z.iter().map(|v| (v - 1.0).powi(2)).sum();
+                let sum: f64 = z.iter().map(|v| v - 1.0).sum();
+                let head = if self == HappyCat {
+                    (r2 - nf).abs().powf(0.25)
+                } else {
+                    (r2 * r2 - sum * sum).abs().sqrt()
+                };
+                head + (0.5 * r2 + sum) / nf + 0.5
+            }
+            GriewankRosenbrock => (0..n)
+                .map(|i| {
+                    let (a, b) = (z[i] + 1.0, z[(i + 1) % n] + 1.0);
+                    let t = 100.0 * (a * a - b).powi(2) + (a - 1.0).powi(2);
+                    t * t / 4000.0 - t.cos() + 1.0
+                })
+                .sum(),
+        }
+    }
+}
+
+/// Part sizes of a hybrid function: `ceil(p_i * D)` for all but the last part,
+/// which takes the remaining dimensions.
+fn hybrid_sizes(hybrid: Hybrid, dims: usize) -> Vec<usize> {
+    let mut remaining = dims;
+    let mut sizes: Vec<usize> = hybrid[..hybrid.len() - 1]
+        .iter()
+        .map(|(_, share)| {
+            let size = usize::min((share * dims as f64).ceil() as usize, remaining);
+            remaining -= size;
+            size
+        })
+        .collect();
+    sizes.push(remaining);
+    sizes
+}
+
+/// Shift, rotation and permutation of one component.
+struct Transform {
+    shift: Vec<f32>,
+    rotation: Rotation,
+    permutation: Vec<usize>,
+}
+
+impl Transform {
+    fn random(dims: usize, rng: &mut DefaultRng) -> Self {
+        let uniform = Uniform::new_inclusive(-80.0f32, 80.0).unwrap();
+        let shift = (0..dims).map(|_| uniform.sample(rng)).collect();
+        let rotation = Rotation::random(dims, rng);
+        let mut permutation: Vec<usize> = (0..dims).collect();
+        shuffle(&mut permutation, rng);
+        Self {
+            shift,
+            rotation,
+            permutation,
+        }
+    }
+
+    fn evaluate(&self, component: Component, x: &[f32]) -> f64 {
+        let mut shifted: Vec<f32> = x.iter().zip(&self.shift).map(|(a, b)| a - b).collect();
+        if let Component::Basic(NonContRastrigin) = component {
+            for v in shifted.iter_mut().filter(|v| v.abs() > 0.5) {
+                *v = f32::floor(2.0 * *v + 0.5) / 2.0;
+            }
+        }
+        let mut rotated = vec![0.0; x.len()];
+        self.rotation.apply(&shifted, &mut rotated);
+        match component {
+            Component::Basic(basic) => {
+                let z: Vec<f64> = rotated.iter().map(|v| *v as f64 * basic.scale()).collect();
+                basic.evaluate(&z)
+            }
+            Component::Hybrid(hybrid) => {
+                let mut start = 0;
+                hybrid
+                    .iter()
+                    .zip(hybrid_sizes(hybrid, x.len()))
+                    .map(|((basic, _), size)| {
+                        let z: Vec<f64> = self.permutation[start..start + size]
+                            .iter()
+                            .map(|i| rotated[*i] as f64 * basic.scale())
+                            .collect();
+                        start += size;
+                        basic.evaluate(&z)
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+/// A CEC function with its shift, rotation and permutation data for one dimension.
+pub struct CecInstance {
+    definition: Definition,
+    f_opt: f32,
+    transforms: Vec<Transform>,
+}
+
+impl CecInstance {
+    pub fn new(suite: CecSuite, id: usize, dims: usize) -> Self {
+        assert!(dims >= 8 && dims.is_multiple_of(8), "CEC dimension must be a multiple of 8");
+        let definition = definition(suite, id);
+        let count = match definition {
+            Definition::Single(_) => 1,
+            Definition::Composition(layers) => layers.len(),
+        };
+        let seed = (suite as u64 + 1) << 40 ^ (id as u64) << 24;
+        let mut rng: DefaultRng = stream_rng(seed, dims as u64);
+        let transforms = (0..count).map(|_| Transform::random(dims, &mut rng)).collect();
+        Self {
+            definition,
+            f_opt: f_star(suite, id),
+            transforms,
+        }
+    }
+
+    pub fn x_opt(&self) -> &[f32] {
+        &self.transforms[0].shift
+    }
+
+    pub fn f_opt(&self) -> f32 {
+        self.f_opt
+    }
+
+    /// Value at `x` (coordinates in dimension order).
+    pub fn evaluate(&self, x: &[f32]) -> f32 {
+        let value = match self.definition {
+            Definition::Single(component) => self.transforms[0].evaluate(component, x),
+            Definition::Composition(layers) => self.composition(layers, x),
+        };
+        (value + self.f_opt as f64) as f32
+    }
+
+    fn composition(&self, layers: Composition, x: &[f32]) -> f64 {
+        let dims = x.len() as f64;
+        let mut weights = Vec::with_capacity(layers.len());
+        let mut values = Vec::with_capacity(layers.len());
+        for ((component, sigma, lambda, bias), transform) in layers.iter().zip(&self.transforms) {
+            let value = lambda * transform.evaluate(*component, x) + bias;
+            let dist: f64 = x
+                .iter()
+                .zip(&transform.shift)
+                .map(|(a, b)| (*a as f64 - *b as f64).powi(2))
+                .sum();
+            if dist == 0.0 {
+                // The weight of a component at its own optimum is infinite
+                return value;
+            }
+            weights.push(f64::exp(-dist / (2.0 * dims * sigma * sigma)) / dist.sqrt());
+            values.push(value);
+        }
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 {
+            return values.iter().sum::<f64>() / values.len() as f64;
+        }
+        weights.iter().zip(&values).map(|(w, v)| w / total * v).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fit_in_bounds_simd;
+
+    fn all_functions() -> Vec<CecFunction> {
+        CEC2017_TEST_FUNCTIONS
+            .values()
+            .chain(CEC2022_TEST_FUNCTIONS.values())
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_optimum_values() {
+        for dims in [16, 32] {
+            for function in all_functions() {
+                let instance = function.instantiate(dims);
+                let at_opt = instance.evaluate(instance.x_opt());
+                assert!(
+                    (at_opt - instance.f_opt()).abs() <= 1e-3,
+                    "{:?} f{} {}D: f(x_opt)={} f_opt={}",
+                    function.suite,
+                    function.id,
+                    dims,
+                    at_opt,
+                    instance.f_opt()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimum_is_not_beaten_by_random_points() {
+        let mut rng: DefaultRng = stream_rng(7, 0);
+        let uniform = Uniform::new_inclusive(-100.0f32, 100.0).unwrap();
+        for function in all_functions() {
+            let instance = function.instantiate(16);
+            for _ in 0..100 {
+                let x: Vec<f32> = (0..16).map(|_| uniform.sample(&mut rng)).collect();
+                let value = instance.evaluate(&x);
+                assert!(value >= instance.f_opt() - 1e-3, "{:?} f{} below F*", function.suite, function.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hybrid_sizes_cover_all_dimensions() {
+        for hybrid in CEC2017_HYBRIDS.iter().chain(&CEC2022_HYBRIDS) {
+            for dims in [16, 32, 64] {
+                assert_eq!(hybrid_sizes(hybrid, dims).iter().sum::<usize>(), dims);
+            }
+        }
+        assert_eq!(hybrid_sizes(CEC2017_HYBRIDS[0], 32), vec![7, 13, 12]);
+    }
+
+    #[test]
+    fn test_instances_are_deterministic() {
+        let a = CecInstance::new(CecSuite::Cec2017, 5, 16);
+        let b = CecInstance::new(CecSuite::Cec2017, 5, 16);
+        let c = CecInstance::new(CecSuite::Cec2022, 5, 16);
+        assert_eq!(a.x_opt(), b.x_opt());
+        assert_ne!(a.x_opt(), c.x_opt());
+        assert!(a.x_opt().iter().all(|v| v.abs() <= 80.0));
+    }
+
+    #[test]
+    fn test_problem_objective() {
+        let function = CEC2017_TEST_FUNCTIONS["f21_composition_1"];
+        let instance = function.instantiate(32);
+        let zeros = vec![0.0; 32];
+        let ones = vec![1.0; 32];
+        let objective = function.objective(32);
+        let at_opt = objective(&fit_in_bounds_simd(instance.x_opt(), &zeros, &ones));
+        assert_eq!(at_opt, function.f_opt(32));
+        assert_eq!(function.f_opt(32), 2100.0);
+        assert_eq!(CEC2022_TEST_FUNCTIONS["f12_composition_4"].f_opt(16), 2700.0);
+    }
+}
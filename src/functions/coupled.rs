@@ -0,0 +1,310 @@
+//! Non-separable functions of the full vector.
+//!
+//! They work directly on the pair layout of `fit_in_bounds_simd`: for group
+//! `g`, `x[2g]` holds dims `16g, 16g+2, ..., 16g+14` and `x[2g+1]` holds dims
+//! `16g+1, ..., 16g+15`. Couplings between consecutive dims are formed with
+//! one lane shift per group, and sums run over whole `Vec8`s. Values are not
+//! scaled to `[0, 1]`; every function has minimum 0.
+
+use std::{collections::BTreeMap, f32::consts::{E, PI}};
+
+use once_cell::sync::Lazy;
+use simd_vector::Vec8;
+use simd_vector::fast::FastMath;
+
+use super::{Optimum, TestFn, TestFunction};
+use crate::utils::Vec8Ext;
+
+/// 1-based indices of the dims in `x[2g]` and `x[2g + 1]` for `g = 0`.
+const EVEN_INDEX: Vec8 = Vec8([1.0, 3.0, 5.0, 7.0, 9.0, 11.0, 13.0, 15.0]);
+const ODD_INDEX: Vec8 = Vec8([2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0]);
+
+/// Zeroes the term of the last dim, which has no successor.
+const LAST_LANE_OFF: Vec8 = Vec8([1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0]);
+
+/// Successors of the odd dims of group `g`: the even lanes shifted down by one,
+/// followed by the first even dim of group `g + 1` (0 in the last group).
+fn odd_successors(x: &[Vec8], g: usize) -> Vec8 {
+    let e = x[2 * g];
+    let next = x.get(2 * g + 2).map_or(0.0, |v| v[0]);
+    Vec8([e[1], e[2], e[3], e[4], e[5], e[6], e[7], next])
+}
+
+/// Sum of `term(x_i, x_{i+1})` over `i = 0..n-1`.
+fn chained_sum<T>(x: &[Vec8], term: T) -> f32
+where
+    T: Fn(Vec8, Vec8) -> Vec8,
+{
+    let groups = x.len() / 2;
+    let mut total = Vec8::ZERO;
+    for g in 0..groups {
+        let (e, o) = (x[2 * g], x[2 * g + 1]);
+        let odd_terms = term(o, odd_successors(x, g));
+        let odd_terms = if g + 1 == groups { odd_terms * LAST_LANE_OFF } else { odd_terms };
+        total = total + term(e, o) + odd_terms;
+    }
+    total.sum()
+}
+
+/// Sum of `term(x_i, i)` with 1-based `i`.
+fn indexed_sum<T>(x: &[Vec8], term: T) -> Vec8
+where
+    T: Fn(Vec8, Vec8) -> Vec8,
+{
+    let mut total = Vec8::ZERO;
+    for (g, pair) in x.chunks_exact(2).enumerate() {
+        let offset = Vec8::splat(16.0 * g as f32);
+        total = total + term(pair[0], EVEN_INDEX + offset) + term(pair[1], ODD_INDEX + offset);
+    }
+    total
+}
+
+fn dims(x: &[Vec8]) -> f32 {
+    (x.len() * 8) as f32
+}
+
+pub const CHAINED_ROSENBROCK_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const CHAINED_ROSENBROCK_OPTIMUM: Option<Optimum> = Some(Optimum { x: [1.0, 1.0], f: 0.0, target: 0.01 });
+
+/// `sum 100 (x_i^2 - x_{i+1})^2 + (x_i - 1)^2` over consecutive dims, divided
+/// by `n - 1`; minimum at `x = 1`.
+pub fn chained_rosenbrock(x: &[Vec8]) -> f32 {
+    let sum = chained_sum(x, |a, b| 100.0 * (a.square() - b).square() + (a - 1.0).square());
+    sum / (dims(x) - 1.0)
+}
+
+pub const SCHWEFEL_1_2_BOUNDS: [[f32; 2]; 2] = [[-10.0, 10.0], [-10.0, 10.0]];
+pub const SCHWEFEL_1_2_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
+
+/// Schwefel 1.2 (double sum) `sum_i (sum_{j<=i} x_j)^2 / n`; minimum at `x = 0`.
+pub fn schwefel_1_2(x: &[Vec8]) -> f32 {
+    let mut carry = 0.0;
+    let mut total = Vec8::ZERO;
+    for pair in x.chunks_exact(2) {
+        let (e, o) = (pair[0], pair[1]);
+        // Inclusive prefix sums of the pair sums, in dim order
+        let mut prefix = (e + o).0;
+        for j in 1..8 {
+            prefix[j] += prefix[j - 1];
+        }
+        let odd_partial = Vec8(prefix) + carry;
+        let even_partial = odd_partial - o;
+        total = total + even_partial.square() + odd_partial.square();
+        carry += prefix[7];
+    }
+    total.sum() / dims(x)
+}
+
+pub const CHAINED_LEVY_BOUNDS: [[f32; 2]; 2] = [[-10.0, 10.0], [-10.0, 10.0]];
+pub const CHAINED_LEVY_OPTIMUM: Option<Optimum> = Some(Optimum { x: [1.0, 1.0], f: 0.0, target: 0.01 });
+
+/// A chained variant of the Levy function, not the standard one: the
+/// oscillation of each term is driven by the next dim rather than its own, so
+/// that neighbouring dims interact. With `w = 1 + (x - 1) / 4`,
+/// `sin^2(pi w_1) + sum (w_i - 1)^2 (1 + 10 sin^2(pi w_{i+1})) + (w_n - 1)^2 (1 + sin^2(2 pi w_n))`,
+/// divided by `n`; minimum at `x = 1`.
+pub fn chained_levy(x: &[Vec8]) -> f32 {
+    let w = |v: Vec8| (v - 1.0) * 0.25 + 1.0;
+    let sum = chained_sum(x, |a, b| {
+        let s = (PI * w(b)).sin();
+        (w(a) - 1.0).square() * (10.0 * s.square() + 1.0)
+    });
+    let first = f32::sin(PI * (1.0 + (x[0][0] - 1.0) * 0.25)).powi(2);
+    let last = 1.0 + (x[x.len() - 1][7] - 1.0) * 0.25;
+    let last = (last - 1.0).powi(2) * (1.0 + f32::sin(2.0 * PI * last).powi(2));
+    (first + sum + last) / dims(x)
+}
+
+pub const GRIEWANK_BOUNDS: [[f32; 2]; 2] = [[-600.0, 600.0], [-600.0, 600.0]];
+pub const GRIEWANK_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
+
+/// `1 + sum x_i^2 / 4000 - prod cos(x_i / sqrt(i))`; minimum at `x = 0`.
+pub fn griewank(x: &[Vec8]) -> f32 {
+    let mut squares = Vec8::ZERO;
+    let mut product = Vec8::splat(1.0);
+    for (g, pair) in x.chunks_exact(2).enumerate() {
+        let offset = Vec8::splat(16.0 * g as f32);
+        for (v, index) in [(pair[0], EVEN_INDEX), (pair[1], ODD_INDEX)] {
+            squares = v.mul_add(v, squares);
+            product = product * (v / (index + offset).sqrt()).cos();
+        }
+    }
+    1.0 + squares.sum() / 4000.0 - product.0.iter().product::<f32>()
+}
+
+pub const ZAKHAROV_BOUNDS: [[f32; 2]; 2] = [[-5.0, 10.0], [-5.0, 10.0]];
+pub const ZAKHAROV_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
+
+/// `(sum x_i^2 + s^2 + s^4) / n` with `s = sum 0.5 i x_i`; minimum at `x = 0`.
+pub fn zakharov(x: &[Vec8]) -> f32 {
+    let squares: Vec8 = x.iter().map(|v| v.square()).sum();
+    let s = indexed_sum(x, |v, index| 0.5 * index * v).sum();
+    (squares.sum() + s * s + s.powi(4)) / dims(x)
+}
+
+pub const STYBLINSKI_TANG_BOUNDS: [[f32; 2]; 2] = [[-5.0, 5.0], [-5.0, 5.0]];
+pub const STYBLINSKI_TANG_OPTIMUM: Option<Optimum> = Some(Optimum { x: [-2.903_534, -2.903_534], f: 0.0, target: 0.01 });
+
+/// Minimum of one Styblinski-Tang term `(x^4 - 16 x^2 + 5 x) / 2`, at `x = -2.903534`.
+const STYBLINSKI_TANG_MIN: f32 = -39.166_165;
+
+/// Styblinski-Tang, `sum (x_i^4 - 16 x_i^2 + 5 x_i) / 2 / n`, shifted to minimum 0.
+/// It is separable, but has `2^n` basins over the full box instead of repeating
+/// one 2D landscape per pair.
+pub fn styblinski_tang(x: &[Vec8]) -> f32 {
+    let sum: Vec8 = x
+        .iter()
+        .map(|v| {
+            let sq = v.square();
+            (sq * sq - 16.0 * sq + 5.0 * *v) * 0.5
+        })
+        .sum();
+    sum.sum() / dims(x) - STYBLINSKI_TANG_MIN
+}
+
+pub const ACKLEY_BOUNDS: [[f32; 2]; 2] = [[-32.768, 32.768], [-32.768, 32.768]];
+pub const ACKLEY_OPTIMUM: Option<Optimum> = Some(Optimum { x: [0.0, 0.0], f: 0.0, target: 0.01 });
+
+/// `-20 exp(-0.2 sqrt(mean x^2)) - exp(mean cos(2 pi x)) + 20 + e`; minimum at `x = 0`.
+pub fn ackley(x: &[Vec8]) -> f32 {
+    let mut squares = Vec8::ZERO;
+    let mut cosines = Vec8::ZERO;
+    for v in x {
+        squares = v.mul_add(*v, squares);
+        cosines = cosines + (2.0 * PI * *v).cos();
+    }
+    let n = dims(x);
+    let value = -20.0 * f32::exp(-0.2 * (squares.sum() / n).sqrt()) - f32::exp(cosines.sum() / n) + 20.0 + E;
+    value.max(0.0)
+}
+
+/// Non-separable functions of the full vector.
+pub static COUPLED_TEST_FUNCTIONS: Lazy<BTreeMap<String, TestFunction>> = Lazy::new(|| {
+    let mut m = BTreeMap::new();
+    m.insert("chained_rosenbrock".to_string(), TestFunction { func: TestFn::Full(chained_rosenbrock), bounds: CHAINED_ROSENBROCK_BOUNDS, optimum: CHAINED_ROSENBROCK_OPTIMUM });
+    m.insert("schwefel_1_2".to_string(), TestFunction { func: TestFn::Full(schwefel_1_2), bounds: SCHWEFEL_1_2_BOUNDS, optimum: SCHWEFEL_1_2_OPTIMUM });
+    m.insert("chained_levy".to_string(), TestFunction { func: TestFn::Full(chained_levy), bounds: CHAINED_LEVY_BOUNDS, optimum: CHAINED_LEVY_OPTIMUM });
+    m.insert("griewank".to_string(), TestFunction { func: TestFn::Full(griewank), bounds: GRIEWANK_BOUNDS, optimum: GRIEWANK_OPTIMUM });
+    m.insert("zakharov".to_string(), TestFunction { func: TestFn::Full(zakharov), bounds: ZAKHAROV_BOUNDS, optimum: ZAKHAROV_OPTIMUM });
+    m.insert("styblinski_tang".to_string(), TestFunction { func: TestFn::Full(styblinski_tang), bounds: STYBLINSKI_TANG_BOUNDS, optimum: STYBLINSKI_TANG_OPTIMUM });
+    m.insert("ackley".to_string(), TestFunction { func: TestFn::Full(ackley), bounds: ACKLEY_BOUNDS, optimum: ACKLEY_OPTIMUM });
+    m
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fit_in_bounds_simd;
+
+    fn pack(values: &[f32]) -> Vec<Vec8> {
+        let n = values.len();
+        fit_in_bounds_simd(values, &vec![0.0; n], &vec![1.0; n])
+    }
+
+    fn scalar_rosenbrock(x: &[f32]) -> f32 {
+        let sum: f32 = x
+            .windows(2)
+            .map(|w| 100.0 * (w[0] * w[0] - w[1]).powi(2) + (w[0] - 1.0).powi(2))
+            .sum();
+        sum / (x.len() - 1) as f32
+    }
+
+    fn scalar_schwefel_1_2(x: &[f32]) -> f32 {
+        let mut partial = 0.0;
+        let mut sum = 0.0;
+        for v in x {
+            partial += v;
+            sum += partial * partial;
+        }
+        sum / x.len() as f32
+    }
+
+    fn scalar_griewank(x: &[f32]) -> f32 {
+        let squares: f32 = x.iter().map(|v| v * v).sum();
+        let product: f32 = x
+            .iter()
+            .enumerate()
+            .map(|(i, v)| f32::cos(v / ((i + 1) as f32).sqrt()))
+            .product();
+        1.0 + squares / 4000.0 - product
+    }
+
+    fn scalar_zakharov(x: &[f32]) -> f32 {
+        let squares: f32 = x.iter().map(|v| v * v).sum();
+        let s: f32 = x.iter().enumerate().map(|(i, v)| 0.5 * (i + 1) as f32 * v).sum();
+        (squares + s * s + s.powi(4)) / x.len() as f32
+    }
+
+    fn scalar_chained_levy(x: &[f32]) -> f32 {
+        let w: Vec<f32> = x.iter().map(|v| 1.0 + (v - 1.0) / 4.0).collect();
+        let n = w.len();
+        let middle: f32 = (0..n - 1)
+            .map(|i| (w[i] - 1.0).powi(2) * (1.0 + 10.0 * f32::sin(PI * w[i + 1]).powi(2)))
+            .sum();
+        let last = (w[n - 1] - 1.0).powi(2) * (1.0 + f32::sin(2.0 * PI * w[n - 1]).powi(2));
+        (f32::sin(PI * w[0]).powi(2) + middle + last) / n as f32
+    }
+
+    fn sample_point(n: usize) -> Vec<f32> {
+        (0..n).map(|i| ((i * 37 % 11) as f32 - 5.0) * 0.3).collect()
+    }
+
+    #[test]
+    fn test_matches_scalar_definitions() {
+        let cases: [(fn(&[Vec8]) -> f32, fn(&[f32]) -> f32); 5] = [
+            (chained_rosenbrock, scalar_rosenbrock),
+            (schwefel_1_2, scalar_schwefel_1_2),
+            (griewank, scalar_griewank),
+            (zakharov, scalar_zakharov),
+            (chained_levy, scalar_chained_levy),
+        ];
+        for n in [16, 48] {
+            let x = sample_point(n);
+            for (simd, scalar) in cases {
+                let (a, b) = (simd(&pack(&x)), scalar(&x));
+                assert!((a - b).abs() <= 1e-3 * b.abs().max(1.0), "simd={} scalar={}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_minima() {
+        let n = 32;
+        let at = |v: f32| pack(&vec![v; n]);
+        assert!(chained_rosenbrock(&at(1.0)).abs() < 1e-6);
+        assert!(schwefel_1_2(&at(0.0)).abs() < 1e-6);
+        assert!(chained_levy(&at(1.0)).abs() < 1e-6);
+        assert!(griewank(&at(0.0)).abs() < 1e-6);
+        assert!(zakharov(&at(0.0)).abs() < 1e-6);
+        assert!(styblinski_tang(&at(-2.903_534)).abs() < 1e-4);
+        assert!(ackley(&at(0.0)).abs() < 1e-5);
+        assert!(ackley(&at(1.0)) > 1.0);
+    }
+
+    #[test]
+    fn test_dims_in_different_pairs_interact() {
+        // Mixed difference over dims 1 and 2 (pairs 0 and 1) is 0 for any
+        // function that is a sum over independent coordinate pairs
+        let coupled: [fn(&[Vec8]) -> f32; 6] = [chained_rosenbrock, schwefel_1_2, chained_levy, griewank, zakharov, ackley];
+        let x = sample_point(16);
+        let moved = |d1: f32, d2: f32| {
+            let mut y = x.clone();
+            y[1] += d1;
+            y[2] += d2;
+            pack(&y)
+        };
+        for f in coupled {
+            let mixed = f(&moved(1.5, 1.5)) - f(&moved(1.5, 0.0)) - f(&moved(0.0, 1.5)) + f(&moved(0.0, 0.0));
+            assert!(mixed.abs() > 1e-4, "mixed difference {}", mixed);
+        }
+    }
+
+    #[test]
+    fn test_coupled_test_functions() {
+        let funcs = &*COUPLED_TEST_FUNCTIONS;
+        assert_eq!(funcs.len(), 7);
+        for tf in funcs.values() {
+            assert!(tf.func.evaluate(&[Vec8::splat(0.5); 4]).is_finite());
+        }
+    }
+}
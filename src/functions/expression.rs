@@ -0,0 +1,786 @@
+//! Objectives defined at runtime by a math expression, e.g.
+//! `sum(x_i^2 - 10*cos(2*pi*x_i)) + 10*n`.
+//!
+//! Grammar, loosest binding first:
+//!
+//! ```text
+//! expr    = term (("+" | "-") term)*
+//! term    = unary (("*" | "/") unary)*
+//! unary   = ("-" | "+") unary | power
+//! power   = primary (("^" | "**") unary)?          right-associative
+//! primary = number | name | name "(" expr ("," expr)* ")" | "(" expr ")"
+//! ```
+//!
+//! Names are `pi`, `e`, `n` (dimension count), `x_1`, `x_2`, ... (one
+//! coordinate), and inside `sum(...)` / `prod(...)` also `x_i` and its 1-based
+//! index `i`. Functions are `sin`, `cos`, `exp`, `sqrt`, `abs`, `floor` and
+//! `pow(a, b)`. `sum` and `prod` run over all dimensions and cannot be nested.
+//!
+//! The parsed tree is constant-folded and compiled to a postfix program that
+//! runs on `Vec8`s, so the body of a `sum` evaluates 8 dimensions at a time.
+
+use std::{
+    f32::consts::{E, PI},
+    fmt,
+    str::FromStr,
+};
+
+use simd_vector::Vec8;
+use simd_vector::fast::FastMath;
+
+use super::{Objective, Problem};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Byte offset into the source.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(usize, usize),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    Comma,
+    Open,
+    Close,
+    End,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+        let token = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                pos += 1;
+                continue;
+            }
+            b'0'..=b'9' | b'.' => {
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                // Exponent, only when digits follow so that `2e` stays `2 e`
+                if pos < bytes.len() && (bytes[pos] == b'e' || bytes[pos] == b'E') {
+                    let mut end = pos + 1;
+                    if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+                        end += 1;
+                    }
+                    if end < bytes.len() && bytes[end].is_ascii_digit() {
+                        pos = end;
+                        while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                            pos += 1;
+                        }
+                    }
+                }
+                let text = &source[start..pos];
+                let value = text.parse().map_err(|_| ParseError {
+                    position: start,
+                    message: format!("invalid number `{text}`"),
+                })?;
+                tokens.push((start, Token::Number(value)));
+                continue;
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                    pos += 1;
+                }
+                tokens.push((start, Token::Name(start, pos)));
+                continue;
+            }
+            b'*' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos += 1;
+                Token::Caret
+            }
+            b'+' => Token::Plus,
+            b'-' => Token::Minus,
+            b'*' => Token::Star,
+            b'/' => Token::Slash,
+            b'^' => Token::Caret,
+            b',' => Token::Comma,
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            _ => {
+                return Err(ParseError {
+                    position: start,
+                    message: format!("unexpected character `{}`", source[start..].chars().next().unwrap()),
+                })
+            }
+        };
+        pos += 1;
+        tokens.push((start, token));
+    }
+    tokens.push((source.len(), Token::End));
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Func {
+    Neg,
+    Sin,
+    Cos,
+    Exp,
+    Sqrt,
+    Abs,
+    Floor,
+}
+
+impl Func {
+    fn apply(self, v: Vec8) -> Vec8 {
+        match self {
+            Func::Neg => -v,
+            Func::Sin => v.sin(),
+            Func::Cos => v.cos(),
+            Func::Exp => v.exp(),
+            Func::Sqrt => v.sqrt(),
+            Func::Abs => v.abs(),
+            Func::Floor => v.floor(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinOp {
+    fn apply(self, a: Vec8, b: Vec8) -> Vec8 {
+        match self {
+            BinOp::Add => a + b,
+            BinOp::Sub => a - b,
+            BinOp::Mul => a * b,
+            BinOp::Div => a / b,
+            BinOp::Pow => Vec8(std::array::from_fn(|j| a[j].powf(b[j]))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reduction {
+    Sum,
+    Prod,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f32),
+    /// `x_i` inside a reduction.
+    X,
+    /// `i` inside a reduction.
+    Index,
+    Dims,
+    /// 0-based coordinate.
+    Coordinate(usize),
+    Unary(Func, Box<Node>),
+    Binary(BinOp, Box<Node>, Box<Node>),
+    Reduce(Reduction, Box<Node>),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    in_reduction: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.tokens[self.next].1
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.next].0
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.peek();
+        if token != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.position(),
+            message,
+        })
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseError> {
+        if self.peek() == token {
+            self.bump();
+            Ok(())
+        } else {
+            self.error(format!("expected {what}"))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Token::Plus => BinOp::Add,
+                Token::Minus => BinOp::Sub,
+                _ => return Ok(node),
+            };
+            self.bump();
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Star => BinOp::Mul,
+                Token::Slash => BinOp::Div,
+                _ => return Ok(node),
+            };
+            self.bump();
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        match self.peek() {
+            Token::Minus => {
+                self.bump();
+                Ok(Node::Unary(Func::Neg, Box::new(self.unary()?)))
+            }
+            Token::Plus => {
+                self.bump();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Node, ParseError> {
+        let base = self.primary()?;
+        if self.peek() == Token::Caret {
+            self.bump();
+            Ok(Node::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, ParseError> {
+        let position = self.position();
+        match self.bump() {
+            Token::Number(v) => Ok(Node::Number(v)),
+            Token::Open => {
+                let node = self.expr()?;
+                self.expect(Token::Close, "`)`")?;
+                Ok(node)
+            }
+            Token::Name(start, end) => {
+                let name = &self.source[start..end];
+                if self.peek() == Token::Open {
+                    self.call(name, position)
+                } else {
+                    self.variable(name, position)
+                }
+            }
+            Token::End => self.error("unexpected end of expression".to_string()),
+            _ => Err(ParseError {
+                position,
+                message: "expected a number, name or `(`".to_string(),
+            }),
+        }
+    }
+
+    fn variable(&self, name: &str, position: usize) -> Result<Node, ParseError> {
+        let error = |message: String| Err(ParseError { position, message });
+        match name {
+            "pi" => Ok(Node::Number(PI)),
+            "e" => Ok(Node::Number(E)),
+            "n" => Ok(Node::Dims),
+            "i" | "x_i" if !self.in_reduction => error(format!("`{name}` is only defined inside sum or prod")),
+            "i" => Ok(Node::Index),
+            "x_i" => Ok(Node::X),
+            _ => match name.strip_prefix("x_").and_then(|k| k.parse::<usize>().ok()) {
+                Some(k) if k >= 1 => Ok(Node::Coordinate(k - 1)),
+                Some(_) => error("coordinates are numbered from x_1".to_string()),
+                None => error(format!("unknown name `{name}`")),
+            },
+        }
+    }
+
+    fn call(&mut self, name: &str, position: usize) -> Result<Node, ParseError> {
+        let reduction = match name {
+            "sum" => Some(Reduction::Sum),
+            "prod" => Some(Reduction::Prod),
+            _ => None,
+        };
+        if reduction.is_some() && self.in_reduction {
+            return Err(ParseError {
+                position,
+                message: format!("`{name}` cannot be nested in another sum or prod"),
+            });
+        }
+        self.bump();
+        self.in_reduction |= reduction.is_some();
+        let mut args = vec![self.expr()?];
+        while self.peek() == Token::Comma {
+            self.bump();
+            args.push(self.expr()?);
+        }
+        self.expect(Token::Close, "`)`")?;
+        if reduction.is_some() {
+            self.in_reduction = false;
+        }
+        let arity = if name == "pow" { 2 } else { 1 };
+        if args.len() != arity {
+            return Err(ParseError {
+                position,
+                message: format!("`{name}` takes {arity} argument(s), got {}", args.len()),
+            });
+        }
+        let func = match name {
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "exp" => Func::Exp,
+            "sqrt" => Func::Sqrt,
+            "abs" => Func::Abs,
+            "floor" => Func::Floor,
+            "pow" => {
+                let exponent = args.pop().unwrap();
+                let base = args.pop().unwrap();
+                return Ok(Node::Binary(BinOp::Pow, Box::new(base), Box::new(exponent)));
+            }
+            _ => match reduction {
+                Some(reduction) => return Ok(Node::Reduce(reduction, Box::new(args.pop().unwrap()))),
+                None => {
+                    return Err(ParseError {
+                        position,
+                        message: format!("unknown function `{name}`"),
+                    })
+                }
+            },
+        };
+        Ok(Node::Unary(func, Box::new(args.pop().unwrap())))
+    }
+}
+
+/// Fold operations on constants.
+fn fold(node: Node) -> Node {
+    match node {
+        Node::Unary(func, a) => match fold(*a) {
+            Node::Number(v) => Node::Number(func.apply(Vec8::splat(v))[0]),
+            a => Node::Unary(func, Box::new(a)),
+        },
+        Node::Binary(op, a, b) => match (fold(*a), fold(*b)) {
+            (Node::Number(u), Node::Number(v)) => Node::Number(op.apply(Vec8::splat(u), Vec8::splat(v))[0]),
+            (a, b) => Node::Binary(op, Box::new(a), Box::new(b)),
+        },
+        Node::Reduce(reduction, body) => Node::Reduce(reduction, Box::new(fold(*body))),
+        node => node,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Push(f32),
+    X,
+    Index,
+    Dims,
+    Coordinate(usize),
+    Unary(Func),
+    Binary(BinOp),
+    /// Small integer power, by repeated multiplication.
+    PowInt(i32),
+    Reduce(Reduction, Program),
+}
+
+/// Postfix program over a stack of `Vec8`s.
+#[derive(Debug, Clone)]
+struct Program {
+    ops: Vec<Op>,
+    depth: usize,
+}
+
+impl Program {
+    fn compile(node: &Node) -> Self {
+        let mut ops = Vec::new();
+        let depth = Self::emit(node, &mut ops);
+        Program { ops, depth }
+    }
+
+    /// Append the ops of `node`, returning the stack depth it needs.
+    fn emit(node: &Node, ops: &mut Vec<Op>) -> usize {
+        match node {
+            Node::Number(v) => ops.push(Op::Push(*v)),
+            Node::X => ops.push(Op::X),
+            Node::Index => ops.push(Op::Index),
+            Node::Dims => ops.push(Op::Dims),
+            Node::Coordinate(k) => ops.push(Op::Coordinate(*k)),
+            Node::Unary(func, a) => {
+                let depth = Self::emit(a, ops);
+                ops.push(Op::Unary(*func));
+                return depth;
+            }
+            Node::Binary(BinOp::Pow, a, b)
+                if matches!(**b, Node::Number(k) if k.fract() == 0.0 && k.abs() <= 16.0) =>
+            {
+                let Node::Number(k) = **b else { unreachable!() };
+                let depth = Self::emit(a, ops);
+                ops.push(Op::PowInt(k as i32));
+                return depth;
+            }
+            Node::Binary(op, a, b) => {
+                let depth = Self::emit(a, ops).max(1 + Self::emit(b, ops));
+                ops.push(Op::Binary(*op));
+                return depth;
+            }
+            Node::Reduce(reduction, body) => ops.push(Op::Reduce(*reduction, Program::compile(body))),
+        }
+        1
+    }
+
+    /// Run on one chunk of 8 dims: `x` and their 1-based `index` are only
+    /// read inside a reduction.
+    fn run(&self, point: &Point, x: Vec8, index: Vec8, stack: &mut Vec<Vec8>) -> Vec8 {
+        let base = stack.len();
+        for op in &self.ops {
+            let value = match op {
+                Op::Push(v) => Vec8::splat(*v),
+                Op::X => x,
+                Op::Index => index,
+                Op::Dims => Vec8::splat(point.dims() as f32),
+                Op::Coordinate(k) => Vec8::splat(point.coordinate(*k)),
+                Op::Unary(func) => {
+                    let a = stack.pop().unwrap();
+                    func.apply(a)
+                }
+                Op::Binary(op) => {
+                    let b = stack.pop().unwrap();
+                    let a = stack.pop().unwrap();
+                    op.apply(a, b)
+                }
+                Op::PowInt(k) => {
+                    let a = stack.pop().unwrap();
+                    pow_int(a, *k)
+                }
+                Op::Reduce(reduction, body) => Vec8::splat(body.reduce(*reduction, point, stack)),
+            };
+            stack.push(value);
+        }
+        let result = stack.pop().unwrap();
+        debug_assert_eq!(stack.len(), base);
+        result
+    }
+
+    fn reduce(&self, reduction: Reduction, point: &Point, stack: &mut Vec<Vec8>) -> f32 {
+        let identity = match reduction {
+            Reduction::Sum => 0.0,
+            Reduction::Prod => 1.0,
+        };
+        let mut total = Vec8::splat(identity);
+        for c in 0..point.chunk_count() {
+            let (x, index, valid) = point.chunk(c);
+            let mut value = self.run(point, x, index, stack);
+            for lane in valid..8 {
+                value.0[lane] = identity;
+            }
+            total = match reduction {
+                Reduction::Sum => total + value,
+                Reduction::Prod => total * value,
+            };
+        }
+        match reduction {
+            Reduction::Sum => total.sum(),
+            Reduction::Prod => total.0.iter().product(),
+        }
+    }
+}
+
+fn pow_int(a: Vec8, k: i32) -> Vec8 {
+    let mut result = Vec8::splat(1.0);
+    let mut base = a;
+    let mut e = k.unsigned_abs();
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result * base;
+        }
+        base = base * base;
+        e >>= 1;
+    }
+    if k < 0 {
+        Vec8::splat(1.0) / result
+    } else {
+        result
+    }
+}
+
+/// 1-based indices of the dims in `x[2g]` and `x[2g + 1]` for `g = 0`.
+const EVEN_INDEX: Vec8 = Vec8([1.0, 3.0, 5.0, 7.0, 9.0, 11.0, 13.0, 15.0]);
+const ODD_INDEX: Vec8 = Vec8([2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0]);
+
+/// The point an expression is evaluated at.
+enum Point<'a> {
+    /// Pair layout of `fit_in_bounds_simd`.
+    Pairs(&'a [Vec8]),
+    /// Dimension order, any length.
+    Dims(&'a [f32]),
+}
+
+impl Point<'_> {
+    fn dims(&self) -> usize {
+        match self {
+            Point::Pairs(x) => x.len() * 8,
+            Point::Dims(x) => x.len(),
+        }
+    }
+
+    fn coordinate(&self, k: usize) -> f32 {
+        match self {
+            Point::Pairs(x) => {
+                let (g, r) = (k / 16, k % 16);
+                x[2 * g + r % 2][r / 2]
+            }
+            Point::Dims(x) => x[k],
+        }
+    }
+
+    fn chunk_count(&self) -> usize {
+        match self {
+            Point::Pairs(x) => x.len(),
+            Point::Dims(x) => x.len().div_ceil(8),
+        }
+    }
+
+    /// Values, 1-based indices and number of valid lanes of chunk `c`.
+    fn chunk(&self, c: usize) -> (Vec8, Vec8, usize) {
+        match self {
+            Point::Pairs(x) => {
+                let index = if c.is_multiple_of(2) { EVEN_INDEX } else { ODD_INDEX };
+                (x[c], index + 16.0 * (c / 2) as f32, 8)
+            }
+            Point::Dims(x) => {
+                let values = &x[8 * c..x.len().min(8 * c + 8)];
+                let mut chunk = [0.0; 8];
+                chunk[..values.len()].copy_from_slice(values);
+                let index = Vec8(std::array::from_fn(|j| (8 * c + j + 1) as f32));
+                (Vec8(chunk), index, values.len())
+            }
+        }
+    }
+}
+
+/// A parsed and compiled expression.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    program: Program,
+    /// Number of dims needed by the highest `x_k`.
+    min_dims: usize,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            source,
+            tokens: tokenize(source)?,
+            next: 0,
+            in_reduction: false,
+        };
+        let node = parser.expr()?;
+        if parser.peek() != Token::End {
+            return parser.error("unexpected input after expression".to_string());
+        }
+        let node = fold(node);
+        Ok(Expression {
+            source: source.to_string(),
+            program: Program::compile(&node),
+            min_dims: max_coordinate(&node),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Smallest dimension count the expression can be evaluated at.
+    pub fn min_dims(&self) -> usize {
+        self.min_dims
+    }
+
+    /// Value at `x` in dimension order.
+    pub fn evaluate(&self, x: &[f32]) -> f32 {
+        self.run(&Point::Dims(x))
+    }
+
+    /// Value at `x` in the pair layout of `fit_in_bounds_simd`.
+    pub fn evaluate_simd(&self, x: &[Vec8]) -> f32 {
+        self.run(&Point::Pairs(x))
+    }
+
+    fn run(&self, point: &Point) -> f32 {
+        assert!(
+            point.dims() >= self.min_dims,
+            "`{}` needs at least {} dims",
+            self.source,
+            self.min_dims
+        );
+        let mut stack = Vec::with_capacity(2 * self.program.depth);
+        self.program.run(point, Vec8::ZERO, Vec8::ZERO, &mut stack)[0]
+    }
+}
+
+fn max_coordinate(node: &Node) -> usize {
+    match node {
+        Node::Coordinate(k) => k + 1,
+        Node::Unary(_, a) | Node::Reduce(_, a) => max_coordinate(a),
+        Node::Binary(_, a, b) => max_coordinate(a).max(max_coordinate(b)),
+        _ => 0,
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Expression::parse(source)
+    }
+}
+
+/// An `Expression` with the same bounds on every coordinate. Its optimum is
+/// unknown, so targets are relative to 0.
+#[derive(Debug, Clone)]
+pub struct ExpressionFunction {
+    pub expression: Expression,
+    pub bounds: [f32; 2],
+}
+
+impl ExpressionFunction {
+    pub fn parse(source: &str, bounds: [f32; 2]) -> Result<Self, ParseError> {
+        Ok(ExpressionFunction {
+            expression: Expression::parse(source)?,
+            bounds,
+        })
+    }
+
+    /// Whether the expression can be evaluated at `dims` dims, for command
+    /// lines to report before the assert in `evaluate` is hit.
+    pub fn check_dims(&self, dims: usize) -> Result<(), String> {
+        let min_dims = self.expression.min_dims();
+        if dims < min_dims {
+            return Err(format!("`{}` needs at least {min_dims} dims, got {dims}", self.expression.source()));
+        }
+        Ok(())
+    }
+}
+
+impl Problem for ExpressionFunction {
+    fn objective(&self, _dims: usize) -> Objective<'_> {
+        Box::new(|x: &[Vec8]| self.expression.evaluate_simd(x))
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        vec![self.bounds; dims]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        functions::coupled::{griewank, zakharov},
+        utils::pack_pair_layout,
+    };
+
+    fn eval(source: &str, x: &[f32]) -> f32 {
+        Expression::parse(source).unwrap().evaluate(x)
+    }
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        assert_eq!(eval("1 - 2 - 3", &[]), -4.0);
+        assert_eq!(eval("2 * 3 + 4 / 2", &[]), 8.0);
+        assert_eq!(eval("-2^2", &[]), -4.0);
+        assert_eq!(eval("2^3^2", &[]), 512.0);
+        assert_eq!(eval("2**-1", &[]), 0.5);
+        assert_eq!(eval("pow(x_2, 3) - floor(x_1) + abs(-n)", &[1.5, 2.0]), 8.0 - 1.0 + 2.0);
+        assert_eq!(eval("1.5e1 + 2*e", &[]), 15.0 + 2.0 * E);
+    }
+
+    #[test]
+    fn test_rastrigin_in_both_layouts() {
+        let source = "10*n + sum(x_i^2 - 10*cos(2*pi*x_i))";
+        let expression = Expression::parse(source).unwrap();
+        let x: Vec<f32> = (0..32).map(|i| (i as f32 * 0.7).sin() * 3.0).collect();
+        let expected: f32 = 10.0 * 32.0
+            + x.iter().map(|v| v * v - 10.0 * (2.0 * PI * v).cos()).sum::<f32>();
+        assert!((expression.evaluate(&x) - expected).abs() < 1e-2);
+        assert!((expression.evaluate_simd(&pack_pair_layout(&x)) - expected).abs() < 1e-2);
+        // Dimension order works for lengths that are not a multiple of 8
+        let expected: f32 = 30.0 + x[..3].iter().map(|v| v * v - 10.0 * (2.0 * PI * v).cos()).sum::<f32>();
+        assert!((expression.evaluate(&x[..3]) - expected).abs() < 1e-3);
+        assert!(expression.evaluate(&[0.0; 5]).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_index_and_prod_follow_dimension_order() {
+        let x: Vec<f32> = (0..32).map(|i| (i as f32 * 0.37).cos() * 2.0).collect();
+        let packed = pack_pair_layout(&x);
+        let z = Expression::parse("(sum(x_i^2) + sum(0.5*i*x_i)^2 + sum(0.5*i*x_i)^4) / n").unwrap();
+        let expected = zakharov(&packed);
+        assert!((z.evaluate_simd(&packed) - expected).abs() <= 1e-3 * expected.abs());
+        let g = Expression::parse("1 + sum(x_i^2)/4000 - prod(cos(x_i/sqrt(i)))").unwrap();
+        assert!((g.evaluate_simd(&packed) - griewank(&packed)).abs() < 1e-4);
+        assert!((g.evaluate(&x) - griewank(&packed)).abs() < 1e-4);
+        let coordinate = Expression::parse("x_18").unwrap();
+        assert_eq!(coordinate.evaluate_simd(&packed), x[17]);
+        assert_eq!(coordinate.min_dims(), 18);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |source: &str| Expression::parse(source).unwrap_err();
+        assert_eq!(error("1 + foo").position, 4);
+        assert_eq!(error("x_i + 1").message, "`x_i` is only defined inside sum or prod");
+        assert_eq!(error("sum(sum(x_i))").position, 4);
+        assert_eq!(error("sin(1, 2)").message, "`sin` takes 1 argument(s), got 2");
+        assert_eq!(error("(1 + 2").message, "expected `)`");
+        assert_eq!(error("1 2").message, "unexpected input after expression");
+        assert_eq!(error("2 # 3").position, 2);
+        assert_eq!(error("x_0").message, "coordinates are numbered from x_1");
+        assert!(error("tan(1)").message.contains("unknown function"));
+    }
+
+    #[test]
+    fn test_expression_function_is_a_problem() {
+        let function = ExpressionFunction::parse("sum(abs(x_i - 1))", [-5.0, 5.0]).unwrap();
+        assert_eq!(function.bounds(16), vec![[-5.0, 5.0]; 16]);
+        let objective = function.objective(16);
+        assert_eq!(objective(&pack_pair_layout(&[1.0; 16])), 0.0);
+        assert_eq!(objective(&pack_pair_layout(&[2.0; 16])), 16.0);
+        assert!(function.check_dims(2).is_ok());
+        let coordinate = ExpressionFunction::parse("x_18 + x_1", [-5.0, 5.0]).unwrap();
+        assert!(coordinate.check_dims(18).is_ok());
+        assert_eq!(coordinate.check_dims(16).unwrap_err(), "`x_18 + x_1` needs at least 18 dims, got 16");
+    }
+}
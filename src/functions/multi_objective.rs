@@ -0,0 +1,313 @@
+//! Multi-objective test problems: ZDT (Zitzler, Deb and Thiele 2000) with two
+//! objectives and DTLZ (Deb, Thiele, Laumanns and Zitzler 2002) with three.
+//!
+//! Unlike the single-objective suites they take points in dimension order,
+//! and every objective is minimised.
+
+use std::{collections::BTreeMap, f32::consts::PI};
+
+use once_cell::sync::Lazy;
+
+use crate::pareto::{lattice_divisions, non_dominated, simplex_lattice};
+
+/// A benchmark problem for the `MultiObjectiveOptimizer`s.
+pub trait MultiObjectiveProblem: Sync {
+    fn objective_count(&self) -> usize;
+
+    /// Writes the objective values of `x`, in dimension order, into `f`.
+    fn evaluate(&self, x: &[f32], f: &mut [f32]);
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]>;
+
+    /// Dimension the problem is usually run at.
+    fn default_dims(&self) -> usize;
+
+    /// About `points` points spread over the true Pareto front, for `igd`.
+    fn pareto_front(&self, points: usize) -> Vec<Vec<f32>>;
+
+    /// Reference point for `hypervolume`, worse than the whole front.
+    fn reference_point(&self) -> Vec<f32>;
+}
+
+#[derive(Clone, Copy)]
+pub struct MultiObjectiveFunction {
+    pub func: fn(&[f32], &mut [f32]),
+    pub objective_count: usize,
+    pub dims: usize,
+    /// Bounds of the first coordinate and of all others.
+    pub bounds: [[f32; 2]; 2],
+    /// Sampler of the true front for a number of points.
+    pub front: fn(usize) -> Vec<Vec<f32>>,
+    /// Every component of the hypervolume reference point.
+    pub reference: f32,
+}
+
+impl MultiObjectiveProblem for MultiObjectiveFunction {
+    fn objective_count(&self) -> usize {
+        self.objective_count
+    }
+
+    fn evaluate(&self, x: &[f32], f: &mut [f32]) {
+        (self.func)(x, f)
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        let mut bounds = vec![self.bounds[1]; dims];
+        bounds[0] = self.bounds[0];
+        bounds
+    }
+
+    fn default_dims(&self) -> usize {
+        self.dims
+    }
+
+    fn pareto_front(&self, points: usize) -> Vec<Vec<f32>> {
+        (self.front)(points)
+    }
+
+    fn reference_point(&self) -> Vec<f32> {
+        vec![self.reference; self.objective_count]
+    }
+}
+
+/// `1 + 9 * mean(x[1..])`, the distance function of ZDT1-3.
+fn zdt_g(x: &[f32]) -> f32 {
+    1.0 + 9.0 * x[1..].iter().sum::<f32>() / (x.len() - 1) as f32
+}
+
+pub fn zdt1(x: &[f32], f: &mut [f32]) {
+    let g = zdt_g(x);
+    f[0] = x[0];
+    f[1] = g * (1.0 - (x[0] / g).sqrt());
+}
+
+pub fn zdt2(x: &[f32], f: &mut [f32]) {
+    let g = zdt_g(x);
+    f[0] = x[0];
+    f[1] = g * (1.0 - (x[0] / g).powi(2));
+}
+
+pub fn zdt3(x: &[f32], f: &mut [f32]) {
+    let g = zdt_g(x);
+    let r = x[0] / g;
+    f[0] = x[0];
+    f[1] = g * (1.0 - r.sqrt() - r * (10.0 * PI * x[0]).sin());
+}
+
+/// ZDT1 with a Rastrigin distance function: `21^9` local fronts.
+pub fn zdt4(x: &[f32], f: &mut [f32]) {
+    let g = 1.0
+        + 10.0 * (x.len() - 1) as f32
+        + x[1..].iter().map(|v| v * v - 10.0 * (4.0 * PI * v).cos()).sum::<f32>();
+    f[0] = x[0];
+    f[1] = g * (1.0 - (x[0] / g).sqrt());
+}
+
+/// Non-convex front with solutions thinning out towards it.
+pub fn zdt6(x: &[f32], f: &mut [f32]) {
+    let g = 1.0 + 9.0 * (x[1..].iter().sum::<f32>() / (x.len() - 1) as f32).powf(0.25);
+    f[0] = 1.0 - (-4.0 * x[0]).exp() * (6.0 * PI * x[0]).sin().powi(6);
+    f[1] = g * (1.0 - (f[0] / g).powi(2));
+}
+
+/// Distance function of DTLZ1 and DTLZ3: a Rastrigin-like sum over the
+/// last `n - m + 1` coordinates.
+fn dtlz_rastrigin_g(tail: &[f32]) -> f32 {
+    let terms: f32 = tail
+        .iter()
+        .map(|v| (v - 0.5).powi(2) - (20.0 * PI * (v - 0.5)).cos())
+        .sum();
+    100.0 * (tail.len() as f32 + terms)
+}
+
+fn dtlz_sphere_g(tail: &[f32]) -> f32 {
+    tail.iter().map(|v| (v - 0.5).powi(2)).sum()
+}
+
+/// Points `(1 + g) * (cos .. cos, cos .. sin, .., sin)` of the spherical
+/// front of DTLZ2-4, for position variables `angles` in `[0, 1]`.
+fn spherical(angles: &[f32], g: f32, f: &mut [f32]) {
+    let m = f.len();
+    for (j, fj) in f.iter_mut().enumerate() {
+        let mut value = 1.0 + g;
+        for a in &angles[..m - 1 - j] {
+            value *= (a * PI / 2.0).cos();
+        }
+        if j > 0 {
+            value *= (angles[m - 1 - j] * PI / 2.0).sin();
+        }
+        *fj = value;
+    }
+}
+
+/// Linear front `sum(f) = 0.5`.
+pub fn dtlz1(x: &[f32], f: &mut [f32]) {
+    let m = f.len();
+    let g = dtlz_rastrigin_g(&x[m - 1..]);
+    for (j, fj) in f.iter_mut().enumerate() {
+        let mut value = 0.5 * (1.0 + g);
+        for v in &x[..m - 1 - j] {
+            value *= v;
+        }
+        if j > 0 {
+            value *= 1.0 - x[m - 1 - j];
+        }
+        *fj = value;
+    }
+}
+
+/// Spherical front `sum(f^2) = 1`.
+pub fn dtlz2(x: &[f32], f: &mut [f32]) {
+    let m = f.len();
+    spherical(&x[..m - 1], dtlz_sphere_g(&x[m - 1..]), f);
+}
+
+/// DTLZ2's front behind DTLZ1's many local fronts.
+pub fn dtlz3(x: &[f32], f: &mut [f32]) {
+    let m = f.len();
+    spherical(&x[..m - 1], dtlz_rastrigin_g(&x[m - 1..]), f);
+}
+
+/// DTLZ2 with a density bias towards the edges of the front.
+pub fn dtlz4(x: &[f32], f: &mut [f32]) {
+    let m = f.len();
+    let angles: Vec<f32> = x[..m - 1].iter().map(|v| v.powi(100)).collect();
+    spherical(&angles, dtlz_sphere_g(&x[m - 1..]), f);
+}
+
+fn curve_front(points: usize, start: f32, shape: fn(f32) -> f32) -> Vec<Vec<f32>> {
+    (0..points)
+        .map(|i| start + (1.0 - start) * i as f32 / (points - 1).max(1) as f32)
+        .map(|f1| vec![f1, shape(f1)])
+        .collect()
+}
+
+fn convex_front(points: usize) -> Vec<Vec<f32>> {
+    curve_front(points, 0.0, |f1| 1.0 - f1.sqrt())
+}
+
+fn concave_front(points: usize) -> Vec<Vec<f32>> {
+    curve_front(points, 0.0, |f1| 1.0 - f1 * f1)
+}
+
+fn zdt3_front(points: usize) -> Vec<Vec<f32>> {
+    // The non-dominated part of a dense sample of the curve, thinned out
+    let curve = curve_front(20 * points, 0.0, |f1| 1.0 - f1.sqrt() - f1 * (10.0 * PI * f1).sin());
+    let front: Vec<Vec<f32>> = non_dominated(&curve).into_iter().map(|i| curve[i].clone()).collect();
+    let step = front.len().div_ceil(points).max(1);
+    front.into_iter().step_by(step).collect()
+}
+
+fn zdt6_front(points: usize) -> Vec<Vec<f32>> {
+    // f1 = 1 - exp(-4 x) sin^6(6 pi x) is smallest at x ~ 0.0808
+    curve_front(points, 0.280_775_3, |f1| 1.0 - f1 * f1)
+}
+
+fn linear_front(points: usize) -> Vec<Vec<f32>> {
+    simplex_lattice(3, lattice_divisions(3, points))
+        .into_iter()
+        .map(|w| w.iter().map(|v| 0.5 * v).collect())
+        .collect()
+}
+
+fn spherical_front(points: usize) -> Vec<Vec<f32>> {
+    simplex_lattice(3, lattice_divisions(3, points))
+        .into_iter()
+        .map(|w| {
+            let norm = w.iter().map(|v| v * v).sum::<f32>().sqrt();
+            w.iter().map(|v| v / norm).collect()
+        })
+        .collect()
+}
+
+const UNIT: [f32; 2] = [0.0, 1.0];
+
+pub static ZDT_TEST_FUNCTIONS: Lazy<BTreeMap<String, MultiObjectiveFunction>> = Lazy::new(|| {
+    let zdt = |func, dims, bounds, front| MultiObjectiveFunction {
+        func,
+        objective_count: 2,
+        dims,
+        bounds,
+        front,
+        reference: 1.1,
+    };
+    let mut m = BTreeMap::new();
+    m.insert("zdt1".to_string(), zdt(zdt1, 30, [UNIT, UNIT], convex_front));
+    m.insert("zdt2".to_string(), zdt(zdt2, 30, [UNIT, UNIT], concave_front));
+    m.insert("zdt3".to_string(), zdt(zdt3, 30, [UNIT, UNIT], zdt3_front));
+    m.insert("zdt4".to_string(), zdt(zdt4, 10, [UNIT, [-5.0, 5.0]], convex_front));
+    m.insert("zdt6".to_string(), zdt(zdt6, 10, [UNIT, UNIT], zdt6_front));
+    m
+});
+
+/// Three objectives; `dims` has 5 (DTLZ1) or 10 distance variables.
+pub static DTLZ_TEST_FUNCTIONS: Lazy<BTreeMap<String, MultiObjectiveFunction>> = Lazy::new(|| {
+    let dtlz = |func, dims, front, reference| MultiObjectiveFunction {
+        func,
+        objective_count: 3,
+        dims,
+        bounds: [UNIT, UNIT],
+        front,
+        reference,
+    };
+    let mut m = BTreeMap::new();
+    m.insert("dtlz1".to_string(), dtlz(dtlz1, 7, linear_front, 0.55));
+    m.insert("dtlz2".to_string(), dtlz(dtlz2, 12, spherical_front, 1.1));
+    m.insert("dtlz3".to_string(), dtlz(dtlz3, 12, spherical_front, 1.1));
+    m.insert("dtlz4".to_string(), dtlz(dtlz4, 12, spherical_front, 1.1));
+    m
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A point on the front: position variables `head`, optimal distance variables.
+    fn optimal_point(problem: &MultiObjectiveFunction, head: &[f32]) -> Vec<f32> {
+        let dims = problem.default_dims();
+        // ZDT distance variables are optimal at 0, DTLZ ones at 0.5
+        let tail = if problem.objective_count == 2 { 0.0 } else { 0.5 };
+        let mut x = vec![tail; dims];
+        x[..head.len()].copy_from_slice(head);
+        x
+    }
+
+    #[test]
+    fn test_optimal_points_lie_on_the_front() {
+        let all = ZDT_TEST_FUNCTIONS.iter().chain(DTLZ_TEST_FUNCTIONS.iter());
+        for (name, problem) in all {
+            let front = problem.pareto_front(100);
+            assert!(front.len() >= 20, "{name}: {} front points", front.len());
+            let m = problem.objective_count;
+            let reference = problem.reference_point();
+            assert!(front.iter().all(|p| p.iter().zip(&reference).all(|(v, r)| v < r)), "{name}");
+            // First coordinates inside the disconnected pieces of the ZDT3 front
+            for head in [[0.0, 0.0], [0.2, 0.5], [0.42, 0.9], [0.63, 0.3]] {
+                let x = optimal_point(problem, &head[..m - 1]);
+                let mut f = vec![0.0; m];
+                problem.evaluate(&x, &mut f);
+                // Dominated by no front point, up to the sampling of the front
+                let nearest = front
+                    .iter()
+                    .map(|p| p.iter().zip(&f).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt())
+                    .fold(f32::INFINITY, f32::min);
+                let on_front = front.iter().all(|p| !p.iter().zip(&f).all(|(a, b)| *a < b - 1e-3));
+                assert!(on_front, "{name} {head:?}: {f:?} dominated, nearest at {nearest}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        let mut f = [0.0; 2];
+        zdt1(&[0.25, 0.0, 0.0], &mut f);
+        assert_eq!(f, [0.25, 0.5]);
+        zdt1(&[0.0, 1.0, 1.0], &mut f);
+        assert_eq!(f, [0.0, 10.0]);
+        let mut f = [0.0; 3];
+        dtlz2(&[0.0, 0.0, 0.5, 0.5], &mut f);
+        assert_eq!(f, [1.0, 0.0, 0.0]);
+        dtlz1(&[0.5, 0.5, 0.5, 0.5], &mut f);
+        assert_eq!(f, [0.125, 0.125, 0.25]);
+    }
+}
@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use rand_distr::{Distribution, StandardNormal, Uniform};
+use simd_vector::Vec8;
+use simd_vector::fast::FastMath;
+
+use super::{
+    bbob::{shuffle, Rotation},
+    Objective, Problem, TestFunction, LMMAES_TEST_FUNCTIONS,
+};
+use crate::{
+    rng::{stream_rng, DefaultRng},
+    utils::{pack_pair_layout, unpack_pair_layout},
+};
+
+/// How the coordinates are mixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationKind {
+    Identity,
+    /// Haar-random orthogonal matrix: `O(D^2)` per evaluation and `O(D^3)` to
+    /// generate, fine up to a few hundred dims.
+    Dense,
+    /// Random permutation followed by independent dense rotations of `block`
+    /// consecutive coordinates (a multiple of 8): `O(D * block)`.
+    BlockDiagonal { block: usize },
+    /// Product of `reflections` random Householder reflections `I - 2 v v^T`:
+    /// `O(D * reflections)`, and every coordinate is mixed with every other.
+    Householder { reflections: usize },
+}
+
+/// A seeded change of coordinates for a `TestFunction`.
+///
+/// In coordinates normalised to `[-1, 1]` per dim, the base function is
+/// evaluated at `z = Lambda R (u - s)`, so its optimum `u*` moves to
+/// `s + R^T Lambda^-1 u*`. `s` is uniform in `[-shift, shift]^D` and
+/// `Lambda = diag(condition^(0.5 i / (D - 1)))` raises the condition number of
+/// the Hessian by `condition` along rotated axes. Since `Lambda >= 1` the
+/// moved optimum stays in bounds for functions centred in their domain; a
+/// rotation can move an off-centre optimum out of the box, and corners of the
+/// box map outside the base function's bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub rotation: RotationKind,
+    pub shift: f32,
+    pub condition: f32,
+    pub seed: u64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        rotation: RotationKind::Identity,
+        shift: 0.0,
+        condition: 1.0,
+        seed: 0,
+    };
+
+    pub fn instantiate(&self, bounds: &[[f32; 2]]) -> TransformInstance {
+        TransformInstance::new(self, bounds)
+    }
+}
+
+enum Orthogonal {
+    Identity,
+    Dense(Rotation),
+    BlockDiagonal {
+        permutation: Vec<usize>,
+        block: usize,
+        blocks: Vec<Rotation>,
+    },
+    Householder(Vec<Vec<Vec8>>),
+}
+
+impl Orthogonal {
+    fn random(kind: RotationKind, dims: usize, rng: &mut DefaultRng) -> Self {
+        match kind {
+            RotationKind::Identity => Orthogonal::Identity,
+            RotationKind::Dense => Orthogonal::Dense(Rotation::random(dims, rng)),
+            RotationKind::BlockDiagonal { block } => {
+                assert!(
+                    block.is_multiple_of(8) && dims.is_multiple_of(block),
+                    "block must be a multiple of 8 that divides the dimension"
+                );
+                let mut permutation: Vec<usize> = (0..dims).collect();
+                shuffle(&mut permutation, rng);
+                let blocks = (0..dims / block).map(|_| Rotation::random(block, rng)).collect();
+                Orthogonal::BlockDiagonal {
+                    permutation,
+                    block,
+                    blocks,
+                }
+            }
+            RotationKind::Householder { reflections } => {
+                let vectors = (0..reflections)
+                    .map(|_| {
+                        let v: Vec<f32> = (0..dims).map(|_| StandardNormal.sample(rng)).collect();
+                        let norm = v.iter().map(|a| a * a).sum::<f32>().sqrt();
+                        v.chunks_exact(8)
+                            .map(|c| Vec8::from([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]) / norm)
+                            .collect()
+                    })
+                    .collect();
+                Orthogonal::Householder(vectors)
+            }
+        }
+    }
+
+    fn apply(&self, x: &mut [f32]) {
+        match self {
+            Orthogonal::Identity => {}
+            Orthogonal::Dense(rotation) => {
+                let input = x.to_vec();
+                rotation.apply(&input, x);
+            }
+            Orthogonal::BlockDiagonal {
+                permutation,
+                block,
+                blocks,
+            } => {
+                let permuted: Vec<f32> = permutation.iter().map(|i| x[*i]).collect();
+                for ((input, out), rotation) in permuted
+                    .chunks_exact(*block)
+                    .zip(x.chunks_exact_mut(*block))
+                    .zip(blocks)
+                {
+                    rotation.apply(input, out);
+                }
+            }
+            Orthogonal::Householder(vectors) => reflect(x, vectors.iter()),
+        }
+    }
+
+    /// Inverse of `apply`.
+    fn apply_transposed(&self, x: &mut [f32]) {
+        match self {
+            Orthogonal::Identity => {}
+            Orthogonal::Dense(rotation) => {
+                let input = x.to_vec();
+                rotation.apply_transposed(&input, x);
+            }
+            Orthogonal::BlockDiagonal {
+                permutation,
+                block,
+                blocks,
+            } => {
+                let mut permuted = vec![0.0; x.len()];
+                for ((input, out), rotation) in x
+                    .chunks_exact(*block)
+                    .zip(permuted.chunks_exact_mut(*block))
+                    .zip(blocks)
+                {
+                    rotation.apply_transposed(input, out);
+                }
+                for (i, v) in permutation.iter().zip(permuted) {
+                    x[*i] = v;
+                }
+            }
+            Orthogonal::Householder(vectors) => reflect(x, vectors.iter().rev()),
+        }
+    }
+}
+
+/// Apply the Householder reflections `I - 2 v v^T` in iteration order.
+fn reflect<'a>(x: &mut [f32], vectors: impl Iterator<Item = &'a Vec<Vec8>>) {
+    let mut packed: Vec<Vec8> = x
+        .chunks_exact(8)
+        .map(|c| Vec8::from([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+        .collect();
+    for v in vectors {
+        let dot = v
+            .iter()
+            .zip(&packed)
+            .fold(Vec8::ZERO, |acc, (a, b)| a.mul_add(*b, acc))
+            .sum();
+        let scale = Vec8::splat(-2.0 * dot);
+        for (p, a) in packed.iter_mut().zip(v) {
+            *p = a.mul_add(scale, *p);
+        }
+    }
+    for (out, p) in x.chunks_exact_mut(8).zip(&packed) {
+        out.copy_from_slice(&p.0);
+    }
+}
+
+/// A `Transform` with its random data drawn for one set of bounds.
+pub struct TransformInstance {
+    center: Vec<f32>,
+    half_width: Vec<f32>,
+    shift: Vec<f32>,
+    rotation: Orthogonal,
+    scaling: Vec<f32>,
+}
+
+impl TransformInstance {
+    pub fn new(transform: &Transform, bounds: &[[f32; 2]]) -> Self {
+        let dims = bounds.len();
+        let mut rng: DefaultRng = stream_rng(transform.seed, dims as u64);
+        let shift = if transform.shift > 0.0 {
+            let uniform = Uniform::new_inclusive(-transform.shift, transform.shift).unwrap();
+            (0..dims).map(|_| uniform.sample(&mut rng)).collect()
+        } else {
+            vec![0.0; dims]
+        };
+        let rotation = Orthogonal::random(transform.rotation, dims, &mut rng);
+        let scaling = (0..dims)
+            .map(|i| transform.condition.powf(0.5 * i as f32 / (dims - 1).max(1) as f32))
+            .collect();
+        Self {
+            center: bounds.iter().map(|b| 0.5 * (b[0] + b[1])).collect(),
+            half_width: bounds.iter().map(|b| 0.5 * (b[1] - b[0])).collect(),
+            shift,
+            rotation,
+            scaling,
+        }
+    }
+
+    /// Point at which the base function is evaluated for `x` (dimension order).
+    pub fn to_base(&self, x: &[f32]) -> Vec<f32> {
+        let mut u: Vec<f32> = x
+            .iter()
+            .zip(&self.center)
+            .zip(&self.half_width)
+            .zip(&self.shift)
+            .map(|(((v, c), h), s)| (v - c) / h - s)
+            .collect();
+        self.rotation.apply(&mut u);
+        u.iter()
+            .zip(&self.scaling)
+            .zip(&self.center)
+            .zip(&self.half_width)
+            .map(|(((z, l), c), h)| c + h * l * z)
+            .collect()
+    }
+
+    /// Inverse of `to_base`: the point whose base coordinates are `y`.
+    pub fn from_base(&self, y: &[f32]) -> Vec<f32> {
+        let mut z: Vec<f32> = y
+            .iter()
+            .zip(&self.scaling)
+            .zip(&self.center)
+            .zip(&self.half_width)
+            .map(|(((v, l), c), h)| (v - c) / (h * l))
+            .collect();
+        self.rotation.apply_transposed(&mut z);
+        z.iter()
+            .zip(&self.shift)
+            .zip(&self.center)
+            .zip(&self.half_width)
+            .map(|(((u, s), c), h)| c + h * (u + s))
+            .collect()
+    }
+
+    /// Where the base function's centre `u = 0` ends up (the new optimum for
+    /// functions whose optimum is at the centre of their bounds).
+    pub fn shifted_center(&self) -> Vec<f32> {
+        self.center
+            .iter()
+            .zip(&self.half_width)
+            .zip(&self.shift)
+            .map(|((c, h), s)| c + h * s)
+            .collect()
+    }
+}
+
+/// A `TestFunction` seen through a `Transform`.
+#[derive(Clone)]
+pub struct TransformedFunction {
+    pub function: TestFunction,
+    pub transform: Transform,
+}
+
+impl Problem for TransformedFunction {
+    fn objective(&self, dims: usize) -> Objective<'_> {
+        let instance = self.transform.instantiate(&self.function.bounds(dims));
+        let func = self.function.func;
+        Box::new(move |x: &[Vec8]| {
+            let mut v = vec![0.0; x.len() * 8];
+            unpack_pair_layout(x, &mut v);
+            func.evaluate(&pack_pair_layout(&instance.to_base(&v)))
+        })
+    }
+
+    fn bounds(&self, dims: usize) -> Vec<[f32; 2]> {
+        self.function.bounds(dims)
+    }
+
+    fn f_opt(&self, dims: usize) -> f32 {
+        self.function.f_opt(dims)
+    }
+
+    fn x_opt(&self, dims: usize) -> Option<Vec<f32>> {
+        let x_opt = self.function.x_opt(dims)?;
+        Some(self.transform.instantiate(&self.function.bounds(dims)).from_base(&x_opt))
+    }
+
+    fn target(&self, dims: usize) -> f32 {
+        self.function.target(dims)
+    }
+}
+
+/// Apply the same transform to every function of a test set.
+pub fn transformed_test_functions(
+    functions: &BTreeMap<String, TestFunction>,
+    transform: Transform,
+) -> BTreeMap<String, TransformedFunction> {
+    functions
+        .iter()
+        .map(|(name, function)| {
+            (
+                name.clone(),
+                TransformedFunction {
+                    function: function.clone(),
+                    transform,
+                },
+            )
+        })
+        .collect()
+}
+
+/// LMMAES functions under a dense rotation and a shift, for rotation-invariance checks.
+pub static LMMAES_ROTATED_TEST_FUNCTIONS: Lazy<BTreeMap<String, TransformedFunction>> =
+    Lazy::new(|| {
+        transformed_test_functions(
+            &LMMAES_TEST_FUNCTIONS,
+            Transform {
+                rotation: RotationKind::Dense,
+                shift: 0.2,
+                condition: 1.0,
+                seed: 0,
+            },
+        )
+    });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::{sphere, TestFn, SPHERE_BOUNDS, SPHERE_OPTIMUM};
+    use crate::utils::fit_in_bounds_simd;
+
+    const KINDS: [RotationKind; 4] = [
+        RotationKind::Identity,
+        RotationKind::Dense,
+        RotationKind::BlockDiagonal { block: 16 },
+        RotationKind::Householder { reflections: 4 },
+    ];
+
+    fn sphere_function() -> TestFunction {
+        TestFunction {
+            func: TestFn::Pairwise(sphere),
+            bounds: SPHERE_BOUNDS,
+            optimum: SPHERE_OPTIMUM,
+        }
+    }
+
+    fn pack(x: &[f32]) -> Vec<Vec8> {
+        fit_in_bounds_simd(x, &vec![0.0; x.len()], &vec![1.0; x.len()])
+    }
+
+    #[test]
+    fn test_rotations_preserve_norm() {
+        let mut rng: DefaultRng = stream_rng(3, 0);
+        for kind in KINDS {
+            let rotation = Orthogonal::random(kind, 64, &mut rng);
+            let x: Vec<f32> = (0..64).map(|i| (i as f32 * 0.37).sin()).collect();
+            let mut y = x.clone();
+            rotation.apply(&mut y);
+            let norm_x: f32 = x.iter().map(|v| v * v).sum();
+            let norm_y: f32 = y.iter().map(|v| v * v).sum();
+            assert!((norm_x - norm_y).abs() < 1e-3 * norm_x, "{:?}", kind);
+            if kind != RotationKind::Identity {
+                assert_ne!(x, y, "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity_transform_is_the_base_function() {
+        let function = &LMMAES_TEST_FUNCTIONS["rosenbrock"];
+        let transformed = TransformedFunction {
+            function: function.clone(),
+            transform: Transform::IDENTITY,
+        };
+        let x: Vec<f32> = (0..32).map(|i| (i as f32 * 0.7).cos() * 4.0).collect();
+        let base = function.func.evaluate(&pack(&x));
+        let value = transformed.objective(32)(&pack(&x));
+        assert!((base - value).abs() <= 1e-6 * base.abs().max(1.0));
+    }
+
+    #[test]
+    fn test_centered_optimum_moves_to_shifted_center() {
+        for kind in KINDS {
+            let transform = Transform {
+                rotation: kind,
+                shift: 0.5,
+                condition: 1e4,
+                seed: 11,
+            };
+            let transformed = TransformedFunction {
+                function: sphere_function(),
+                transform,
+            };
+            let bounds = transformed.bounds(32);
+            let optimum = transform.instantiate(&bounds).shifted_center();
+            assert!(optimum.iter().zip(&bounds).all(|(v, b)| b[0] <= *v && *v <= b[1]));
+            let objective = transformed.objective(32);
+            assert!(objective(&pack(&optimum)) < 1e-9, "{:?}", kind);
+            let mut off = optimum.clone();
+            off[5] += 0.1;
+            assert!(objective(&pack(&off)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_from_base_inverts_to_base() {
+        let bounds = vec![[-5.0, 3.0]; 32];
+        let y: Vec<f32> = (0..32).map(|i| (i as f32 * 0.61).cos() * 2.0).collect();
+        for kind in KINDS {
+            let transform = Transform {
+                rotation: kind,
+                shift: 0.3,
+                condition: 100.0,
+                seed: 4,
+            };
+            let instance = transform.instantiate(&bounds);
+            let back = instance.to_base(&instance.from_base(&y));
+            for (a, b) in back.iter().zip(&y) {
+                assert!((a - b).abs() < 1e-4, "{:?}: {} vs {}", kind, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_couples_pairs() {
+        let transformed = TransformedFunction {
+            function: sphere_function(),
+            transform: Transform {
+                rotation: RotationKind::Householder { reflections: 2 },
+                shift: 0.0,
+                condition: 100.0,
+                seed: 1,
+            },
+        };
+        let objective = transformed.objective(16);
+        let x = vec![0.5; 16];
+        let moved = |d1: f32, d2: f32| {
+            let mut y = x.clone();
+            y[1] += d1;
+            y[2] += d2;
+            objective(&pack(&y))
+        };
+        let mixed = moved(1.0, 1.0) - moved(1.0, 0.0) - moved(0.0, 1.0) + moved(0.0, 0.0);
+        assert!(mixed.abs() > 1e-6, "mixed difference {}", mixed);
+    }
+
+    #[test]
+    fn test_transform_is_deterministic_per_seed() {
+        let bounds = vec![[-5.0, 5.0]; 16];
+        let transform = Transform {
+            rotation: RotationKind::Householder { reflections: 3 },
+            shift: 0.3,
+            condition: 10.0,
+            seed: 5,
+        };
+        let x = vec![1.0; 16];
+        let a = transform.instantiate(&bounds).to_base(&x);
+        let b = transform.instantiate(&bounds).to_base(&x);
+        let c = Transform { seed: 6, ..transform }.instantiate(&bounds).to_base(&x);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
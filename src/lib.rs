@@ -1,9 +1,26 @@
 pub mod algorithms;
+pub mod ask_tell;
 pub mod benchmark_params;
+pub mod capi;
 pub mod default_algorithms_params;
 pub mod early_stop_callback;
+pub mod evaluator;
+pub mod experiment;
+pub mod external;
 pub mod functions;
+pub mod json;
+pub mod metadata;
+pub mod noise;
 pub mod optimizer;
+pub mod pareto;
 pub mod plot;
+pub mod profiles;
+pub mod report;
+pub mod restart;
+pub mod rng;
 pub mod runner;
+pub mod scheduler;
+pub mod shard;
+pub mod stats;
+pub mod trace;
 pub mod utils;
@@ -0,0 +1,119 @@
+//! Provenance of result files. Next to every CSV it writes, a binary stores
+//! `<name>.meta.json`: what was run (parameters, suite, dimensions, budget,
+//! threshold, seeds) and how (crate version, git commit, CPU, threads,
+//! start and end time, wall-clock seconds).
+
+use std::{
+    fs,
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::json::Json;
+
+/// Commit the crate was built from, when built from a git checkout.
+pub const GIT_COMMIT: Option<&str> = option_env!("OPTIMIZERS_GIT_COMMIT");
+
+/// When a run started, for the timing fields of its metadata.
+pub struct RunClock {
+    start: SystemTime,
+    instant: Instant,
+}
+
+impl RunClock {
+    pub fn start() -> Self {
+        Self {
+            start: SystemTime::now(),
+            instant: Instant::now(),
+        }
+    }
+
+    /// Build, machine and timing fields, the run ending now.
+    pub fn provenance(&self) -> Vec<(&'static str, Json)> {
+        let cpu_count = std::thread::available_parallelism().map_or(1, |n| n.get());
+        vec![
+            ("crate_version", Json::from(env!("CARGO_PKG_VERSION"))),
+            ("git_commit", GIT_COMMIT.map_or(Json::Null, Json::from)),
+            ("cpu", Json::from(cpu_model())),
+            ("cpu_count", Json::from(cpu_count)),
+            ("threads", Json::from(rayon::current_num_threads())),
+            ("os", Json::from(format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH))),
+            ("start_time", Json::from(iso8601(self.start))),
+            ("end_time", Json::from(iso8601(SystemTime::now()))),
+            ("wall_clock_seconds", Json::from(self.instant.elapsed().as_secs_f64())),
+        ]
+    }
+}
+
+/// `benchmark_results.csv` has its metadata in `benchmark_results.meta.json`.
+pub fn metadata_path(path: &str) -> String {
+    Path::new(path).with_extension("meta.json").to_string_lossy().into_owned()
+}
+
+/// Writes `fields` and the provenance of `clock` next to `path`; returns
+/// where.
+pub fn write_metadata(path: &str, fields: Vec<(&str, Json)>, clock: &RunClock) -> Result<String, String> {
+    let meta_path = metadata_path(path);
+    let record = Json::object(fields.into_iter().chain(clock.provenance()));
+    fs::write(&meta_path, format!("{record}\n")).map_err(|e| format!("{meta_path}: {e}"))?;
+    Ok(meta_path)
+}
+
+/// Model name of the first CPU on Linux, else the architecture.
+fn cpu_model() -> String {
+    fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string())
+}
+
+/// UTC time as `YYYY-MM-DDThh:mm:ssZ`.
+pub fn iso8601(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, rest) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // Civil date from days since 1970-01-01 (Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z", rest / 3600, rest % 3600 / 60, rest % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(iso8601(UNIX_EPOCH + Duration::from_secs(1_792_454_399)), "2026-10-19T23:59:59Z");
+    }
+
+    #[test]
+    fn test_write_metadata() {
+        let dir = std::env::temp_dir().join(format!("metadata_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let results = dir.join("results.csv").to_string_lossy().into_owned();
+        let clock = RunClock::start();
+        let path = write_metadata(&results, vec![("seeds", Json::from(3u64))], &clock).unwrap();
+        assert!(path.ends_with("results.meta.json"));
+        let record = Json::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(record.get("seeds").and_then(Json::as_u64), Some(3));
+        assert_eq!(record.get("crate_version").and_then(Json::as_str), Some(env!("CARGO_PKG_VERSION")));
+        assert!(record.get("wall_clock_seconds").and_then(Json::as_f64).is_some_and(|s| s >= 0.0));
+        assert!(record.get("threads").and_then(Json::as_u64).is_some_and(|t| t >= 1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    f32::consts::PI,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use simd_vector::Vec8;
+
+use crate::{functions::TestFunction, rng::stream_seed};
+
+/// Noise models applied to the objective value, after the BBOB noisy suite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Noise {
+    /// `f + sigma * N(0, 1)`.
+    Gaussian { sigma: f32 },
+    /// `f * exp(beta * N(0, 1))`: the noise scales with `f`, so it vanishes at a
+    /// zero optimum and the sign of `f` is preserved.
+    Multiplicative { beta: f32 },
+    /// `f + scale * C(0, 1)` with probability `p`, otherwise `f`.
+    CauchyOutliers { p: f32, scale: f32 },
+}
+
+/// Counter-based noise source: draw `i` depends only on `(seed, i)`, so a
+/// sequential run is reproducible and the objective stays `Sync`.
+struct NoiseSource {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl NoiseSource {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Two independent uniforms in (0, 1].
+    fn uniforms(&self) -> (f32, f32) {
+        let i = self.counter.fetch_add(1, Ordering::Relaxed);
+        let bits = stream_seed(self.seed, i + 1);
+        let to_unit = |b: u64| ((b & 0xff_ffff) + 1) as f32 / (1 << 24) as f32;
+        (to_unit(bits), to_unit(bits >> 32))
+    }
+
+    /// Standard normal via Box-Muller.
+    fn normal(&self) -> f32 {
+        let (u1, u2) = self.uniforms();
+        f32::sqrt(-2.0 * f32::ln(u1)) * f32::cos(2.0 * PI * u2)
+    }
+}
+
+impl Noise {
+    /// Wrap an objective; every call draws fresh noise from the `seed` stream.
+    pub fn wrap<F>(self, func: F, seed: u64) -> impl Fn(&[Vec8]) -> f32 + Sync
+    where
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let source = NoiseSource::new(seed);
+        move |x: &[Vec8]| self.apply(func(x), &source)
+    }
+
+    fn apply(&self, f: f32, source: &NoiseSource) -> f32 {
+        match *self {
+            Noise::Gaussian { sigma } => f + sigma * source.normal(),
+            Noise::Multiplicative { beta } => f * f32::exp(beta * source.normal()),
+            Noise::CauchyOutliers { p, scale } => {
+                let (u1, u2) = source.uniforms();
+                if u1 <= p {
+                    f + scale * f32::tan(PI * (u2 - 0.5))
+                } else {
+                    f
+                }
+            }
+        }
+    }
+}
+
+/// A `TestFunction` with a noise model, for benchmarking noisy-objective handling.
+#[derive(Clone)]
+pub struct NoisyFunction {
+    pub function: TestFunction,
+    pub noise: Noise,
+}
+
+impl NoisyFunction {
+    /// Noisy objective for one run; use a distinct `seed` per run.
+    pub fn objective(&self, seed: u64) -> impl Fn(&[Vec8]) -> f32 + Sync {
+        let func = self.function.func;
+        self.noise.wrap(move |x: &[Vec8]| func.evaluate(x), seed)
+    }
+
+    /// The noise-free objective, for judging the true quality of a result.
+    pub fn exact(&self) -> impl Fn(&[Vec8]) -> f32 + Sync {
+        let func = self.function.func;
+        move |x: &[Vec8]| func.evaluate(x)
+    }
+}
+
+/// Wrap every function of a test set with the same noise model.
+pub fn noisy_test_functions(
+    functions: &BTreeMap<String, TestFunction>,
+    noise: Noise,
+) -> BTreeMap<String, NoisyFunction> {
+    functions
+        .iter()
+        .map(|(name, function)| {
+            (
+                name.clone(),
+                NoisyFunction {
+                    function: function.clone(),
+                    noise,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::MINI_TEST_FUNCTIONS;
+
+    fn constant(value: f32) -> impl Fn(&[Vec8]) -> f32 + Sync {
+        move |_x: &[Vec8]| value
+    }
+
+    fn samples<F: Fn(&[Vec8]) -> f32>(func: F, n: usize) -> Vec<f32> {
+        (0..n).map(|_| func(&[])).collect()
+    }
+
+    #[test]
+    fn test_gaussian_noise_moments() {
+        let values = samples(Noise::Gaussian { sigma: 0.5 }.wrap(constant(2.0), 1), 20_000);
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let var = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+        assert!((mean - 2.0).abs() < 0.02, "mean={}", mean);
+        assert!((var.sqrt() - 0.5).abs() < 0.02, "std={}", var.sqrt());
+    }
+
+    #[test]
+    fn test_multiplicative_noise_keeps_sign_and_zero() {
+        let noise = Noise::Multiplicative { beta: 1.0 };
+        assert!(samples(noise.wrap(constant(3.0), 2), 1000).iter().all(|v| *v > 0.0));
+        assert!(samples(noise.wrap(constant(0.0), 2), 1000).iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_cauchy_outlier_rate() {
+        let noise = Noise::CauchyOutliers { p: 0.1, scale: 1.0 };
+        let values = samples(noise.wrap(constant(1.0), 3), 20_000);
+        let outliers = values.iter().filter(|v| **v != 1.0).count() as f32 / values.len() as f32;
+        assert!((outliers - 0.1).abs() < 0.01, "rate={}", outliers);
+    }
+
+    #[test]
+    fn test_noise_is_reproducible_per_seed() {
+        let noise = Noise::Gaussian { sigma: 1.0 };
+        let a = samples(noise.wrap(constant(0.0), 4), 10);
+        let b = samples(noise.wrap(constant(0.0), 4), 10);
+        let c = samples(noise.wrap(constant(0.0), 5), 10);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_noisy_test_functions() {
+        let noisy = noisy_test_functions(&MINI_TEST_FUNCTIONS, Noise::Gaussian { sigma: 0.1 });
+        assert_eq!(noisy.len(), MINI_TEST_FUNCTIONS.len());
+        let sphere = &noisy["shifted_sphere"];
+        let x = vec![Vec8::splat(-PI); 2];
+        assert_eq!(sphere.exact()(&x), 0.0);
+        assert_ne!(sphere.objective(0)(&x), 0.0);
+    }
+}
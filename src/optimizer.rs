@@ -1,6 +1,12 @@
+use rand::{Rng, SeedableRng};
 use simd_vector::Vec8;
 
-use crate::early_stop_callback::EarlyStopCallback;
+use crate::{
+    early_stop_callback::EarlyStopCallback,
+    evaluator::{external_search, fallible_search, noisy_search, PopulationEvaluator, RESAMPLE_STREAM},
+    external::{ExternalError, ExternalObjective},
+    rng::{stream_rng, DefaultRng},
+};
 
 #[derive(Debug)]
 pub struct OptimizationHistory {
@@ -16,7 +22,79 @@ pub struct OptimizerResult {
     pub history: Option<OptimizationHistory>,
 }
 
+/// What a population evaluator does when the objective fails (`Err` or NaN).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailurePolicy {
+    /// Score the candidate as `f32::INFINITY`.
+    Worst,
+    /// Re-evaluate the same point up to `attempts` more times, then score it as worst.
+    Retry { attempts: u32 },
+    /// Replace the candidate with a uniform random point up to `attempts`
+    /// times, then score it as worst. The optimizer sees the replaced point.
+    Resample { attempts: u32 },
+    /// Stop the run and report the error; the best point found so far is kept.
+    Abort,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ObjectiveError<E> {
+    Failed(E),
+    NaN,
+}
+
+#[derive(Debug)]
+pub struct FallibleResult<E> {
+    /// `nfev` counts every objective call, including retries and resamples.
+    pub result: OptimizerResult,
+    /// Objective calls that returned `Err` or NaN.
+    pub failures: u64,
+    /// Set when `FailurePolicy::Abort` stopped the run.
+    pub error: Option<ObjectiveError<E>>,
+}
+
+/// How a population evaluator re-samples a noisy objective.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resampling {
+    /// Average `samples` evaluations of every candidate.
+    Fixed { samples: u32 },
+    /// Evaluate every candidate once, then re-evaluate the best
+    /// `elite_fraction` of each population until they have `max_samples`.
+    AdaptiveElite { elite_fraction: f32, max_samples: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseHandling {
+    pub resampling: Resampling,
+    /// Evaluations of the final best point averaged into `NoisyResult::mean_f_x`.
+    pub final_samples: u32,
+}
+
+#[derive(Debug)]
+pub struct NoisyResult {
+    /// `f_x` is the optimizer's own (optimistically biased) estimate; `nfev`
+    /// counts every objective call made during the search.
+    pub result: OptimizerResult,
+    /// Mean of `final_samples` fresh evaluations of `result.x`.
+    pub mean_f_x: f32,
+    /// Standard error of `mean_f_x`; infinite for a single sample.
+    pub std_error: f32,
+}
+
 pub trait Optimizer {
+    /// Run with a caller-supplied RNG; see `rng::stream_rng` for seeding.
+    fn find_infimum_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        early_stop_callback: &EarlyStopCallback<&F>,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync;
+
     fn find_infimum<F>(
         &self,
         func: &F,
@@ -27,5 +105,274 @@ pub trait Optimizer {
         early_stop_callback: &EarlyStopCallback<&F>,
     ) -> OptimizerResult
     where
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let mut rng = DefaultRng::seed_from_u64(seed);
+        self.find_infimum_with_rng(func, bounds, maxiter, &mut rng, use_history, early_stop_callback)
+    }
+}
+
+/// Optimizers that evaluate eight candidates per objective call using the
+/// candidate-per-lane layout of `broadcast_simd_batch`.
+pub trait BatchOptimizer {
+    fn find_infimum_batch_with_rng<R, F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Vec8 + Sync;
+
+    fn find_infimum_batch<F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        F: Fn(&[Vec8]) -> Vec8 + Sync,
+    {
+        let mut rng = DefaultRng::seed_from_u64(seed);
+        self.find_infimum_batch_with_rng(func, bounds, maxiter, &mut rng, use_history, stop_residual)
+    }
+}
+
+/// Optimizers that accept objectives returning `Result<f32, E>`. NaN values
+/// are treated as failures and handled by `policy` as well.
+pub trait FallibleOptimizer {
+    /// Run with a caller-supplied RNG. `FailurePolicy::Resample` draws from a
+    /// `DefaultRng` forked from `rng` before the run.
+    #[allow(clippy::too_many_arguments)]
+    fn find_infimum_fallible_with_rng<F, E, R>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+        policy: FailurePolicy,
+    ) -> FallibleResult<E>
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Result<f32, E> + Sync;
+
+    /// Seeded on stream 0 of `seed`; `FailurePolicy::Resample` draws from its
+    /// own stream, so resampling never shifts the optimizer's sequence.
+    #[allow(clippy::too_many_arguments)]
+    fn find_infimum_fallible<F, E>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+        policy: FailurePolicy,
+    ) -> FallibleResult<E>
+    where
+        F: Fn(&[Vec8]) -> Result<f32, E> + Sync;
+}
+
+/// Every population-based optimizer runs fallible objectives through a
+/// `FallibleEvaluator`.
+impl<T: PopulationOptimizer> FallibleOptimizer for T {
+    fn find_infimum_fallible_with_rng<F, E, R>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+        policy: FailurePolicy,
+    ) -> FallibleResult<E>
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> Result<f32, E> + Sync,
+    {
+        let resample_rng = DefaultRng::from_rng(rng);
+        fallible_search(func, bounds, rng, resample_rng, policy, |evaluator, rng| {
+            self.find_infimum_population(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+        })
+    }
+
+    fn find_infimum_fallible<F, E>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+        policy: FailurePolicy,
+    ) -> FallibleResult<E>
+    where
+        F: Fn(&[Vec8]) -> Result<f32, E> + Sync,
+    {
+        let mut rng: DefaultRng = stream_rng(seed, 0);
+        fallible_search(func, bounds, &mut rng, stream_rng(seed, RESAMPLE_STREAM), policy, |evaluator, rng| {
+            self.find_infimum_population(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+        })
+    }
+}
+
+/// Population-based optimizers that can run against any `PopulationEvaluator`,
+/// e.g. one fed from outside the process (see `ask_tell`). Positions handed to
+/// the evaluator are in the unit cube; the result is in `bounds`.
+pub trait PopulationOptimizer {
+    fn find_infimum_population<E, R>(
+        &self,
+        evaluator: &mut E,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> OptimizerResult
+    where
+        E: PopulationEvaluator,
+        R: Rng;
+}
+
+/// Optimizers that can evaluate whole populations in an external program.
+/// `nfev` counts candidates sent, re-sent batches included; `failures` counts
+/// candidates the program answered with NaN.
+pub trait ExternalOptimizer {
+    fn find_infimum_external(
+        &self,
+        objective: &mut ExternalObjective,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> FallibleResult<ExternalError>;
+}
+
+/// Every population-based optimizer runs against an external program through
+/// an `ExternalEvaluator`.
+impl<T: PopulationOptimizer> ExternalOptimizer for T {
+    fn find_infimum_external(
+        &self,
+        objective: &mut ExternalObjective,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+    ) -> FallibleResult<ExternalError> {
+        external_search(objective, bounds, seed, |evaluator, rng| {
+            self.find_infimum_population(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+        })
+    }
+}
+
+/// Optimizers that handle noisy objectives by re-sampling. `maxiter` bounds the
+/// number of objective calls, re-evaluations included.
+pub trait NoisyOptimizer {
+    /// Run with a caller-supplied RNG; see `rng::stream_rng` for seeding.
+    #[allow(clippy::too_many_arguments)]
+    fn find_infimum_noisy_with_rng<F, R>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+        handling: NoiseHandling,
+    ) -> NoisyResult
+    where
+        R: Rng,
         F: Fn(&[Vec8]) -> f32 + Sync;
+
+    #[allow(clippy::too_many_arguments)]
+    fn find_infimum_noisy<F>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+        use_history: bool,
+        stop_residual: f32,
+        handling: NoiseHandling,
+    ) -> NoisyResult
+    where
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        let mut rng = DefaultRng::seed_from_u64(seed);
+        self.find_infimum_noisy_with_rng(func, bounds, maxiter, &mut rng, use_history, stop_residual, handling)
+    }
+}
+
+/// Every population-based optimizer handles noise through a `NoisyEvaluator`.
+impl<T: PopulationOptimizer> NoisyOptimizer for T {
+    fn find_infimum_noisy_with_rng<F, R>(
+        &self,
+        func: &F,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+        use_history: bool,
+        stop_residual: f32,
+        handling: NoiseHandling,
+    ) -> NoisyResult
+    where
+        R: Rng,
+        F: Fn(&[Vec8]) -> f32 + Sync,
+    {
+        noisy_search(func, bounds, maxiter, rng, handling, |evaluator, rng| {
+            self.find_infimum_population(evaluator, bounds, maxiter, rng, use_history, stop_residual)
+        })
+    }
+}
+
+/// Non-dominated solutions found by a `MultiObjectiveOptimizer`, in
+/// dimension order within bounds, with their objective vectors.
+#[derive(Debug)]
+pub struct ParetoResult {
+    pub x: Vec<Vec<f32>>,
+    pub f_x: Vec<Vec<f32>>,
+    pub nfev: u64,
+}
+
+/// Optimizers for objectives with several values to minimise at once.
+/// `func(x, f)` writes the `objective_count` values of `x`, given in
+/// dimension order, into `f`; NaN values are treated as worst.
+pub trait MultiObjectiveOptimizer {
+    fn find_pareto_front_with_rng<R, F>(
+        &self,
+        func: &F,
+        objective_count: usize,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        rng: &mut R,
+    ) -> ParetoResult
+    where
+        R: Rng,
+        F: Fn(&[f32], &mut [f32]) + Sync;
+
+    fn find_pareto_front<F>(
+        &self,
+        func: &F,
+        objective_count: usize,
+        bounds: &[[f32; 2]],
+        maxiter: u64,
+        seed: u64,
+    ) -> ParetoResult
+    where
+        F: Fn(&[f32], &mut [f32]) + Sync,
+    {
+        let mut rng = DefaultRng::seed_from_u64(seed);
+        self.find_pareto_front_with_rng(func, objective_count, bounds, maxiter, &mut rng)
+    }
 }
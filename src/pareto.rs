@@ -0,0 +1,245 @@
+//! Pareto dominance, sorting and quality metrics for multi-objective
+//! minimisation.
+//!
+//! Objective vectors of a population are stored flat, `count * m` values for
+//! `m` objectives, like the positions of the single-objective algorithms.
+
+/// True if `a` is no worse than `b` in every objective and better in one.
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    let mut better = false;
+    for (x, y) in a.iter().zip(b) {
+        if x > y {
+            return false;
+        }
+        better |= x < y;
+    }
+    better
+}
+
+/// Fast non-dominated sort (Deb et al. 2002): indices of the points grouped
+/// into fronts, best front first.
+pub fn fast_non_dominated_sort(objectives: &[f32], m: usize) -> Vec<Vec<usize>> {
+    let count = objectives.len() / m;
+    let point = |i: usize| &objectives[i * m..(i + 1) * m];
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut domination_count = vec![0usize; count];
+    for p in 0..count {
+        for q in p + 1..count {
+            if dominates(point(p), point(q)) {
+                dominated[p].push(q);
+                domination_count[q] += 1;
+            } else if dominates(point(q), point(p)) {
+                dominated[q].push(p);
+                domination_count[p] += 1;
+            }
+        }
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    let mut fronts = vec![(0..count).filter(|&p| domination_count[p] == 0).collect::<Vec<_>>()];
+    loop {
+        let mut next = Vec::new();
+        for &p in fronts.last().unwrap() {
+            for &q in &dominated[p] {
+                domination_count[q] -= 1;
+                if domination_count[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        next.sort_unstable();
+        fronts.push(next);
+    }
+    fronts
+}
+
+/// Crowding distance of every point of `front`, in the order of `front`.
+/// Boundary points of each objective get infinity.
+pub fn crowding_distance(objectives: &[f32], m: usize, front: &[usize]) -> Vec<f32> {
+    let n = front.len();
+    let mut distance = vec![0.0f32; n];
+    if n <= 2 {
+        distance.fill(f32::INFINITY);
+        return distance;
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    for k in 0..m {
+        let value = |i: usize| objectives[front[i] * m + k];
+        order.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+        let (min, max) = (value(order[0]), value(order[n - 1]));
+        distance[order[0]] = f32::INFINITY;
+        distance[order[n - 1]] = f32::INFINITY;
+        if max > min {
+            for w in order.windows(3) {
+                distance[w[1]] += (value(w[2]) - value(w[0])) / (max - min);
+            }
+        }
+    }
+    distance
+}
+
+/// Indices of the points not dominated by any other; of identical points
+/// only the first is kept.
+pub fn non_dominated(points: &[Vec<f32>]) -> Vec<usize> {
+    (0..points.len())
+        .filter(|&i| {
+            points.iter().enumerate().all(|(j, q)| {
+                !(dominates(q, &points[i]) || (j < i && q == &points[i]))
+            })
+        })
+        .collect()
+}
+
+/// Volume dominated by `front` and bounded by `reference`, which should be
+/// worse than every point in every objective; points that are not count
+/// for nothing. Exact, by slicing along the last objective, so the cost
+/// grows as `n^(m-1)`; meant for two or three objectives.
+pub fn hypervolume(front: &[Vec<f32>], reference: &[f32]) -> f32 {
+    let points: Vec<Vec<f64>> = front
+        .iter()
+        .filter(|p| p.iter().zip(reference).all(|(v, r)| v < r))
+        .map(|p| p.iter().map(|&v| v as f64).collect())
+        .collect();
+    let reference: Vec<f64> = reference.iter().map(|&r| r as f64).collect();
+    sliced_volume(points, &reference) as f32
+}
+
+fn sliced_volume(mut points: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    let d = reference.len();
+    if points.is_empty() {
+        return 0.0;
+    }
+    if d == 1 {
+        return reference[0] - points.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+    }
+    points.sort_by(|a, b| a[d - 1].total_cmp(&b[d - 1]));
+    if d == 2 {
+        let mut area = 0.0;
+        let mut min_x = reference[0];
+        for (i, p) in points.iter().enumerate() {
+            min_x = min_x.min(p[0]);
+            let top = points.get(i + 1).map_or(reference[1], |q| q[1]);
+            area += (reference[0] - min_x) * (top - p[1]);
+        }
+        return area;
+    }
+    let mut volume = 0.0;
+    for i in 0..points.len() {
+        let top = points.get(i + 1).map_or(reference[d - 1], |q| q[d - 1]);
+        let height = top - points[i][d - 1];
+        if height > 0.0 {
+            let slice = points[..=i].iter().map(|p| p[..d - 1].to_vec()).collect();
+            volume += sliced_volume(slice, &reference[..d - 1]) * height;
+        }
+    }
+    volume
+}
+
+/// Inverted generational distance: mean distance from each point of the
+/// `reference` front to its nearest point of `front`.
+pub fn igd(front: &[Vec<f32>], reference: &[Vec<f32>]) -> f32 {
+    let total: f32 = reference
+        .iter()
+        .map(|r| {
+            front
+                .iter()
+                .map(|p| p.iter().zip(r).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt())
+                .fold(f32::INFINITY, f32::min)
+        })
+        .sum();
+    total / reference.len() as f32
+}
+
+/// All weight vectors of `m` components that are multiples of
+/// `1 / divisions` and sum to 1 (Das and Dennis), in lexicographic order.
+pub fn simplex_lattice(m: usize, divisions: usize) -> Vec<Vec<f32>> {
+    fn fill(prefix: &mut Vec<usize>, m: usize, left: usize, out: &mut Vec<Vec<usize>>) {
+        if prefix.len() + 1 == m {
+            prefix.push(left);
+            out.push(prefix.clone());
+            prefix.pop();
+            return;
+        }
+        for k in 0..=left {
+            prefix.push(k);
+            fill(prefix, m, left - k, out);
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    fill(&mut Vec::new(), m, divisions, &mut out);
+    out.into_iter()
+        .map(|w| w.into_iter().map(|k| k as f32 / divisions as f32).collect())
+        .collect()
+}
+
+/// Largest number of divisions whose lattice has at most `count` vectors
+/// (at least 1).
+pub fn lattice_divisions(m: usize, count: usize) -> usize {
+    // The lattice has C(divisions + m - 1, m - 1) vectors
+    let size = |h: usize| (1..m).fold(1usize, |acc, i| acc * (h + i) / i);
+    let mut h = 1;
+    while size(h + 1) <= count {
+        h += 1;
+    }
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_and_crowding() {
+        // (1,4) (2,2) (4,1) are mutually non-dominated; (3,3) is dominated by (2,2)
+        let objectives = [3.0, 3.0, 1.0, 4.0, 2.0, 2.0, 4.0, 1.0, 5.0, 5.0];
+        let fronts = fast_non_dominated_sort(&objectives, 2);
+        assert_eq!(fronts, vec![vec![1, 2, 3], vec![0], vec![4]]);
+        let distance = crowding_distance(&objectives, 2, &fronts[0]);
+        assert!(distance[0].is_infinite() && distance[2].is_infinite());
+        assert!((distance[1] - 2.0).abs() < 1e-6);
+        assert_eq!(fast_non_dominated_sort(&[], 2), Vec::<Vec<usize>>::new());
+        let points = vec![vec![1.0, 1.0], vec![1.0, 1.0], vec![0.0, 2.0], vec![2.0, 2.0]];
+        assert_eq!(non_dominated(&points), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_hypervolume() {
+        let front = vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0]];
+        assert_eq!(hypervolume(&front, &[4.0, 4.0]), 6.0);
+        // Points outside the reference box and dominated points add nothing
+        let mut more = front.clone();
+        more.extend([vec![5.0, 0.0], vec![3.0, 3.0]]);
+        assert_eq!(hypervolume(&more, &[4.0, 4.0]), 6.0);
+        // Unit cube minus the part not dominated by the two corners
+        let front = vec![vec![0.0, 0.0, 0.5], vec![0.5, 0.5, 0.0]];
+        assert_eq!(hypervolume(&front, &[1.0, 1.0, 1.0]), 0.5 + 0.25 * 0.5);
+        // Continuous front f2 = 1 - sqrt(f1): 1.1^2 - 1/3
+        let front: Vec<Vec<f32>> = (0..=2000)
+            .map(|i| i as f32 / 2000.0)
+            .map(|f1| vec![f1, 1.0 - f1.sqrt()])
+            .collect();
+        assert!((hypervolume(&front, &[1.1, 1.1]) - (1.21 - 1.0 / 3.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_igd() {
+        let reference = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        assert_eq!(igd(&reference, &reference), 0.0);
+        assert_eq!(igd(&[vec![0.0, 0.0]], &reference), 1.0);
+    }
+
+    #[test]
+    fn test_simplex_lattice() {
+        let weights = simplex_lattice(3, 2);
+        assert_eq!(weights.len(), 6);
+        assert!(weights.iter().all(|w| (w.iter().sum::<f32>() - 1.0).abs() < 1e-6));
+        assert_eq!(simplex_lattice(2, 4).len(), 5);
+        assert_eq!(lattice_divisions(3, 100), 12);
+        assert_eq!(lattice_divisions(2, 100), 99);
+    }
+}
@@ -0,0 +1,276 @@
+//! Runtime distributions of benchmark runs: COCO-style runtime ECDFs over
+//! targets and seeds, Moré–Wild data profiles and Dolan–Moré performance
+//! profiles, computed from the best-so-far traces of `runner::SeedResult`.
+//!
+//! Traces are sampled on a logarithmic grid (see `trace`), so a target counts
+//! as hit at the grid point that first shows it, which may be a little after
+//! the evaluation that actually reached it.
+
+use std::{collections::BTreeMap, fs};
+
+use crate::runner::SeedResult;
+
+/// Best-so-far trace of one run, as error to the optimum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub algorithm: String,
+    pub function: String,
+    pub seed: u64,
+    pub trace: Vec<(u64, f32)>,
+}
+
+impl Run {
+    pub fn from_seed_result(algorithm: &str, result: &SeedResult) -> Self {
+        let f_opt = result.f_x - result.error;
+        Self {
+            algorithm: algorithm.to_string(),
+            function: result.function.clone(),
+            seed: result.seed,
+            trace: result.trace.iter().map(|&(nfev, f)| (nfev, f - f_opt)).collect(),
+        }
+    }
+
+    /// Evaluations until the error is at most `target`, if it ever is.
+    pub fn hitting_time(&self, target: f32) -> Option<u64> {
+        self.trace.iter().find(|&&(_, error)| error <= target).map(|&(nfev, _)| nfev)
+    }
+
+    fn first_error(&self) -> f32 {
+        self.trace.first().map_or(f32::INFINITY, |&(_, error)| error)
+    }
+
+    fn final_error(&self) -> f32 {
+        self.trace.last().map_or(f32::INFINITY, |&(_, error)| error)
+    }
+}
+
+/// `per_decade` targets per factor of ten from `10^high` down to `10^low`;
+/// COCO uses 51 targets from `10^2` to `10^-8`.
+pub fn log_targets(high: i32, low: i32, per_decade: u32) -> Vec<f32> {
+    let steps = (high - low) as u32 * per_decade;
+    (0..=steps)
+        .map(|i| 10f32.powf(high as f32 - i as f32 / per_decade as f32))
+        .collect()
+}
+
+/// Fraction of all (run, target) pairs solved within a budget, as the steps
+/// `(nfev, fraction)` of a non-decreasing curve. Runs count with equal
+/// weight, so functions do too when they have the same number of seeds.
+pub fn runtime_ecdf(runs: &[&Run], targets: &[f32]) -> Vec<(u64, f32)> {
+    let total = runs.len() * targets.len();
+    let mut times: Vec<u64> = runs
+        .iter()
+        .flat_map(|run| targets.iter().filter_map(|&t| run.hitting_time(t)))
+        .collect();
+    times.sort_unstable();
+    cumulative_steps(&times, total)
+}
+
+fn cumulative_steps<T: Copy + PartialEq>(sorted: &[T], total: usize) -> Vec<(T, f32)> {
+    let mut steps: Vec<(T, f32)> = Vec::new();
+    for (i, &x) in sorted.iter().enumerate() {
+        let fraction = (i + 1) as f32 / total as f32;
+        match steps.last_mut() {
+            Some(last) if last.0 == x => last.1 = fraction,
+            _ => steps.push((x, fraction)),
+        }
+    }
+    steps
+}
+
+/// Evaluations each algorithm needs on each problem under the Moré–Wild
+/// convergence test `f <= f_L + tau * (f_0 - f_L)`, where `f_0` is the first
+/// evaluation of the run and `f_L` the best value any algorithm reached on
+/// the function. A problem is a function and seed index.
+fn convergence_times(runs: &[&Run], tau: f32) -> BTreeMap<(String, u64), BTreeMap<String, Option<u64>>> {
+    let mut best: BTreeMap<&str, f32> = BTreeMap::new();
+    for run in runs {
+        let entry = best.entry(run.function.as_str()).or_insert(f32::INFINITY);
+        *entry = entry.min(run.final_error());
+    }
+    let mut times: BTreeMap<(String, u64), BTreeMap<String, Option<u64>>> = BTreeMap::new();
+    for run in runs {
+        let f_l = best[run.function.as_str()];
+        let target = f_l + tau * (run.first_error() - f_l);
+        times
+            .entry((run.function.clone(), run.seed))
+            .or_default()
+            .insert(run.algorithm.clone(), run.hitting_time(target));
+    }
+    times
+}
+
+/// Moré–Wild data profiles: for every algorithm, the fraction of problems
+/// solved within `alpha` simplex gradients, i.e. `alpha * (dims + 1)`
+/// evaluations, as `(alpha, fraction)` steps.
+pub fn data_profiles(runs: &[&Run], dims: usize, tau: f32) -> BTreeMap<String, Vec<(f32, f32)>> {
+    let times = convergence_times(runs, tau);
+    per_algorithm(&times, |t, _| t as f32 / (dims + 1) as f32)
+}
+
+/// Dolan–Moré performance profiles: for every algorithm, the fraction of
+/// problems solved within a factor `ratio` of the fastest algorithm on the
+/// problem, as `(ratio, fraction)` steps.
+pub fn performance_profiles(runs: &[&Run], tau: f32) -> BTreeMap<String, Vec<(f32, f32)>> {
+    let times = convergence_times(runs, tau);
+    per_algorithm(&times, |t, fastest| t as f32 / fastest as f32)
+}
+
+/// Steps of the distribution of `measure(time, fastest time)` over problems,
+/// per algorithm; unsolved problems count in the total only.
+fn per_algorithm<M>(
+    times: &BTreeMap<(String, u64), BTreeMap<String, Option<u64>>>,
+    measure: M,
+) -> BTreeMap<String, Vec<(f32, f32)>>
+where
+    M: Fn(u64, u64) -> f32,
+{
+    let mut values: BTreeMap<String, Vec<f32>> = BTreeMap::new();
+    for by_algorithm in times.values() {
+        let fastest = by_algorithm.values().flatten().copied().min();
+        for (algorithm, time) in by_algorithm {
+            let entry = values.entry(algorithm.clone()).or_default();
+            if let (Some(t), Some(fastest)) = (time, fastest) {
+                entry.push(measure((*t).max(1), fastest.max(1)));
+            }
+        }
+    }
+    let problem_count = times.len();
+    values
+        .into_iter()
+        .map(|(algorithm, mut v)| {
+            v.sort_by(f32::total_cmp);
+            (algorithm, cumulative_steps(&v, problem_count))
+        })
+        .collect()
+}
+
+/// Runs of a benchmark, by test set and dimension, from the results and
+/// traces CSV files the `benchmark` binary writes. Results without an
+/// `error` column are taken to have their optimum at 0.
+pub fn load_runs(results_path: &str, traces_path: &str) -> Result<BTreeMap<(String, usize), Vec<Run>>, String> {
+    type Key = (String, usize, String, String, u64);
+    let read = |path: &str| fs::read_to_string(path).map_err(|e| format!("{path}: {e}"));
+    let results = read(results_path)?;
+    let traces = read(traces_path)?;
+
+    let mut f_opt: BTreeMap<Key, f32> = BTreeMap::new();
+    for row in csv_rows(&results, &["test_set", "dim", "algorithm", "function", "seed", "f_x", "error"], results_path)? {
+        let f_x: f32 = parse(&row[5], results_path)?;
+        let error = if row[6].is_empty() { f_x } else { parse(&row[6], results_path)? };
+        f_opt.insert(run_key(&row, results_path)?, f_x - error);
+    }
+
+    let mut runs: BTreeMap<Key, Run> = BTreeMap::new();
+    for row in csv_rows(&traces, &["test_set", "dim", "algorithm", "function", "seed", "nfev", "best_f"], traces_path)? {
+        let key = run_key(&row, traces_path)?;
+        let offset = f_opt.get(&key).copied().unwrap_or(0.0);
+        let point = (parse(&row[5], traces_path)?, parse::<f32>(&row[6], traces_path)? - offset);
+        runs.entry(key.clone())
+            .or_insert_with(|| Run {
+                algorithm: key.2.clone(),
+                function: key.3.clone(),
+                seed: key.4,
+                trace: Vec::new(),
+            })
+            .trace
+            .push(point);
+    }
+
+    let mut grouped: BTreeMap<(String, usize), Vec<Run>> = BTreeMap::new();
+    for ((test_set, dim, ..), mut run) in runs {
+        run.trace.sort_by_key(|&(nfev, _)| nfev);
+        grouped.entry((test_set, dim)).or_default().push(run);
+    }
+    Ok(grouped)
+}
+
+fn run_key(row: &[String], path: &str) -> Result<(String, usize, String, String, u64), String> {
+    Ok((row[0].clone(), parse(&row[1], path)?, row[2].clone(), row[3].clone(), parse(&row[4], path)?))
+}
+
+pub(crate) fn parse<T: std::str::FromStr>(value: &str, path: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{path}: invalid value {value:?}"))
+}
+
+/// The `columns` of every row of a CSV with a header, in the given order;
+/// missing columns read as empty.
+pub(crate) fn csv_rows(source: &str, columns: &[&str], path: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut lines = source.lines();
+    let header: Vec<&str> = lines.next().ok_or(format!("{path}: empty file"))?.split(',').collect();
+    let positions: Vec<Option<usize>> = columns.iter().map(|c| header.iter().position(|h| h == c)).collect();
+    Ok(lines
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            positions
+                .iter()
+                .map(|p| p.and_then(|p| fields.get(p)).unwrap_or(&"").to_string())
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(algorithm: &str, seed: u64, trace: &[(u64, f32)]) -> Run {
+        Run {
+            algorithm: algorithm.to_string(),
+            function: "f".to_string(),
+            seed,
+            trace: trace.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_runtime_ecdf() {
+        let a = run("a", 0, &[(1, 10.0), (10, 1.0), (100, 0.01)]);
+        let b = run("a", 1, &[(1, 10.0), (1000, 1.0)]);
+        let targets = [1.0, 0.01];
+        assert_eq!(a.hitting_time(1.0), Some(10));
+        assert_eq!(b.hitting_time(0.01), None);
+        // 4 pairs: hit at 10, 100 and 1000
+        assert_eq!(runtime_ecdf(&[&a, &b], &targets), vec![(10, 0.25), (100, 0.5), (1000, 0.75)]);
+        let targets = log_targets(2, -8, 5);
+        assert_eq!(targets.len(), 51);
+        assert_eq!(targets[0], 100.0);
+        assert!((targets[50] - 1e-8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_data_and_performance_profiles() {
+        // Both reach the best value 0; fast needs 10 evaluations, slow 40,
+        // and on seed 1 slow never converges
+        let runs = [
+            run("fast", 0, &[(1, 1.0), (10, 0.0)]),
+            run("slow", 0, &[(1, 1.0), (40, 0.0)]),
+            run("fast", 1, &[(1, 1.0), (10, 0.0)]),
+            run("slow", 1, &[(1, 1.0), (40, 0.5)]),
+        ];
+        let runs: Vec<&Run> = runs.iter().collect();
+        let data = data_profiles(&runs, 4, 1e-3);
+        assert_eq!(data["fast"], vec![(2.0, 1.0)]);
+        assert_eq!(data["slow"], vec![(8.0, 0.5)]);
+        let performance = performance_profiles(&runs, 1e-3);
+        assert_eq!(performance["fast"], vec![(1.0, 1.0)]);
+        assert_eq!(performance["slow"], vec![(4.0, 0.5)]);
+    }
+
+    #[test]
+    fn test_load_runs() {
+        let dir = std::env::temp_dir().join(format!("profiles_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let results = dir.join("results.csv");
+        let traces = dir.join("traces.csv");
+        fs::write(&results, "test_set,dim,algorithm,function,seed,f_x,nfev,error,distance\nbbob,16,de,f,0,5.5,30,0.5,\n").unwrap();
+        fs::write(&traces, "test_set,dim,algorithm,function,seed,nfev,best_f\nbbob,16,de,f,0,30,5.5\nbbob,16,de,f,0,1,9\n").unwrap();
+        let runs = load_runs(results.to_str().unwrap(), traces.to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let runs = &runs[&("bbob".to_string(), 16)];
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].algorithm, "de");
+        assert_eq!(runs[0].trace, vec![(1, 4.0), (30, 0.5)]);
+    }
+}
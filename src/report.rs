@@ -0,0 +1,380 @@
+//! Tables of benchmark results for the README and the paper, read from the
+//! results CSV. A cell is the median nfev of the successful runs, with the
+//! success rate in parentheses below 100% and `---` without any success; the
+//! fastest algorithm on a function is in bold.
+//!
+//! The Markdown table of a test set has a row per dimension and function and
+//! a column per algorithm, and per dimension an `all` row: the median of the
+//! per-function medians with the success rate over all runs. The LaTeX tables
+//! are per test set and dimension, with a row per algorithm, a column per
+//! function and the mean of the medians.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+use crate::{
+    benchmark_params::BENCHMARK_ALGORITHMS,
+    profiles::{csv_rows, parse},
+    runner::SeedResult,
+    stats::median,
+};
+
+/// Runs per `(test_set, dim, algorithm)`.
+pub type Results = BTreeMap<(String, usize, String), Vec<SeedResult>>;
+
+/// Reads the runs of a results CSV written by `benchmark`.
+pub fn load_results(path: &str) -> Result<Results, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let columns = [
+        "test_set", "dim", "algorithm", "function", "seed", "f_x", "nfev", "error", "distance", "wall_seconds", "objective_seconds",
+    ];
+    let mut results = Results::new();
+    for row in csv_rows(&source, &columns, path)? {
+        let key = (row[0].clone(), parse(&row[1], path)?, row[2].clone());
+        let run = parse_run(&row[3], parse(&row[4], path)?, &row[5..]).ok_or_else(|| format!("{path}: invalid row {row:?}"))?;
+        results.entry(key).or_default().push(run);
+    }
+    Ok(results)
+}
+
+/// A run from the fields of its results row after the cell: `f_x`, `nfev`,
+/// `error`, `distance`, `wall_seconds` and `objective_seconds`. Columns added
+/// after `nfev` may be empty: the error is then `f_x`, and the timings NaN.
+pub fn parse_run<S: AsRef<str>>(function: &str, seed: u64, fields: &[S]) -> Option<SeedResult> {
+    let [f_x, nfev, error, distance, wall, objective] = fields else { return None };
+    let f_x = f_x.as_ref().parse().ok()?;
+    Some(SeedResult {
+        function: function.to_string(),
+        seed,
+        f_x,
+        nfev: nfev.as_ref().parse().ok()?,
+        error: if error.as_ref().is_empty() { f_x } else { error.as_ref().parse().ok()? },
+        distance: distance.as_ref().parse().ok(),
+        wall_seconds: wall.as_ref().parse().unwrap_or(f64::NAN),
+        objective_seconds: objective.as_ref().parse().unwrap_or(f64::NAN),
+        trace: Vec::new(),
+    })
+}
+
+/// Success rate and speed of one algorithm on one function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outcome {
+    pub runs: usize,
+    pub successes: usize,
+    /// Median nfev of the successful runs, infinite without any.
+    pub median_nfev: f64,
+}
+
+impl Outcome {
+    pub fn of<S>(runs: &[&SeedResult], solved: S) -> Self
+    where
+        S: Fn(&SeedResult) -> bool,
+    {
+        let nfev: Vec<f64> = runs.iter().filter(|r| solved(r)).map(|r| r.nfev as f64).collect();
+        Self {
+            runs: runs.len(),
+            successes: nfev.len(),
+            median_nfev: if nfev.is_empty() { f64::INFINITY } else { median(&nfev) },
+        }
+    }
+
+    /// The median of the medians of `outcomes` and their success rate over
+    /// all runs.
+    pub fn aggregate(outcomes: &[Outcome]) -> Self {
+        let medians: Vec<f64> = outcomes.iter().map(|o| o.median_nfev).collect();
+        Self {
+            runs: outcomes.iter().map(|o| o.runs).sum(),
+            successes: outcomes.iter().map(|o| o.successes).sum(),
+            median_nfev: if medians.is_empty() { f64::INFINITY } else { median(&medians) },
+        }
+    }
+
+    /// The median nfev, `---` when infinite, and the success rate in percent
+    /// when below 100% but above 0, with one decimal.
+    fn text(&self) -> (String, Option<String>) {
+        if self.successes == 0 {
+            return ("---".to_string(), None);
+        }
+        let rate = format!("{:.1}", 100.0 * self.successes as f64 / self.runs as f64);
+        let nfev = if self.median_nfev.is_finite() { format_nfev(self.median_nfev) } else { "---".to_string() };
+        (nfev, (self.successes < self.runs).then_some(rate))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub dim: usize,
+    pub function: String,
+    /// Per algorithm of the table, none where it did not run.
+    pub outcomes: Vec<Option<Outcome>>,
+}
+
+impl Row {
+    /// Whether each algorithm has the lowest median nfev of the row, ties
+    /// included; none does if no run succeeded.
+    pub fn winners(&self) -> Vec<bool> {
+        let best = self.outcomes.iter().flatten().map(|o| o.median_nfev).fold(f64::INFINITY, f64::min);
+        self.outcomes.iter().map(|o| best.is_finite() && o.is_some_and(|o| o.median_nfev == best)).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub test_set: String,
+    pub algorithms: Vec<String>,
+    /// By dimension, then function.
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    /// Without a function column when the test set has a single function.
+    fn has_function_column(&self) -> bool {
+        self.rows.iter().map(|r| &r.function).collect::<BTreeSet<_>>().len() > 1
+    }
+
+    pub fn dims(&self) -> BTreeSet<usize> {
+        self.rows.iter().map(|r| r.dim).collect()
+    }
+
+    /// The `all` row of `dim`: per algorithm, the aggregate of its outcomes on
+    /// the functions it ran.
+    pub fn aggregate(&self, dim: usize) -> Row {
+        let rows: Vec<&Row> = self.rows.iter().filter(|r| r.dim == dim).collect();
+        let outcomes = (0..self.algorithms.len())
+            .map(|i| {
+                let outcomes: Vec<Outcome> = rows.iter().filter_map(|r| r.outcomes[i]).collect();
+                (!outcomes.is_empty()).then(|| Outcome::aggregate(&outcomes))
+            })
+            .collect();
+        Row { dim, function: "all".to_string(), outcomes }
+    }
+
+    /// A `####` heading with the test set and its dimensions.
+    pub fn heading(&self) -> String {
+        let dims = self.dims();
+        let (first, last) = (dims.first().unwrap_or(&0), dims.last().unwrap_or(&0));
+        let range = if first == last { format!("{first}D") } else { format!("{first}--{last}D") };
+        format!("#### {} ({range})\n", self.test_set)
+    }
+
+    /// A GitHub table.
+    pub fn markdown(&self) -> String {
+        let mut header = vec!["Dim"];
+        if self.has_function_column() {
+            header.push("Func");
+        }
+        header.extend(self.algorithms.iter().map(|a| display_name(a)));
+        let rule: Vec<String> = header.iter().map(|h| "-".repeat(h.len().max(3))).collect();
+        let mut out = format!("| {} |\n| {} |\n", header.join(" | "), rule.join(" | "));
+        for dim in self.dims() {
+            let mut rows: Vec<Row> = self.rows.iter().filter(|r| r.dim == dim).cloned().collect();
+            if self.has_function_column() {
+                rows.push(self.aggregate(dim));
+            }
+            for row in rows {
+                let mut cells = vec![row.dim.to_string()];
+                if self.has_function_column() {
+                    cells.push(row.function.clone());
+                }
+                for (outcome, winner) in row.outcomes.iter().zip(row.winners()) {
+                    let text = outcome.map_or(String::new(), |o| match o.text() {
+                        (nfev, Some(rate)) => format!("{nfev} ({rate}%)"),
+                        (nfev, None) => nfev,
+                    });
+                    cells.push(if winner { format!("**{text}**") } else { text });
+                }
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+        out
+    }
+
+    /// A booktabs `tabular` of `dim`, with a row per algorithm, a column per
+    /// function and the mean of the medians, `---` if one function was never
+    /// solved. Only the nfev of the winner is in bold, as in the paper.
+    pub fn latex(&self, dim: usize) -> String {
+        let escape = |s: &str| s.replace('_', "\\_");
+        let rows: Vec<&Row> = self.rows.iter().filter(|r| r.dim == dim).collect();
+        let winners: Vec<Vec<bool>> = rows.iter().map(|r| r.winners()).collect();
+        let mut out = format!("\\begin{{tabular}}{{@{{}}l{}r@{{}}}}\n\\toprule\n", "r".repeat(rows.len()));
+        let functions: Vec<String> = rows.iter().map(|r| escape(&r.function)).collect();
+        out.push_str(&format!("Algorithm & {} & Mean \\\\\n\\midrule\n", functions.join(" & ")));
+        for (i, algorithm) in self.algorithms.iter().enumerate() {
+            let outcomes: Vec<Outcome> = rows.iter().filter_map(|r| r.outcomes[i]).collect();
+            if outcomes.is_empty() {
+                continue;
+            }
+            let mut cells = vec![display_name(algorithm).to_string()];
+            for (row, winners) in rows.iter().zip(&winners) {
+                cells.push(row.outcomes[i].map_or(String::new(), |o| {
+                    let (nfev, rate) = o.text();
+                    let nfev = if winners[i] { format!("\\textbf{{{nfev}}}") } else { nfev };
+                    match rate {
+                        Some(rate) => format!("{nfev} ({rate}\\%)"),
+                        None => nfev,
+                    }
+                }));
+            }
+            let mean = outcomes.iter().map(|o| o.median_nfev).sum::<f64>() / outcomes.len() as f64;
+            cells.push(if mean.is_finite() { format_nfev(mean) } else { "---".to_string() });
+            out.push_str(&format!("{} \\\\\n", cells.join(" & ")));
+        }
+        out.push_str("\\bottomrule\n\\end{tabular}\n");
+        out
+    }
+}
+
+/// The name of an algorithm in the README and the paper; other labels are
+/// kept as they are.
+pub fn display_name(algorithm: &str) -> &str {
+    match algorithm {
+        "ans" => "ANS",
+        "ans_sort" => "ANS Sort",
+        "ansr" => "ANSR",
+        "ansr_dpnm" => "DPNM",
+        "de" => "DE",
+        "shade" => "SHADE",
+        "zero_gradient" => "ZG",
+        other => other,
+    }
+}
+
+/// A table per test set of `results`, in name order. Algorithms are in the
+/// order of `BENCHMARK_ALGORITHMS`, then by name; `solved(test_set, dim, run)`
+/// tells the successful runs.
+pub fn tables<S>(results: &Results, solved: S) -> Vec<Table>
+where
+    S: Fn(&str, usize, &SeedResult) -> bool,
+{
+    let mut algorithms: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut rows: BTreeMap<&str, BTreeSet<(usize, &str)>> = BTreeMap::new();
+    for ((test_set, dim, algorithm), runs) in results {
+        algorithms.entry(test_set).or_default().insert(algorithm);
+        rows.entry(test_set).or_default().extend(runs.iter().map(|r| (*dim, r.function.as_str())));
+    }
+    algorithms
+        .into_iter()
+        .zip(rows.into_values())
+        .map(|((test_set, algorithms), rows)| {
+            let mut algorithms: Vec<&str> = algorithms.into_iter().collect();
+            algorithms.sort_by_key(|a| BENCHMARK_ALGORITHMS.iter().position(|b| b == a).unwrap_or(BENCHMARK_ALGORITHMS.len()));
+            let rows = rows
+                .into_iter()
+                .map(|(dim, function)| {
+                    let outcomes = algorithms
+                        .iter()
+                        .map(|&algorithm| {
+                            let key = (test_set.to_string(), dim, algorithm.to_string());
+                            let runs: Vec<&SeedResult> =
+                                results.get(&key).map_or(Vec::new(), |runs| runs.iter().filter(|r| r.function == function).collect());
+                            (!runs.is_empty()).then(|| Outcome::of(&runs, |r| solved(test_set, dim, r)))
+                        })
+                        .collect();
+                    Row { dim, function: function.to_string(), outcomes }
+                })
+                .collect();
+            Table {
+                test_set: test_set.to_string(),
+                algorithms: algorithms.into_iter().map(str::to_string).collect(),
+                rows,
+            }
+        })
+        .collect()
+}
+
+/// `1856`, `70.7k`, `402k`, `1.25M`: exact below 10k, else three significant
+/// digits.
+pub fn format_nfev(nfev: f64) -> String {
+    if nfev < 1e4 {
+        return format!("{nfev:.0}");
+    }
+    let (value, suffix) = if nfev < 1e6 { (nfev / 1e3, "k") } else { (nfev / 1e6, "M") };
+    let decimals = if value < 10.0 {
+        2
+    } else if value < 100.0 {
+        1
+    } else {
+        0
+    };
+    format!("{value:.decimals$}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(function: &str, seed: u64, nfev: u64, error: f32) -> SeedResult {
+        SeedResult {
+            function: function.to_string(),
+            seed,
+            f_x: error,
+            nfev,
+            error,
+            distance: None,
+            wall_seconds: f64::NAN,
+            objective_seconds: f64::NAN,
+            trace: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_nfev() {
+        assert_eq!(format_nfev(1856.0), "1856");
+        assert_eq!(format_nfev(70_700.0), "70.7k");
+        assert_eq!(format_nfev(402_000.0), "402k");
+        assert_eq!(format_nfev(1_250_000.0), "1.25M");
+    }
+
+    #[test]
+    fn test_tables() {
+        let mut results = Results::new();
+        let key = |dim: usize, algorithm: &str| ("terrain".to_string(), dim, algorithm.to_string());
+        results.insert(key(64, "my_de"), vec![run("hilly", 0, 900, 0.0), run("hilly", 1, 1100, 0.0)]);
+        results.insert(key(64, "ansr"), vec![run("hilly", 0, 40_000, 0.0), run("hilly", 1, 50_000, 1.0)]);
+        results.insert(key(128, "ansr"), vec![run("hilly", 0, 80_000, 1.0), run("forest", 0, 60_000, 0.0)]);
+        let tables = tables(&results, |_, _, r| r.error <= 0.01);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.algorithms, vec!["ansr", "my_de"]);
+        assert_eq!(table.rows[0].winners(), vec![false, true]);
+        assert_eq!(
+            table.heading() + &table.markdown(),
+            "#### terrain (64--128D)\n\
+             | Dim | Func | ANSR | my_de |\n\
+             | --- | ---- | ---- | ----- |\n\
+             | 64 | hilly | 40.0k (50.0%) | **1000** |\n\
+             | 64 | all | 40.0k (50.0%) | **1000** |\n\
+             | 128 | forest | **60.0k** |  |\n\
+             | 128 | hilly | --- |  |\n\
+             | 128 | all | --- (50.0%) |  |\n"
+        );
+        assert_eq!(
+            table.latex(128),
+            "\\begin{tabular}{@{}lrrr@{}}\n\\toprule\n\
+             Algorithm & forest & hilly & Mean \\\\\n\\midrule\n\
+             ANSR & \\textbf{60.0k} & --- & --- \\\\\n\
+             \\bottomrule\n\\end{tabular}\n"
+        );
+    }
+
+    #[test]
+    fn test_resume_results_without_added_columns() {
+        let path = std::env::temp_dir().join(format!("report_test_{}.csv", std::process::id())).to_string_lossy().into_owned();
+        fs::write(&path, "test_set,dim,algorithm,function,seed,f_x,nfev\neasy,64,ans,sphere,0,0.004,448\n").unwrap();
+        let header = "test_set,dim,algorithm,function,seed,f_x,nfev,error,distance,wall_seconds,objective_seconds";
+        let mut runs = Vec::new();
+        let check = |row: &str| {
+            let (cell, fields) = crate::shard::parse_cell(row).unwrap();
+            runs.push(parse_run(&cell.3, cell.4, &fields).ok_or("invalid row")?);
+            Ok(())
+        };
+        crate::shard::open_append(&path, header, None, check).unwrap();
+        assert_eq!((runs[0].f_x, runs[0].nfev, runs[0].error, runs[0].distance), (0.004, 448, 0.004, None));
+        assert!(runs[0].wall_seconds.is_nan());
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{header}\neasy,64,ans,sphere,0,0.004,448,,,,\n"));
+        assert_eq!(load_results(&path).unwrap()[&("easy".to_string(), 64, "ans".to_string())][0].error, 0.004);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,315 @@
+/// One restart decision: `loser` is reset, `winner` is the particle it lost to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Restart {
+    pub loser: usize,
+    pub winner: usize,
+}
+
+/// Restart detection shared by the ANSR family.
+///
+/// Two particles are considered converged to the same basin when
+/// `close(min, max)` holds for their best residuals. The reference scan
+/// compares every pair `(lhs, rhs)` with `lhs < rhs` in index order and resets
+/// the worse particle (the best particle `best` is never reset; on ties `lhs`
+/// loses). A reset particle drops out for the rest of the pass, so both the set
+/// of restarts and their order depend on the scan order.
+///
+/// `detect` reproduces that scan in O(p log p): residuals are sorted once, the
+/// particles close to `lhs` form a contiguous window of the sorted order
+/// (found by binary search from `lhs`'s sorted neighbours), and a min-index
+/// segment tree over the window yields the alive particles in the order the
+/// pairwise scan would have met them. The returned restarts are identical,
+/// element for element, to `detect_pairwise`.
+///
+/// The window argument needs `close(min, max)` to be the relative-gap test
+/// `(max - min) / max < tolerance` for `0 < min <= max` with
+/// `0 < tolerance <= 0.5` (then `max - min` is exact and the test is monotone
+/// in both arguments). When a residual is zero, negative or NaN, or the
+/// tolerance is outside that range, `detect` falls back to the pairwise scan.
+/// It also does so below `SORTED_MIN_POPSIZE`, where the pairwise scan is faster.
+/// Infinite residuals mark particles that take no part in the pass.
+pub struct RestartDetector {
+    order: Vec<usize>,
+    position: Vec<usize>,
+    alive: Vec<bool>,
+    tree: Vec<usize>,
+    restarts: Vec<Restart>,
+}
+
+const NONE: usize = usize::MAX;
+
+/// Population size from which the sorted scan beats the pairwise one.
+pub const SORTED_MIN_POPSIZE: usize = 128;
+
+impl RestartDetector {
+    pub fn new(popsize: usize) -> Self {
+        Self {
+            order: Vec::with_capacity(popsize),
+            position: vec![0; popsize],
+            alive: vec![false; popsize],
+            tree: vec![NONE; popsize * 2],
+            restarts: Vec::with_capacity(popsize),
+        }
+    }
+
+    /// Detect restarts, using the sorted scan whenever its preconditions hold.
+    pub fn detect<C>(
+        &mut self,
+        residuals: &[f32],
+        best: usize,
+        tolerance: f32,
+        close: C,
+    ) -> &[Restart]
+    where
+        C: Fn(f32, f32) -> bool,
+    {
+        let sortable = residuals.len() >= SORTED_MIN_POPSIZE
+            && tolerance > 0.0
+            && tolerance <= 0.5
+            && residuals.iter().all(|&r| r == f32::INFINITY || r > 0.0);
+        if sortable {
+            self.detect_sorted(residuals, best, close)
+        } else {
+            self.detect_pairwise(residuals, best, close)
+        }
+    }
+
+    /// Reference O(p²) scan over all pairs in index order.
+    pub fn detect_pairwise<C>(&mut self, residuals: &[f32], best: usize, close: C) -> &[Restart]
+    where
+        C: Fn(f32, f32) -> bool,
+    {
+        let n = residuals.len();
+        self.restarts.clear();
+        self.alive.clear();
+        self.alive
+            .extend(residuals.iter().map(|&r| r != f32::INFINITY));
+        for lhs in 0..n {
+            if !self.alive[lhs] {
+                continue;
+            }
+            for rhs in (lhs + 1)..n {
+                if !self.alive[rhs] {
+                    continue;
+                }
+                let (min_residual, max_residual) = if residuals[lhs] <= residuals[rhs] {
+                    (residuals[lhs], residuals[rhs])
+                } else {
+                    (residuals[rhs], residuals[lhs])
+                };
+                if !close(min_residual, max_residual) {
+                    continue;
+                }
+                if lhs == best || (rhs != best && residuals[lhs] < residuals[rhs]) {
+                    self.alive[rhs] = false;
+                    self.restarts.push(Restart { loser: rhs, winner: lhs });
+                } else {
+                    self.alive[lhs] = false;
+                    self.restarts.push(Restart { loser: lhs, winner: rhs });
+                    break;
+                }
+            }
+        }
+        &self.restarts
+    }
+
+    /// O(p log p) scan over sorted neighbours. Only valid under the
+    /// preconditions documented on the type; prefer `detect`.
+    pub fn detect_sorted<C>(&mut self, residuals: &[f32], best: usize, close: C) -> &[Restart]
+    where
+        C: Fn(f32, f32) -> bool,
+    {
+        let n = residuals.len();
+        self.restarts.clear();
+        self.alive.clear();
+        self.alive
+            .extend(residuals.iter().map(|&r| r != f32::INFINITY));
+        self.order.clear();
+        self.order.extend((0..n).filter(|&i| self.alive[i]));
+        self.order
+            .sort_by(|&a, &b| residuals[a].total_cmp(&residuals[b]).then(a.cmp(&b)));
+        let m = self.order.len();
+        self.position.resize(n, 0);
+        for (k, &i) in self.order.iter().enumerate() {
+            self.position[i] = k;
+        }
+        self.tree.clear();
+        self.tree.resize(m * 2, NONE);
+        self.tree[m..].copy_from_slice(&self.order);
+        for k in (1..m).rev() {
+            self.tree[k] = self.tree[2 * k].min(self.tree[2 * k + 1]);
+        }
+
+        for lhs in 0..n {
+            if !self.alive[lhs] {
+                continue;
+            }
+            // Every alive particle with a smaller index has already restarted all
+            // close particles it met, so the window holds only indices > lhs.
+            let value = residuals[lhs];
+            let q = self.position[lhs];
+            self.remove(q);
+            let order = &self.order;
+            let lo = order[..q].partition_point(|&j| !close(residuals[j], value));
+            let hi = q + order[q..].partition_point(|&j| close(value, residuals[j]));
+            // [lo, upper) holds residuals <= value, [upper, hi) strictly larger ones.
+            let upper = q + order[q..hi].partition_point(|&j| residuals[j] <= value);
+
+            let stop = if lhs == best {
+                NONE
+            } else {
+                let best_in_upper = best < n
+                    && self.alive[best]
+                    && (upper..hi).contains(&self.position[best]);
+                let first_lower = self.query(lo, upper);
+                if best_in_upper { first_lower.min(best) } else { first_lower }
+            };
+            let (from, to) = if lhs == best { (lo, hi) } else { (upper, hi) };
+            loop {
+                let rhs = self.query(from, to);
+                if rhs >= stop {
+                    break;
+                }
+                self.alive[rhs] = false;
+                self.remove(self.position[rhs]);
+                self.restarts.push(Restart { loser: rhs, winner: lhs });
+            }
+            if stop != NONE {
+                self.alive[lhs] = false;
+                self.restarts.push(Restart { loser: lhs, winner: stop });
+            }
+        }
+        &self.restarts
+    }
+
+    fn remove(&mut self, position: usize) {
+        let m = self.order.len();
+        let mut k = position + m;
+        self.tree[k] = NONE;
+        while k > 1 {
+            k /= 2;
+            self.tree[k] = self.tree[2 * k].min(self.tree[2 * k + 1]);
+        }
+    }
+
+    /// Smallest alive index among sorted positions `[from, to)`.
+    fn query(&self, from: usize, to: usize) -> usize {
+        let m = self.order.len();
+        let (mut l, mut r) = (from + m, to + m);
+        let mut result = NONE;
+        while l < r {
+            if l & 1 == 1 {
+                result = result.min(self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                result = result.min(self.tree[r]);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Uniform};
+    use rand_pcg::Pcg64Mcg;
+
+    fn relative_gap(tolerance: f32) -> impl Fn(f32, f32) -> bool {
+        move |min, max| max != 0.0 && (max - min) / max < tolerance
+    }
+
+    fn argmin(residuals: &[f32]) -> usize {
+        let mut ind = 0;
+        for (i, r) in residuals.iter().enumerate() {
+            if *r < residuals[ind] {
+                ind = i;
+            }
+        }
+        ind
+    }
+
+    #[test]
+    fn test_sorted_matches_pairwise_random() {
+        let mut rng = Pcg64Mcg::seed_from_u64(7);
+        let cluster = Uniform::new(0, 6).unwrap();
+        let jitter = Uniform::new(0.0f32, 1e-3).unwrap();
+        let coin = Uniform::new(0.0f32, 1.0).unwrap();
+        let mut pairwise = RestartDetector::new(0);
+        let mut sorted = RestartDetector::new(0);
+        for trial in 0..500 {
+            let n = 2 + trial % 200;
+            let residuals: Vec<f32> = (0..n)
+                .map(|_| {
+                    if coin.sample(&mut rng) < 0.1 {
+                        f32::INFINITY
+                    } else if coin.sample(&mut rng) < 0.2 {
+                        0.25
+                    } else {
+                        0.1 * (1 + cluster.sample(&mut rng)) as f32 + jitter.sample(&mut rng)
+                    }
+                })
+                .collect();
+            let best = argmin(&residuals);
+            for tolerance in [1e-8, 1e-3, 1e-2, 0.3, 0.5] {
+                let expected = pairwise
+                    .detect_pairwise(&residuals, best, relative_gap(tolerance))
+                    .to_vec();
+                let actual = sorted.detect_sorted(&residuals, best, relative_gap(tolerance));
+                assert_eq!(actual, &expected[..], "residuals={:?} tol={}", residuals, tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sorted_matches_pairwise_with_non_argmin_best() {
+        let residuals = [0.5, 0.5001, 0.4999, 0.5002, 0.7, 0.70001];
+        let mut a = RestartDetector::new(residuals.len());
+        let mut b = RestartDetector::new(residuals.len());
+        for best in 0..residuals.len() {
+            let expected = a.detect_pairwise(&residuals, best, relative_gap(0.01)).to_vec();
+            let actual = b.detect_sorted(&residuals, best, relative_gap(0.01));
+            assert_eq!(actual, &expected[..], "best={}", best);
+        }
+    }
+
+    #[test]
+    fn test_best_is_never_restarted() {
+        let residuals = [0.3, 0.3, 0.3, 0.3];
+        let mut detector = RestartDetector::new(4);
+        let restarts = detector.detect(&residuals, 2, 0.01, relative_gap(0.01));
+        assert!(restarts.iter().all(|r| r.loser != 2));
+        assert_eq!(restarts.len(), 3);
+    }
+
+    #[test]
+    fn test_ties_restart_lhs() {
+        let residuals = [0.1, 0.2, 0.2];
+        let mut detector = RestartDetector::new(3);
+        let restarts = detector.detect(&residuals, 0, 0.01, relative_gap(0.01));
+        assert_eq!(restarts, &[Restart { loser: 1, winner: 2 }]);
+    }
+
+    #[test]
+    fn test_falls_back_for_non_positive_residuals() {
+        let residuals = [0.0, -1.0, -1.0000001, 2.0];
+        let mut a = RestartDetector::new(4);
+        let mut b = RestartDetector::new(4);
+        let expected = a.detect_pairwise(&residuals, 2, relative_gap(0.01)).to_vec();
+        let actual = b.detect(&residuals, 2, 0.01, relative_gap(0.01));
+        assert_eq!(actual, &expected[..]);
+    }
+
+    #[test]
+    fn test_infinite_residuals_are_ignored() {
+        let residuals = [f32::INFINITY, 0.5, f32::INFINITY, 0.5];
+        let mut detector = RestartDetector::new(4);
+        let restarts = detector.detect(&residuals, 3, 0.01, relative_gap(0.01));
+        assert_eq!(restarts, &[Restart { loser: 1, winner: 3 }]);
+    }
+}
@@ -0,0 +1,74 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+
+/// RNG used by `find_infimum` / `find_infimum_batch`. Any other `rand::Rng`
+/// can be plugged in through the `*_with_rng` methods of the optimizer traits.
+pub type DefaultRng = Pcg64Mcg;
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// SplitMix64 finaliser: a bijection on `u64` with full avalanche.
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Seed of sub-stream `stream` of the run seeded with `seed`.
+///
+/// Stream 0 is the run itself (`stream_seed(seed, 0) == seed`), so a run that
+/// never splits is seeded exactly as `seed_from_u64(seed)`. Stream `k > 0` is
+/// `mix64(seed + k * GOLDEN_GAMMA) ^ mix64(k)`: the sub-streams of one seed
+/// are pairwise distinct, and neighbouring seeds (0, 1, 2, ... as used by the
+/// runner) do not share sub-streams in practice because every input bit
+/// avalanches into the output.
+pub fn stream_seed(seed: u64, stream: u64) -> u64 {
+    if stream == 0 {
+        return seed;
+    }
+    mix64(seed.wrapping_add(stream.wrapping_mul(GOLDEN_GAMMA))) ^ mix64(stream)
+}
+
+/// RNG for sub-stream `stream` of the run seeded with `seed`.
+///
+/// Use one stream per island, worker or evaluation batch instead of sharing an
+/// RNG across threads; results then depend only on `(seed, stream)` and not on
+/// scheduling. Seeding the same `(seed, stream)` in different algorithms gives
+/// common random numbers for variance-reduction comparisons.
+pub fn stream_rng<R: SeedableRng>(seed: u64, stream: u64) -> R {
+    R::seed_from_u64(stream_seed(seed, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_stream_zero_is_plain_seed() {
+        let mut a: DefaultRng = stream_rng(42, 0);
+        let mut b = DefaultRng::seed_from_u64(42);
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn test_streams_are_distinct() {
+        let mut seen = HashSet::new();
+        for seed in 0..64 {
+            for stream in 0..64 {
+                assert!(seen.insert(stream_seed(seed, stream)), "seed={} stream={}", seed, stream);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_rng_is_deterministic() {
+        let mut a: rand::rngs::StdRng = stream_rng(7, 3);
+        let mut b: rand::rngs::StdRng = stream_rng(7, 3);
+        let mut c: rand::rngs::StdRng = stream_rng(7, 4);
+        let x = a.next_u64();
+        assert_eq!(x, b.next_u64());
+        assert_ne!(x, c.next_u64());
+    }
+}
@@ -1,18 +1,21 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::Instant};
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 
 use crate::{
     early_stop_callback::EarlyStopCallback,
-    functions::TestFunction,
-    optimizer::{Optimizer, OptimizerResult},
-    utils::broadcast_simd,
+    functions::{multi_objective::MultiObjectiveProblem, Objective, Problem},
+    optimizer::{MultiObjectiveOptimizer, Optimizer, OptimizerResult, ParetoResult},
+    pareto::{hypervolume, igd},
+    scheduler::{run_cost, run_jobs, Job},
+    trace::{traced, TraceRecorder, SAMPLES_PER_DECADE},
+    utils::quantile,
 };
 
-pub fn run_multiple_optimizaions<T>(
+pub fn run_multiple_optimizaions<T, P>(
     optimizer: &T,
-    functions: &BTreeMap<String, TestFunction>,
+    functions: &BTreeMap<String, P>,
     dimension_count: usize,
     maxiter: u64,
     seed_count: u64,
@@ -22,6 +25,7 @@ pub fn run_multiple_optimizaions<T>(
 ) -> BTreeMap<String, f32>
 where
     T: Optimizer + Sync,
+    P: Problem,
 {
     let mut total_result: BTreeMap<String, f32> = BTreeMap::new();
     let m = MultiProgress::new();
@@ -46,10 +50,11 @@ where
         } else {
             None
         };
-        let func = &broadcast_simd(&function.func);
-        let bounds = &function.bounds.repeat(dimension_count / 2);
+        let func = &function.objective(dimension_count);
+        let bounds = &function.bounds(dimension_count);
+        let target = function.f_opt(dimension_count) + stop_residual;
         let mut total_nfev = 0;
-        let early_stop_callback = EarlyStopCallback::new(func, stop_residual);
+        let early_stop_callback = EarlyStopCallback::new(func, target);
         let compute = |seed: u64| {
             let result =
                 optimizer.find_infimum(func, bounds, maxiter, seed, false, &early_stop_callback);
@@ -68,7 +73,7 @@ where
             seed_pb.finish_and_clear();
         }
         for result in results {
-            if result.f_x <= stop_residual {
+            if result.f_x <= target {
                 total_nfev += result.nfev;
             } else {
                 total_nfev = u64::MAX;
@@ -91,16 +96,78 @@ where
     total_result
 }
 
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(u, v)| (u - v) * (u - v)).sum::<f32>().sqrt()
+}
+
+#[derive(Debug, Clone)]
 pub struct SeedResult {
     pub function: String,
     pub seed: u64,
     pub f_x: f32,
     pub nfev: u64,
+    /// Error to the optimum, `f_x - f_opt`.
+    pub error: f32,
+    /// Euclidean distance from the returned point to `x_opt`, if known.
+    pub distance: Option<f32>,
+    /// Best-so-far `(nfev, f)` pairs on a logarithmic grid; see `trace`.
+    pub trace: Vec<(u64, f32)>,
+    /// Wall-clock seconds of the run.
+    pub wall_seconds: f64,
+    /// Seconds of `wall_seconds` spent in the objective; the rest is the
+    /// optimizer's own work.
+    pub objective_seconds: f64,
+}
+
+impl SeedResult {
+    pub fn evals_per_second(&self) -> f64 {
+        self.nfev as f64 / self.wall_seconds
+    }
+
+    pub fn overhead_seconds(&self) -> f64 {
+        self.wall_seconds - self.objective_seconds
+    }
 }
 
-pub fn run_multiple_optimizations_detailed<T>(
+/// Time spent by a set of runs, split between objective and optimizer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    pub runs: u64,
+    pub nfev: u64,
+    pub wall_seconds: f64,
+    pub objective_seconds: f64,
+}
+
+impl Throughput {
+    /// Totals over the runs of `results` that were timed.
+    pub fn of(results: &[SeedResult]) -> Self {
+        let timed = results.iter().filter(|r| r.wall_seconds.is_finite() && r.objective_seconds.is_finite());
+        timed.fold(Throughput { runs: 0, nfev: 0, wall_seconds: 0.0, objective_seconds: 0.0 }, |t, r| Throughput {
+            runs: t.runs + 1,
+            nfev: t.nfev + r.nfev,
+            wall_seconds: t.wall_seconds + r.wall_seconds,
+            objective_seconds: t.objective_seconds + r.objective_seconds,
+        })
+    }
+
+    pub fn evals_per_second(&self) -> f64 {
+        self.nfev as f64 / self.wall_seconds
+    }
+
+    /// Share of the time the optimizer spends outside the objective.
+    pub fn overhead_fraction(&self) -> f64 {
+        (self.wall_seconds - self.objective_seconds) / self.wall_seconds
+    }
+
+    /// Optimizer seconds per evaluation.
+    pub fn overhead_per_eval(&self) -> f64 {
+        (self.wall_seconds - self.objective_seconds) / self.nfev as f64
+    }
+}
+
+pub fn run_multiple_optimizations_detailed<T, P>(
     optimizer: &T,
-    functions: &BTreeMap<String, TestFunction>,
+    functions: &BTreeMap<String, P>,
     dimension_count: usize,
     maxiter: u64,
     seed_count: u64,
@@ -110,60 +177,307 @@ pub fn run_multiple_optimizations_detailed<T>(
 ) -> Vec<SeedResult>
 where
     T: Optimizer + Sync,
+    P: Problem,
 {
-    let mut all_results: Vec<SeedResult> = Vec::new();
-    let m = MultiProgress::new();
-    let sty = ProgressStyle::with_template(
-        "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
+    run_selected_optimizations(
+        optimizer, functions, dimension_count, maxiter, seed_count, stop_residual, use_progress_bar, use_par_iter,
+        |_, _| true,
     )
-    .unwrap()
-    .progress_chars("##-");
-    let optional_pb = if use_progress_bar {
-        let pb = m.add(ProgressBar::new(functions.len() as u64));
-        pb.set_style(sty.clone());
-        Some(pb)
-    } else {
-        None
-    };
-    for (function_name, function) in functions {
-        let optional_seed_pb = if use_progress_bar {
-            let seed_pb = m.add(ProgressBar::new(seed_count));
-            seed_pb.set_style(sty.clone());
-            seed_pb.set_message(function_name.clone());
-            Some(seed_pb)
-        } else {
-            None
-        };
-        let func = &broadcast_simd(&function.func);
-        let bounds = &function.bounds.repeat(dimension_count / 2);
-        let early_stop_callback = EarlyStopCallback::new(func, stop_residual);
-        let compute = |seed: u64| {
-            let result =
-                optimizer.find_infimum(func, bounds, maxiter, seed, false, &early_stop_callback);
-            if let Some(seed_pb) = &optional_seed_pb {
-                seed_pb.inc(1);
-            }
-            result
+}
+
+/// Like `run_multiple_optimizations_detailed`, but only the seeds below
+/// `seed_count` for which `select(function, seed)` holds; for resuming and
+/// sharding benchmarks.
+#[allow(clippy::too_many_arguments)]
+pub fn run_selected_optimizations<T, P>(
+    optimizer: &T,
+    functions: &BTreeMap<String, P>,
+    dimension_count: usize,
+    maxiter: u64,
+    seed_count: u64,
+    stop_residual: f32,
+    use_progress_bar: bool,
+    use_par_iter: bool,
+    select: impl Fn(&str, u64) -> bool,
+) -> Vec<SeedResult>
+where
+    T: Optimizer + Sync,
+    P: Problem,
+{
+    let instances = instances(functions, dimension_count);
+    let jobs: Vec<Job<SeedResult>> = instances
+        .iter()
+        .flat_map(|instance| {
+            let select = &select;
+            (0..seed_count)
+                .filter(move |&seed| select(&instance.name, seed))
+                .map(move |seed| seed_job(optimizer, instance, maxiter, seed, stop_residual))
+        })
+        .collect();
+    let optional_pb = use_progress_bar.then(|| {
+        let pb = ProgressBar::new(jobs.len() as u64);
+        pb.set_style(
+            ProgressStyle::with_template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+                .unwrap()
+                .progress_chars("##-"),
+        );
+        pb
+    });
+    let results = run_jobs(jobs, use_par_iter, |r: &SeedResult| {
+        if let Some(pb) = &optional_pb {
+            pb.set_message(r.function.clone());
+            pb.inc(1);
+        }
+    });
+    if let Some(pb) = &optional_pb {
+        pb.finish_and_clear();
+    }
+    results
+}
+
+/// A problem built for one dimension: its objective, bounds and optimum,
+/// computed once and shared by the jobs of every seed and algorithm.
+pub struct Instance<'a> {
+    pub name: String,
+    pub dims: usize,
+    pub objective: Objective<'a>,
+    pub bounds: Vec<[f32; 2]>,
+    pub f_opt: f32,
+    pub x_opt: Option<Vec<f32>>,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new<P>(name: &str, function: &'a P, dims: usize) -> Self
+    where
+        P: Problem + ?Sized,
+    {
+        Instance {
+            name: name.to_string(),
+            dims,
+            objective: function.objective(dims),
+            bounds: function.bounds(dims),
+            f_opt: function.f_opt(dims),
+            x_opt: function.x_opt(dims),
+        }
+    }
+}
+
+/// Every function of `functions` at `dims` dimensions, in name order.
+pub fn instances<P: Problem>(functions: &BTreeMap<String, P>, dims: usize) -> Vec<Instance<'_>> {
+    functions.iter().map(|(name, function)| Instance::new(name, function, dims)).collect()
+}
+
+/// One seed of `instance` as a job for `scheduler::run_jobs`.
+pub fn seed_job<'a, T>(optimizer: &'a T, instance: &'a Instance<'_>, maxiter: u64, seed: u64, stop_residual: f32) -> Job<'a, SeedResult>
+where
+    T: Optimizer + Sync,
+{
+    Job::new(run_cost(instance.dims, maxiter), move || {
+        let recorder = TraceRecorder::new(SAMPLES_PER_DECADE);
+        let start = Instant::now();
+        let result = {
+            let func = traced(&instance.objective, &recorder);
+            let early_stop_callback = EarlyStopCallback::new(&func, instance.f_opt + stop_residual);
+            optimizer.find_infimum(&func, &instance.bounds, maxiter, seed, false, &early_stop_callback)
         };
-        let results: Vec<OptimizerResult> = if use_par_iter {
+        let wall_seconds = start.elapsed().as_secs_f64();
+        SeedResult {
+            function: instance.name.clone(),
+            seed,
+            f_x: result.f_x,
+            nfev: result.nfev,
+            error: result.f_x - instance.f_opt,
+            distance: instance.x_opt.as_ref().map(|x_opt| distance(&result.x, x_opt)),
+            objective_seconds: recorder.objective_time().as_secs_f64(),
+            trace: recorder.finish(),
+            wall_seconds,
+        }
+    })
+}
+
+/// Outcome of all seeds on one function. A run succeeds when its error to the
+/// optimum is at most the stop residual; the evaluation statistics are over
+/// successful runs only and infinite when there are none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSummary {
+    pub runs: u64,
+    pub successes: u64,
+    pub success_rate: f32,
+    pub mean_nfev: f32,
+    pub median_nfev: f32,
+    pub nfev_q10: f32,
+    pub nfev_q90: f32,
+    /// Expected running time: evaluations spent by all runs, failed ones
+    /// included, per success.
+    pub ert: f32,
+    pub best_error: f32,
+    pub median_error: f32,
+}
+
+pub fn summarize_seeds(results: &[&SeedResult], stop_residual: f32) -> FunctionSummary {
+    let solved: Vec<f32> = results
+        .iter()
+        .filter(|r| r.error <= stop_residual)
+        .map(|r| r.nfev as f32)
+        .collect();
+    let errors: Vec<f32> = results.iter().map(|r| r.error).collect();
+    let total_nfev: u64 = results.iter().map(|r| r.nfev).sum();
+    let successes = solved.len() as u64;
+    let over_successes = |value: f32| if solved.is_empty() { f32::INFINITY } else { value };
+    FunctionSummary {
+        runs: results.len() as u64,
+        successes,
+        success_rate: successes as f32 / results.len().max(1) as f32,
+        mean_nfev: over_successes(solved.iter().sum::<f32>() / solved.len() as f32),
+        median_nfev: over_successes(quantile(&solved, 0.5)),
+        nfev_q10: over_successes(quantile(&solved, 0.1)),
+        nfev_q90: over_successes(quantile(&solved, 0.9)),
+        ert: over_successes(total_nfev as f32 / successes as f32),
+        best_error: errors.iter().copied().fold(f32::INFINITY, f32::min),
+        median_error: quantile(&errors, 0.5),
+    }
+}
+
+/// Like `run_multiple_optimizations_detailed`, summarized per function.
+#[allow(clippy::too_many_arguments)]
+pub fn run_multiple_optimizations_summary<T, P>(
+    optimizer: &T,
+    functions: &BTreeMap<String, P>,
+    dimension_count: usize,
+    maxiter: u64,
+    seed_count: u64,
+    stop_residual: f32,
+    use_progress_bar: bool,
+    use_par_iter: bool,
+) -> BTreeMap<String, FunctionSummary>
+where
+    T: Optimizer + Sync,
+    P: Problem,
+{
+    let results = run_multiple_optimizations_detailed(
+        optimizer,
+        functions,
+        dimension_count,
+        maxiter,
+        seed_count,
+        stop_residual,
+        use_progress_bar,
+        use_par_iter,
+    );
+    summarize_functions(&results, stop_residual)
+}
+
+/// `summarize_seeds` of every function in `results`.
+pub fn summarize_functions(results: &[SeedResult], stop_residual: f32) -> BTreeMap<String, FunctionSummary> {
+    let mut by_function: BTreeMap<&str, Vec<&SeedResult>> = BTreeMap::new();
+    for r in results {
+        by_function.entry(&r.function).or_default().push(r);
+    }
+    by_function
+        .into_iter()
+        .map(|(name, seeds)| (name.to_string(), summarize_seeds(&seeds, stop_residual)))
+        .collect()
+}
+
+/// Scalar to rank configurations by, lower is better: the mean over
+/// functions of a per-function value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingObjective {
+    /// Mean evaluations to success, infinite unless every seed succeeds.
+    MeanNfev,
+    /// Expected running time, infinite for a function no seed solves.
+    Ert,
+    /// Share of failed runs.
+    FailureRate,
+    /// Median final error to the optimum.
+    MedianError,
+}
+
+impl RankingObjective {
+    pub const NAMES: [&str; 4] = ["mean_nfev", "ert", "failure_rate", "median_error"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mean_nfev" => Some(Self::MeanNfev),
+            "ert" => Some(Self::Ert),
+            "failure_rate" => Some(Self::FailureRate),
+            "median_error" => Some(Self::MedianError),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MeanNfev => "mean_nfev",
+            Self::Ert => "ert",
+            Self::FailureRate => "failure_rate",
+            Self::MedianError => "median_error",
+        }
+    }
+
+    pub fn function_value(&self, summary: &FunctionSummary) -> f32 {
+        match self {
+            Self::MeanNfev if summary.successes == summary.runs => summary.mean_nfev,
+            Self::MeanNfev => f32::INFINITY,
+            Self::Ert => summary.ert,
+            Self::FailureRate => 1.0 - summary.success_rate,
+            Self::MedianError => summary.median_error,
+        }
+    }
+
+    pub fn score(&self, summaries: &BTreeMap<String, FunctionSummary>) -> f32 {
+        summaries.values().map(|s| self.function_value(s)).sum::<f32>() / summaries.len() as f32
+    }
+}
+
+pub struct ParetoSeedResult {
+    pub function: String,
+    pub seed: u64,
+    pub nfev: u64,
+    /// Number of non-dominated points returned.
+    pub front_size: usize,
+    /// Hypervolume of the front, against the problem's reference point.
+    pub hypervolume: f32,
+    /// Inverted generational distance to a sample of the true front.
+    pub igd: f32,
+}
+
+/// Runs a multi-objective optimizer on every problem at its default
+/// dimension for `seed_count` seeds and scores each front.
+pub fn run_multi_objective_optimizations<T, P>(
+    optimizer: &T,
+    problems: &BTreeMap<String, P>,
+    maxiter: u64,
+    seed_count: u64,
+    use_par_iter: bool,
+) -> Vec<ParetoSeedResult>
+where
+    T: MultiObjectiveOptimizer + Sync,
+    P: MultiObjectiveProblem,
+{
+    let mut all_results = Vec::new();
+    for (function_name, problem) in problems {
+        let m = problem.objective_count();
+        let bounds = &problem.bounds(problem.default_dims());
+        let func = |x: &[f32], f: &mut [f32]| problem.evaluate(x, f);
+        let true_front = problem.pareto_front(500);
+        let reference = problem.reference_point();
+        let compute = |seed: u64| optimizer.find_pareto_front(&func, m, bounds, maxiter, seed);
+        let results: Vec<ParetoResult> = if use_par_iter {
             (0..seed_count).into_par_iter().map(compute).collect()
         } else {
-            (0..seed_count).into_iter().map(compute).collect()
+            (0..seed_count).map(compute).collect()
         };
-        if let Some(seed_pb) = &optional_seed_pb {
-            seed_pb.finish_and_clear();
-        }
         for (seed, result) in results.into_iter().enumerate() {
-            all_results.push(SeedResult {
+            all_results.push(ParetoSeedResult {
                 function: function_name.clone(),
                 seed: seed as u64,
-                f_x: result.f_x,
                 nfev: result.nfev,
+                front_size: result.f_x.len(),
+                hypervolume: hypervolume(&result.f_x, &reference),
+                igd: igd(&result.f_x, &true_front),
             });
         }
-        if let Some(pb) = &optional_pb {
-            pb.inc(1);
-        }
     }
     all_results
 }
@@ -172,8 +486,8 @@ where
 mod tests {
     use super::*;
     use crate::{
-        default_algorithms_params::DEFAULT_ANSR,
-        functions::MINI_TEST_FUNCTIONS,
+        default_algorithms_params::{DEFAULT_ANSR, DEFAULT_NSGA2},
+        functions::{bbob::bbob_suite, EASY_TEST_FUNCTIONS, MINI_TEST_FUNCTIONS, ZDT_TEST_FUNCTIONS},
     };
 
     #[test]
@@ -238,4 +552,157 @@ mod tests {
         );
         assert_eq!(result["shifted_sphere"], f32::INFINITY);
     }
+
+    #[test]
+    fn test_run_multiple_targets_are_relative_to_f_opt() {
+        let suite = bbob_suite(1);
+        let mut functions = BTreeMap::new();
+        functions.insert("sphere".to_string(), suite["f01_sphere"]);
+        let result = run_multiple_optimizaions(
+            &DEFAULT_ANSR,
+            &functions,
+            16,
+            50_000,
+            2,
+            0.01,
+            false,
+            false,
+        );
+        assert!(result["sphere"].is_finite());
+        let detailed = run_multiple_optimizations_detailed(
+            &DEFAULT_ANSR,
+            &functions,
+            16,
+            50_000,
+            1,
+            0.01,
+            false,
+            false,
+        );
+        assert!(detailed[0].f_x - functions["sphere"].f_opt(16) <= 0.01);
+        assert_eq!(detailed[0].error, detailed[0].f_x - functions["sphere"].f_opt(16));
+        assert!(detailed[0].distance.is_some());
+    }
+
+    #[test]
+    fn test_detailed_reports_distance_to_optimum() {
+        let mut functions = BTreeMap::new();
+        functions.insert("sphere".to_string(), EASY_TEST_FUNCTIONS["sphere"].clone());
+        let detailed = run_multiple_optimizations_detailed(
+            &DEFAULT_ANSR,
+            &functions,
+            16,
+            50_000,
+            2,
+            0.001,
+            false,
+            false,
+        );
+        for r in &detailed {
+            assert!(r.error <= 0.001);
+            // The trace ends at the reported evaluations and value
+            let &(last_nfev, last_f) = r.trace.last().unwrap();
+            assert_eq!(last_f, r.f_x);
+            assert!(last_nfev >= r.nfev);
+            // 16D sphere: mean over pairs of |x|^2 / 50, so error 0.001 bounds |x|^2 by 0.4
+            assert!(r.distance.unwrap() <= 0.4f32.sqrt() + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_multi_objective_scores_fronts() {
+        let mut problems = BTreeMap::new();
+        problems.insert("zdt1".to_string(), ZDT_TEST_FUNCTIONS["zdt1"]);
+        let results = run_multi_objective_optimizations(&DEFAULT_NSGA2, &problems, 2_000, 2, true);
+        assert_eq!(results.len(), 2);
+        for r in &results {
+            assert!(r.nfev <= 2_000 && r.front_size > 0);
+            assert!(r.igd.is_finite() && r.hypervolume >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_summary_counts_failed_runs_in_ert() {
+        let seed = |error: f32, nfev: u64| SeedResult {
+            function: "f".to_string(),
+            seed: 0,
+            f_x: error,
+            nfev,
+            error,
+            distance: None,
+            trace: Vec::new(),
+            wall_seconds: 0.0,
+            objective_seconds: 0.0,
+        };
+        let runs = [seed(0.0, 100), seed(0.005, 300), seed(1.0, 1000), seed(2.0, 1000)];
+        let summary = summarize_seeds(&runs.iter().collect::<Vec<_>>(), 0.01);
+        assert_eq!(summary.successes, 2);
+        assert_eq!(summary.success_rate, 0.5);
+        assert_eq!(summary.mean_nfev, 200.0);
+        assert_eq!(summary.median_nfev, 200.0);
+        assert_eq!(summary.ert, 2400.0 / 2.0);
+        assert_eq!(summary.best_error, 0.0);
+        assert_eq!(summary.median_error, 0.5025);
+
+        let failed = summarize_seeds(&runs[2..].iter().collect::<Vec<_>>(), 0.01);
+        assert_eq!(failed.success_rate, 0.0);
+        assert!(failed.ert.is_infinite() && failed.median_nfev.is_infinite());
+
+        let summaries = BTreeMap::from([("a".to_string(), summary), ("b".to_string(), failed)]);
+        assert!(RankingObjective::MeanNfev.score(&summaries).is_infinite());
+        assert!(RankingObjective::Ert.score(&summaries).is_infinite());
+        assert_eq!(RankingObjective::FailureRate.score(&summaries), 0.75);
+        for name in RankingObjective::NAMES {
+            assert_eq!(RankingObjective::from_name(name).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn test_run_multiple_summary() {
+        let summaries = run_multiple_optimizations_summary(
+            &DEFAULT_ANSR,
+            &MINI_TEST_FUNCTIONS,
+            16,
+            10_000,
+            3,
+            0.1,
+            false,
+            true,
+        );
+        assert_eq!(summaries.len(), MINI_TEST_FUNCTIONS.len());
+        for s in summaries.values() {
+            assert_eq!(s.runs, 3);
+            assert!(s.best_error <= s.median_error);
+            assert_eq!(s.ert.is_finite(), s.successes > 0);
+        }
+    }
+
+    #[test]
+    fn test_selected_seeds_match_full_run() {
+        let full = run_multiple_optimizations_detailed(&DEFAULT_ANSR, &MINI_TEST_FUNCTIONS, 8, 2_000, 4, 0.1, false, false);
+        let selected = run_selected_optimizations(
+            &DEFAULT_ANSR, &MINI_TEST_FUNCTIONS, 8, 2_000, 4, 0.1, false, false,
+            |_, seed| seed % 2 == 1,
+        );
+        assert_eq!(selected.len(), MINI_TEST_FUNCTIONS.len() * 2);
+        for r in &selected {
+            let same = full.iter().find(|f| f.function == r.function && f.seed == r.seed).unwrap();
+            assert_eq!((r.f_x.to_bits(), r.nfev, &r.trace), (same.f_x.to_bits(), same.nfev, &same.trace));
+        }
+    }
+
+    #[test]
+    fn test_runs_are_timed() {
+        let results = run_multiple_optimizations_detailed(&DEFAULT_ANSR, &MINI_TEST_FUNCTIONS, 8, 2_000, 2, 0.1, false, false);
+        for r in &results {
+            assert!(r.wall_seconds > 0.0 && r.objective_seconds > 0.0, "{r:?}");
+            assert!(r.objective_seconds <= r.wall_seconds);
+            assert!(r.evals_per_second().is_finite());
+        }
+        let mut timed = results[..2].to_vec();
+        timed[1].wall_seconds = f64::NAN;
+        let throughput = Throughput::of(&timed);
+        assert_eq!((throughput.runs, throughput.nfev), (1, results[0].nfev));
+        assert!((throughput.overhead_fraction() - results[0].overhead_seconds() / results[0].wall_seconds).abs() < 1e-12);
+    }
 }
@@ -0,0 +1,99 @@
+//! One work queue for benchmark runs. Every algorithm × function × dimension
+//! × seed cell becomes a job, and the threads of rayon's global pool take the
+//! next one as they become free, most costly first: small cells no longer
+//! leave cores idle, and large ones no longer run last on their own.
+//!
+//! The global pool is the thread cap for everything, sized by
+//! `set_thread_count` before its first use or by `RAYON_NUM_THREADS`.
+
+use std::sync::Mutex;
+
+pub struct Job<'a, R> {
+    /// Estimated cost; only the order of costs matters.
+    pub cost: f64,
+    run: Box<dyn FnOnce() -> R + Send + 'a>,
+}
+
+impl<'a, R: 'a> Job<'a, R> {
+    pub fn new(cost: f64, run: impl FnOnce() -> R + Send + 'a) -> Self {
+        Self { cost, run: Box::new(run) }
+    }
+
+    /// The same job, followed by `f` on its result.
+    pub fn map<S: 'a>(self, f: impl FnOnce(R) -> S + Send + 'a) -> Job<'a, S> {
+        let run = self.run;
+        Job::new(self.cost, move || f(run()))
+    }
+}
+
+/// Cost of a run: its evaluation budget times the dimension, so that the
+/// largest dimensions go first.
+pub fn run_cost(dimension_count: usize, maxiter: u64) -> f64 {
+    dimension_count as f64 * maxiter as f64
+}
+
+/// Caps all later parallel work at `threads`. Fails once the pool is in use.
+pub fn set_thread_count(threads: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build_global().map_err(|e| e.to_string())
+}
+
+/// Runs `jobs` on the current pool, most costly first, or one after another
+/// on this thread if not `parallel`. `on_done` sees each result as its job
+/// finishes; the results are returned in the order of `jobs`.
+pub fn run_jobs<R: Send>(jobs: Vec<Job<'_, R>>, parallel: bool, on_done: impl Fn(&R) + Sync) -> Vec<R> {
+    if !parallel {
+        return jobs
+            .into_iter()
+            .map(|job| {
+                let result = (job.run)();
+                on_done(&result);
+                result
+            })
+            .collect();
+    }
+    let results: Vec<Mutex<Option<R>>> = jobs.iter().map(|_| Mutex::new(None)).collect();
+    let mut order: Vec<(usize, Job<'_, R>)> = jobs.into_iter().enumerate().collect();
+    order.sort_by(|(_, a), (_, b)| b.cost.total_cmp(&a.cost));
+    // FIFO on the spawning thread too, so that every worker takes the
+    // costliest job left
+    rayon::scope_fifo(|scope| {
+        for (i, job) in order {
+            let (results, on_done) = (&results, &on_done);
+            scope.spawn_fifo(move |_| {
+                let result = (job.run)();
+                on_done(&result);
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    results.into_iter().map(|r| r.into_inner().unwrap().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_run_costliest_first_and_return_in_order() {
+        let started = Mutex::new(Vec::new());
+        let costs = [1.0, 5.0, 3.0, 4.0, 2.0];
+        let jobs: Vec<Job<usize>> = costs
+            .iter()
+            .enumerate()
+            .map(|(i, &cost)| {
+                let started = &started;
+                Job::new(cost, move || {
+                    started.lock().unwrap().push(i);
+                    i
+                })
+                .map(|i| i * 10)
+            })
+            .collect();
+        let done = Mutex::new(0);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let results = pool.install(|| run_jobs(jobs, true, |_| *done.lock().unwrap() += 1));
+        assert_eq!(results, vec![0, 10, 20, 30, 40]);
+        assert_eq!(*done.lock().unwrap(), 5);
+        assert_eq!(*started.lock().unwrap(), vec![1, 3, 2, 4, 0]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Resumable and sharded benchmark output. Rows of the results and traces
+//! CSVs start with the cell they belong to, `test_set,dim,algorithm,function,seed`;
+//! a cell is complete once its results row is written, after its trace rows.
+//!
+//! A restarted benchmark appends to its files and skips complete cells. Rows of
+//! a cell cut off by the restart are dropped first, so nothing is duplicated.
+//! With `--shard i/n` a process runs the seeds `s` with `s % n == i - 1` into
+//! files of its own, which `merge` joins in a fixed order.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File, OpenOptions},
+    path::Path,
+};
+
+/// `(test_set, dim, algorithm, function, seed)` of one run.
+pub type Cell = (String, usize, String, String, u64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shard {
+    /// From 1 to `count`.
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Shard {
+    pub const ALL: Shard = Shard { index: 1, count: 1 };
+
+    /// Reads `i/n`.
+    pub fn parse(source: &str) -> Result<Shard, String> {
+        let error = || format!("invalid shard {source:?}, expected i/n with 1 <= i <= n");
+        let (index, count) = source.split_once('/').ok_or_else(error)?;
+        let (index, count) = (index.parse().map_err(|_| error())?, count.parse().map_err(|_| error())?);
+        if index == 0 || index > count {
+            return Err(error());
+        }
+        Ok(Shard { index, count })
+    }
+
+    pub fn contains(&self, seed: u64) -> bool {
+        seed % self.count == self.index - 1
+    }
+
+    /// Output file of this shard: `benchmark_results.csv` becomes
+    /// `benchmark_results.shard-2-of-4.csv`, unchanged for `ALL`.
+    pub fn path(&self, path: &str) -> String {
+        if *self == Shard::ALL {
+            return path.to_string();
+        }
+        let path = Path::new(path);
+        let stem = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+        let name = match path.extension() {
+            Some(extension) => format!("{stem}.shard-{}-of-{}.{}", self.index, self.count, extension.to_string_lossy()),
+            None => format!("{stem}.shard-{}-of-{}", self.index, self.count),
+        };
+        path.with_file_name(name).to_string_lossy().into_owned()
+    }
+}
+
+/// The cell a row belongs to and the fields after it.
+pub fn parse_cell(row: &str) -> Option<(Cell, Vec<&str>)> {
+    let fields: Vec<&str> = row.split(',').collect();
+    let [test_set, dim, algorithm, function, seed, rest @ ..] = fields.as_slice() else { return None };
+    let cell = (test_set.to_string(), dim.parse().ok()?, algorithm.to_string(), function.to_string(), seed.parse().ok()?);
+    Some((cell, rest.to_vec()))
+}
+
+/// The complete rows of a CSV with `header`, without a line cut off at the
+/// end; none when the file does not exist. Files from before columns were
+/// added at the end of `header` read with those columns empty.
+fn complete_rows(path: &str, header: &str) -> Result<Option<Vec<String>>, String> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("{path}: {e}")),
+    };
+    let complete = &source[..source.rfind('\n').map_or(0, |i| i + 1)];
+    let mut lines = complete.lines();
+    match lines.next() {
+        None => Ok(Some(Vec::new())),
+        Some(h) if header.strip_prefix(h).is_some_and(|added| added.is_empty() || added.starts_with(',')) => {
+            let padding = ",".repeat(header[h.len()..].matches(',').count());
+            Ok(Some(lines.filter(|l| parse_cell(l).is_some()).map(|l| format!("{l}{padding}")).collect()))
+        }
+        Some(h) => Err(format!("{path}: header {h:?} differs from {header:?}")),
+    }
+}
+
+fn write_rows<'a>(path: &str, header: &str, rows: impl Iterator<Item = &'a String>) -> Result<(), String> {
+    let mut source = format!("{header}\n");
+    for row in rows {
+        source.push_str(row);
+        source.push('\n');
+    }
+    fs::write(path, source).map_err(|e| format!("{path}: {e}"))
+}
+
+/// Opens `path` for appending rows under `header`, creating it if needed. Keeps
+/// the complete rows whose cell is in `keep`, or all of them; returns the file
+/// and the rows kept. The file is left as it is if `check` fails on a kept row.
+pub fn open_append<C>(path: &str, header: &str, keep: Option<&BTreeSet<Cell>>, check: C) -> Result<(File, Vec<String>), String>
+where
+    C: FnMut(&str) -> Result<(), String>,
+{
+    let rows = complete_rows(path, header)?.unwrap_or_default();
+    let kept: Vec<String> = rows
+        .into_iter()
+        .filter(|row| keep.is_none_or(|keep| parse_cell(row).is_some_and(|(cell, _)| keep.contains(&cell))))
+        .collect();
+    kept.iter().map(String::as_str).try_for_each(check)?;
+    // Rewritten every time, which also drops a partly written last row
+    write_rows(path, header, kept.iter())?;
+    let file = OpenOptions::new().append(true).open(path).map_err(|e| format!("{path}: {e}"))?;
+    Ok((file, kept))
+}
+
+/// Joins the rows of `inputs` into `output`, sorted by cell and then by the
+/// `extra_keys` integer columns after it; of rows with the same key the first
+/// is kept. Only cells in `keep` are written if given; missing inputs are
+/// skipped. Returns the cells written.
+pub fn merge(
+    inputs: &[String],
+    output: &str,
+    header: &str,
+    extra_keys: usize,
+    keep: Option<&BTreeSet<Cell>>,
+) -> Result<BTreeSet<Cell>, String> {
+    let mut rows: BTreeMap<(Cell, Vec<u64>), String> = BTreeMap::new();
+    for input in inputs {
+        for row in complete_rows(input, header)?.unwrap_or_default() {
+            let (cell, rest) = parse_cell(&row).unwrap();
+            if keep.is_some_and(|keep| !keep.contains(&cell)) {
+                continue;
+            }
+            let extra: Vec<u64> = rest
+                .iter()
+                .take(extra_keys)
+                .map(|v| v.parse().map_err(|_| format!("{input}: invalid value {v:?} in {row:?}")))
+                .collect::<Result<_, _>>()?;
+            rows.entry((cell, extra)).or_insert(row);
+        }
+    }
+    write_rows(output, header, rows.values())?;
+    Ok(rows.into_keys().map(|(cell, _)| cell).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const HEADER: &str = "test_set,dim,algorithm,function,seed,nfev";
+
+    #[test]
+    fn test_shard() {
+        let shard = Shard::parse("2/3").unwrap();
+        assert_eq!((0..7).filter(|&s| shard.contains(s)).collect::<Vec<_>>(), vec![1, 4]);
+        assert_eq!(shard.path("out/benchmark_results.csv"), "out/benchmark_results.shard-2-of-3.csv");
+        assert_eq!(Shard::ALL.path("benchmark_results.csv"), "benchmark_results.csv");
+        assert!(Shard::parse("0/3").is_err());
+        assert!(Shard::parse("4/3").is_err());
+        assert!(Shard::parse("1").is_err());
+    }
+
+    #[test]
+    fn test_append_and_merge() {
+        let dir = std::env::temp_dir().join(format!("shard_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        let cell = |seed: u64| ("s".to_string(), 2, "a".to_string(), "f".to_string(), seed);
+
+        // A run cut off in the middle of a row, with a row of an incomplete cell
+        fs::write(path("a.csv"), format!("{HEADER}\ns,2,a,f,2,10\ns,2,a,f,3,20\ns,2,a,f,0,5")).unwrap();
+        let keep = BTreeSet::from([cell(2)]);
+        let (mut file, rows) = open_append(&path("a.csv"), HEADER, Some(&keep), |_| Ok(())).unwrap();
+        assert_eq!(rows, vec!["s,2,a,f,2,10"]);
+        writeln!(file, "s,2,a,f,0,5").unwrap();
+        fs::write(path("b.csv"), format!("{HEADER}\ns,2,a,f,1,7\ns,2,a,f,0,9\n")).unwrap();
+
+        let inputs = [path("a.csv"), path("b.csv"), path("missing.csv")];
+        let cells = merge(&inputs, &path("merged.csv"), HEADER, 0, None).unwrap();
+        assert_eq!(cells, BTreeSet::from([cell(0), cell(1), cell(2)]));
+        let merged = fs::read_to_string(path("merged.csv")).unwrap();
+        assert_eq!(merged, format!("{HEADER}\ns,2,a,f,0,5\ns,2,a,f,1,7\ns,2,a,f,2,10\n"));
+
+        assert!(open_append(&path("a.csv"), "other", None, |_| Ok(())).is_err());
+        // Columns added since
+        let (_, rows) = open_append(&path("b.csv"), &format!("{HEADER},seconds"), None, |_| Ok(())).unwrap();
+        assert_eq!(rows, vec!["s,2,a,f,1,7,", "s,2,a,f,0,9,"]);
+        // Nothing is rewritten when a row does not check
+        let before = fs::read_to_string(path("merged.csv")).unwrap();
+        let check = |row: &str| if row.contains(",1,7") { Err(format!("invalid row {row:?}")) } else { Ok(()) };
+        assert_eq!(open_append(&path("merged.csv"), &format!("{HEADER},seconds"), None, check).unwrap_err(), "invalid row \"s,2,a,f,1,7,\"");
+        assert_eq!(fs::read_to_string(path("merged.csv")).unwrap(), before);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
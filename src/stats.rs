@@ -0,0 +1,444 @@
+//! Significance tests and confidence intervals for comparing algorithms on
+//! `runner::SeedResult`s.
+//!
+//! Runs are compared by a runtime score where every success beats every
+//! failure: successful runs score their `nfev`, failed runs score above all
+//! successes in the order of their final error (see `runtime_scores`).
+
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand_distr::{Distribution, Uniform};
+
+use crate::runner::SeedResult;
+
+/// Runtime scores of several groups of runs on one function, lower is
+/// better, on a common scale: `nfev` for runs that `solved` the function,
+/// and past the largest `nfev` of all groups, by final error, for the others.
+pub fn runtime_scores<S>(groups: &[&[SeedResult]], solved: S) -> Vec<Vec<f64>>
+where
+    S: Fn(&SeedResult) -> bool,
+{
+    let max_nfev = groups.iter().flat_map(|g| g.iter()).map(|r| r.nfev).max().unwrap_or(0) as f64;
+    groups
+        .iter()
+        .map(|g| {
+            g.iter()
+                .map(|r| if solved(r) { r.nfev as f64 } else { max_nfev + 1.0 + (r.error as f64).max(0.0) })
+                .collect()
+        })
+        .collect()
+}
+
+/// Ranks starting at 1, ties sharing their average rank; also returns the
+/// tie correction `sum(t^3 - t)` over groups of `t` tied values.
+fn average_ranks(values: &[f64]) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut ties = 0.0;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        let t = (end - start) as f64;
+        ties += t * t * t - t;
+        start = end;
+    }
+    (ranks, ties)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// U statistic of the first sample.
+    pub u: f64,
+    pub z: f64,
+    /// Two-sided p-value of the tie-corrected normal approximation.
+    pub p_value: f64,
+    /// Vargha–Delaney effect size of the first sample being smaller,
+    /// `P(a < b) + P(a = b) / 2`; 0.5 means no effect.
+    pub effect_size: f64,
+}
+
+/// Wilcoxon rank-sum / Mann–Whitney U test of `a` against `b`.
+pub fn mann_whitney(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let pooled: Vec<f64> = a.iter().chain(b).copied().collect();
+    let (ranks, ties) = average_ranks(&pooled);
+    let rank_sum: f64 = ranks[..a.len()].iter().sum();
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    // Continuity correction towards the mean
+    let delta = u - mean;
+    let z = if variance > 0.0 { (delta.abs() - 0.5).max(0.0).copysign(delta) / variance.sqrt() } else { 0.0 };
+    MannWhitney {
+        u,
+        z,
+        p_value: if variance > 0.0 { normal_two_sided(z) } else { 1.0 },
+        effect_size: if n1 * n2 > 0.0 { 1.0 - u / (n1 * n2) } else { 0.5 },
+    }
+}
+
+/// Two-sided tail probability of a standard normal.
+pub fn normal_two_sided(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function, with a relative error below 1.2e-7
+/// (Numerical Recipes, `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806, 0.27886807, -1.13520398, 1.48851587,
+        -0.82215223, 0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| c + t * acc);
+    let value = t * (-z * z + poly).exp();
+    if x >= 0.0 { value } else { 2.0 - value }
+}
+
+/// Natural logarithm of the gamma function for `x > 0` (Lanczos).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |acc, (i, c)| acc + c / (x + 1.0 + i as f64));
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x)`.
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = -x + a * x.ln() - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series for P(a, x)
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..500 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        1.0 - sum * log_prefix.exp()
+    } else {
+        // Continued fraction for Q(a, x), modified Lentz
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { 1.0 / tiny } else { 1.0 / d };
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        log_prefix.exp() * h
+    }
+}
+
+/// Upper tail probability of a chi-square distribution.
+pub fn chi_square_sf(x: f64, degrees_of_freedom: f64) -> f64 {
+    gamma_q(degrees_of_freedom / 2.0, x / 2.0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Friedman {
+    /// Mean rank of every treatment over the blocks, 1 being best.
+    pub mean_ranks: Vec<f64>,
+    pub chi_square: f64,
+    pub p_value: f64,
+    pub blocks: usize,
+}
+
+/// Friedman test of `k` treatments over blocks, `scores[block][treatment]`,
+/// lower scores being better.
+pub fn friedman(scores: &[Vec<f64>]) -> Friedman {
+    let k = scores.first().map_or(0, |row| row.len());
+    let n = scores.len() as f64;
+    let mut mean_ranks = vec![0.0; k];
+    for row in scores {
+        for (m, r) in mean_ranks.iter_mut().zip(average_ranks(row).0) {
+            *m += r / n;
+        }
+    }
+    let kf = k as f64;
+    let sum_squares: f64 = mean_ranks.iter().map(|r| r * r).sum();
+    let chi_square = 12.0 * n / (kf * (kf + 1.0)) * (sum_squares - kf * (kf + 1.0) * (kf + 1.0) / 4.0);
+    Friedman {
+        p_value: if k > 1 && n > 0.0 { chi_square_sf(chi_square, kf - 1.0) } else { 1.0 },
+        mean_ranks,
+        chi_square,
+        blocks: scores.len(),
+    }
+}
+
+/// Nemenyi critical difference of mean ranks at the 0.05 level for `k`
+/// treatments over `blocks` blocks (Demšar 2006); `None` beyond 10 treatments.
+pub fn nemenyi_critical_difference(k: usize, blocks: usize) -> Option<f64> {
+    // Studentized range quantiles divided by sqrt(2), for k = 2..10
+    const Q_05: [f64; 9] = [1.960, 2.343, 2.569, 2.728, 2.850, 2.949, 3.031, 3.102, 3.164];
+    let q = Q_05.get(k.checked_sub(2)?)?;
+    Some(q * (k as f64 * (k as f64 + 1.0) / (6.0 * blocks as f64)).sqrt())
+}
+
+/// Holm step-down adjustment of p-values, in the given order.
+pub fn holm(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+    let mut adjusted = vec![0.0; m];
+    let mut running = 0.0f64;
+    for (step, &i) in order.iter().enumerate() {
+        running = running.max(((m - step) as f64 * p_values[i]).min(1.0));
+        adjusted[i] = running;
+    }
+    adjusted
+}
+
+/// Holm-adjusted p-values of every treatment against the one with the best
+/// mean rank (1 for that one), from the post-hoc z statistics of the
+/// Friedman mean ranks.
+pub fn holm_against_best(friedman: &Friedman) -> Vec<f64> {
+    let k = friedman.mean_ranks.len() as f64;
+    let best = (0..friedman.mean_ranks.len())
+        .min_by(|&a, &b| friedman.mean_ranks[a].total_cmp(&friedman.mean_ranks[b]));
+    let Some(best) = best else { return Vec::new() };
+    let se = (k * (k + 1.0) / (6.0 * friedman.blocks as f64)).sqrt();
+    let others: Vec<usize> = (0..friedman.mean_ranks.len()).filter(|&i| i != best).collect();
+    let raw: Vec<f64> = others
+        .iter()
+        .map(|&i| normal_two_sided((friedman.mean_ranks[i] - friedman.mean_ranks[best]) / se))
+        .collect();
+    let mut adjusted = vec![1.0; friedman.mean_ranks.len()];
+    for (&i, p) in others.iter().zip(holm(&raw)) {
+        adjusted[i] = p;
+    }
+    adjusted
+}
+
+/// Friedman test of algorithms over functions, each algorithm scored on a
+/// function by its median runtime score. Only functions every algorithm ran
+/// count; the algorithms are in key order.
+pub fn friedman_over_functions<S>(results: &BTreeMap<String, Vec<SeedResult>>, solved: S) -> Friedman
+where
+    S: Fn(&SeedResult) -> bool,
+{
+    let mut functions: Vec<&String> = results.values().flatten().map(|r| &r.function).collect();
+    functions.sort();
+    functions.dedup();
+    let mut scores = Vec::new();
+    for function in functions {
+        let groups: Vec<Vec<SeedResult>> = results
+            .values()
+            .map(|runs| runs.iter().filter(|r| &r.function == function).cloned().collect())
+            .collect();
+        if groups.iter().any(|g| g.is_empty()) {
+            continue;
+        }
+        let slices: Vec<&[SeedResult]> = groups.iter().map(|g| g.as_slice()).collect();
+        scores.push(runtime_scores(&slices, &solved).iter().map(|s| median(s)).collect());
+    }
+    friedman(&scores)
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    percentile(values, 0.5)
+}
+
+fn percentile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+    if sorted[lo] == sorted[hi] {
+        sorted[lo]
+    } else {
+        sorted[lo] + (sorted[hi] - sorted[lo]) * (position - lo as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub estimate: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Percentile bootstrap interval of `statistic` at level `confidence`.
+pub fn bootstrap_interval<T, S, R>(samples: &[T], statistic: S, resamples: usize, confidence: f64, rng: &mut R) -> Interval
+where
+    T: Clone,
+    S: Fn(&[T]) -> f64,
+    R: Rng,
+{
+    let estimate = statistic(samples);
+    if samples.is_empty() {
+        return Interval { estimate, low: estimate, high: estimate };
+    }
+    let index = Uniform::new(0, samples.len()).unwrap();
+    let mut resample = samples.to_vec();
+    let values: Vec<f64> = (0..resamples)
+        .map(|_| {
+            for slot in resample.iter_mut() {
+                *slot = samples[index.sample(rng)].clone();
+            }
+            statistic(&resample)
+        })
+        .collect();
+    let alpha = (1.0 - confidence) / 2.0;
+    Interval {
+        estimate,
+        low: percentile(&values, alpha),
+        high: percentile(&values, 1.0 - alpha),
+    }
+}
+
+/// Bootstrap interval of the median `nfev` of successful runs, infinite
+/// when a resample has none.
+pub fn median_nfev_interval<S, R>(runs: &[SeedResult], solved: S, resamples: usize, confidence: f64, rng: &mut R) -> Interval
+where
+    S: Fn(&SeedResult) -> bool,
+    R: Rng,
+{
+    let outcomes: Vec<Option<f64>> = runs.iter().map(|r| solved(r).then_some(r.nfev as f64)).collect();
+    let statistic = |sample: &[Option<f64>]| {
+        let nfev: Vec<f64> = sample.iter().flatten().copied().collect();
+        if nfev.is_empty() { f64::INFINITY } else { median(&nfev) }
+    };
+    bootstrap_interval(&outcomes, statistic, resamples, confidence, rng)
+}
+
+/// Bootstrap interval of the share of successful runs.
+pub fn success_rate_interval<S, R>(runs: &[SeedResult], solved: S, resamples: usize, confidence: f64, rng: &mut R) -> Interval
+where
+    S: Fn(&SeedResult) -> bool,
+    R: Rng,
+{
+    let outcomes: Vec<f64> = runs.iter().map(|r| if solved(r) { 1.0 } else { 0.0 }).collect();
+    let statistic = |sample: &[f64]| sample.iter().sum::<f64>() / sample.len() as f64;
+    bootstrap_interval(&outcomes, statistic, resamples, confidence, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{stream_rng, DefaultRng};
+
+    fn seed(nfev: u64, error: f32) -> SeedResult {
+        SeedResult {
+            function: "f".to_string(),
+            seed: 0,
+            f_x: error,
+            nfev,
+            error,
+            distance: None,
+            trace: Vec::new(),
+            wall_seconds: 0.0,
+            objective_seconds: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_distributions() {
+        assert!((normal_two_sided(1.959964) - 0.05).abs() < 1e-6);
+        assert!((normal_two_sided(0.0) - 1.0).abs() < 1e-6);
+        // Two degrees of freedom: exp(-x / 2)
+        assert!((chi_square_sf(8.0, 2.0) - (-4.0f64).exp()).abs() < 1e-9);
+        assert!((chi_square_sf(3.841459, 1.0) - 0.05).abs() < 1e-6);
+        assert!((chi_square_sf(0.5, 5.0) - 0.992123).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mann_whitney() {
+        let a: Vec<f64> = (0..10).map(f64::from).collect();
+        let b: Vec<f64> = (10..20).map(f64::from).collect();
+        let test = mann_whitney(&a, &b);
+        assert_eq!(test.u, 0.0);
+        assert_eq!(test.effect_size, 1.0);
+        // Exact p is 1.1e-5; the normal approximation is close
+        assert!(test.p_value < 2e-4, "{}", test.p_value);
+        let same = mann_whitney(&a, &a);
+        assert_eq!(same.effect_size, 0.5);
+        assert!(same.p_value > 0.99);
+        assert_eq!(mann_whitney(&[1.0, 1.0], &[1.0]).p_value, 1.0);
+    }
+
+    #[test]
+    fn test_failures_score_after_successes() {
+        let solved = |r: &SeedResult| r.error <= 0.01;
+        let a = [seed(900, 0.0), seed(1000, 5.0)];
+        let b = [seed(100, 2.0), seed(500, 0.001)];
+        let scores = runtime_scores(&[&a, &b], solved);
+        assert_eq!(scores[0], vec![900.0, 1006.0]);
+        assert_eq!(scores[1], vec![1003.0, 500.0]);
+    }
+
+    #[test]
+    fn test_friedman_and_post_hoc() {
+        // Four blocks ranking three treatments the same way
+        let scores = vec![vec![1.0, 2.0, 3.0]; 4];
+        let test = friedman(&scores);
+        assert_eq!(test.mean_ranks, vec![1.0, 2.0, 3.0]);
+        assert!((test.chi_square - 8.0).abs() < 1e-9);
+        assert!((test.p_value - (-4.0f64).exp()).abs() < 1e-9);
+        let cd = nemenyi_critical_difference(3, 4).unwrap();
+        assert!((cd - 2.343 * 0.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(nemenyi_critical_difference(11, 4), None);
+        let adjusted = holm_against_best(&test);
+        assert_eq!(adjusted[0], 1.0);
+        assert!(adjusted[2] < adjusted[1]);
+        let holm_p = holm(&[0.01, 0.04, 0.03]);
+        let expected = [0.03, 0.06, 0.06];
+        assert!(holm_p.iter().zip(expected).all(|(p, e)| (p - e).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_bootstrap_intervals() {
+        let solved = |r: &SeedResult| r.error <= 0.01;
+        let runs: Vec<SeedResult> = (0..40).map(|i| seed(1000 + 10 * i, if i % 4 == 0 { 1.0 } else { 0.0 })).collect();
+        let mut rng = stream_rng::<DefaultRng>(3, 0);
+        let rate = success_rate_interval(&runs, solved, 2000, 0.95, &mut rng);
+        assert_eq!(rate.estimate, 0.75);
+        assert!(rate.low < 0.75 && 0.75 < rate.high && rate.low > 0.5);
+        let median = median_nfev_interval(&runs, solved, 2000, 0.95, &mut rng);
+        assert!(median.low <= median.estimate && median.estimate <= median.high);
+        assert!(median.low >= 1000.0 && median.high <= 1390.0);
+    }
+}
@@ -0,0 +1,184 @@
+//! Compact best-so-far traces of single runs, for convergence curves and
+//! anytime measures without the cost of `use_history`.
+//!
+//! A trace is a list of `(nfev, best_f)` pairs read as a step function: from
+//! `nfev` evaluations on, the best value seen is `best_f`, until the next pair.
+//! Points are taken on a logarithmic grid of evaluation counts and only when
+//! the best value changed since the previous point; the last evaluation is
+//! always recorded.
+//!
+//! The wrapper also times the objective, which splits the wall-clock of a run
+//! into objective time and the optimizer's own overhead.
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use simd_vector::Vec8;
+
+/// Grid points per factor of ten in evaluations.
+pub const SAMPLES_PER_DECADE: u32 = 10;
+
+/// Records the evaluations of a run, possibly from several threads. The count,
+/// the best value and the objective time are atomics; the lock on the grid is
+/// only taken by the evaluations that reach its next point, so recording costs
+/// the optimizer little more than the timing itself.
+pub struct TraceRecorder {
+    nfev: AtomicU64,
+    /// Bits of the best `f32` seen.
+    best: AtomicU32,
+    objective_nanos: AtomicU64,
+    /// The evaluation count of the next grid point, mirrored from `grid`.
+    next_sample: AtomicU64,
+    grid: Mutex<Grid>,
+}
+
+struct Grid {
+    samples_per_decade: u32,
+    index: u32,
+    next_sample: u64,
+    points: Vec<(u64, f32)>,
+}
+
+impl TraceRecorder {
+    pub fn new(samples_per_decade: u32) -> Self {
+        Self {
+            nfev: AtomicU64::new(0),
+            best: AtomicU32::new(f32::INFINITY.to_bits()),
+            objective_nanos: AtomicU64::new(0),
+            next_sample: AtomicU64::new(1),
+            grid: Mutex::new(Grid { samples_per_decade, index: 0, next_sample: 1, points: Vec::new() }),
+        }
+    }
+
+    /// Count one evaluation with value `f`.
+    pub fn record(&self, f: f32) {
+        let nfev = self.nfev.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut best = self.best.load(Ordering::Relaxed);
+        while f < f32::from_bits(best) {
+            match self.best.compare_exchange_weak(best, f.to_bits(), Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(current) => best = current,
+            }
+        }
+        if nfev >= self.next_sample.load(Ordering::Relaxed) {
+            let mut grid = self.grid.lock().unwrap();
+            // Another thread may have taken this point with a later count
+            if nfev >= grid.next_sample {
+                grid.push_if_changed(nfev, self.best());
+                grid.advance_past(nfev);
+                self.next_sample.store(grid.next_sample, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn best(&self) -> f32 {
+        f32::from_bits(self.best.load(Ordering::Relaxed))
+    }
+
+    /// Time spent in the objective by the evaluations recorded through `traced`.
+    pub fn objective_time(&self) -> Duration {
+        Duration::from_nanos(self.objective_nanos.load(Ordering::Relaxed))
+    }
+
+    /// The trace, ending with the last evaluation.
+    pub fn finish(self) -> Vec<(u64, f32)> {
+        let (nfev, best) = (self.nfev.into_inner(), f32::from_bits(self.best.into_inner()));
+        let mut points = self.grid.into_inner().unwrap().points;
+        if nfev > 0 && points.last().is_none_or(|&(n, _)| n != nfev) {
+            points.push((nfev, best));
+        }
+        points
+    }
+}
+
+impl Grid {
+    fn push_if_changed(&mut self, nfev: u64, best: f32) {
+        if self.points.last().is_none_or(|&(_, f)| f != best) {
+            self.points.push((nfev, best));
+        }
+    }
+
+    fn advance_past(&mut self, nfev: u64) {
+        while self.next_sample <= nfev {
+            self.index += 1;
+            let exponent = self.index as f64 / self.samples_per_decade as f64;
+            // Slightly below, so that powers of ten land on themselves
+            self.next_sample = (10f64.powf(exponent) - 1e-9).ceil() as u64;
+        }
+    }
+}
+
+/// `func` that also records every evaluation and its time in `recorder`.
+pub fn traced<'a, F>(func: &'a F, recorder: &'a TraceRecorder) -> impl Fn(&[Vec8]) -> f32 + Sync + 'a
+where
+    F: Fn(&[Vec8]) -> f32 + Sync,
+{
+    move |x: &[Vec8]| {
+        let start = Instant::now();
+        let f = func(x);
+        let elapsed = start.elapsed().as_nanos() as u64;
+        recorder.objective_nanos.fetch_add(elapsed, Ordering::Relaxed);
+        recorder.record(f);
+        f
+    }
+}
+
+/// Best value after `nfev` evaluations, infinity before the first point.
+pub fn best_at(trace: &[(u64, f32)], nfev: u64) -> f32 {
+    let count = trace.partition_point(|&(n, _)| n <= nfev);
+    if count == 0 { f32::INFINITY } else { trace[count - 1].1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_is_logarithmic_and_monotone() {
+        let recorder = TraceRecorder::new(SAMPLES_PER_DECADE);
+        // Improves at every evaluation
+        for i in 0..10_000 {
+            recorder.record(1.0 / (i + 1) as f32);
+        }
+        let trace = recorder.finish();
+        // 10 points per decade over 4 decades, plus the last evaluation
+        assert!(trace.len() <= 42, "{}", trace.len());
+        assert_eq!(trace[0], (1, 1.0));
+        assert_eq!(*trace.last().unwrap(), (10_000, 1.0 / 10_000.0));
+        assert!(trace.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 > w[1].1));
+        assert_eq!(best_at(&trace, 0), f32::INFINITY);
+        assert_eq!(best_at(&trace, 10), 0.1);
+        assert_eq!(best_at(&trace, 11), 0.1);
+    }
+
+    #[test]
+    fn test_trace_skips_unchanged_points() {
+        let recorder = TraceRecorder::new(SAMPLES_PER_DECADE);
+        for i in 0..1000 {
+            recorder.record(if i < 5 { 2.0 } else { 1.0 });
+        }
+        // The improvement at 6 falls on the grid (10^0.7 rounds up to 6)
+        assert_eq!(recorder.finish(), vec![(1, 2.0), (6, 1.0), (1000, 1.0)]);
+    }
+
+    #[test]
+    fn test_trace_from_threads() {
+        let recorder = TraceRecorder::new(SAMPLES_PER_DECADE);
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let recorder = &recorder;
+                scope.spawn(move || (0..2500).for_each(|i| recorder.record(1.0 / (4 * i + t + 1) as f32)));
+            }
+        });
+        let trace = recorder.finish();
+        assert_eq!(*trace.last().unwrap(), (10_000, 1.0 / 10_000.0));
+        // A point may see improvements of evaluations counted after it, so the
+        // last evaluation can repeat its value
+        assert!(trace.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 >= w[1].1));
+    }
+}
@@ -107,6 +107,50 @@ impl BoundsSimd {
     }
 }
 
+/// Pre-computed bounds for the candidate-per-lane layout used by
+/// `broadcast_simd_batch`. Lane `k` of output `d` holds coordinate `d` of
+/// candidate `k`, so any even dimension count is supported.
+pub struct BatchBoundsSimd {
+    mins: Vec<f32>,
+    ranges: Vec<f32>,
+}
+
+impl BatchBoundsSimd {
+    pub fn new(range_min: &[f32], range_max: &[f32]) -> Self {
+        let ranges = range_min
+            .iter()
+            .zip(range_max)
+            .map(|(min, max)| max - min)
+            .collect();
+        Self {
+            mins: range_min.to_vec(),
+            ranges,
+        }
+    }
+
+    #[inline]
+    pub fn output_len(&self) -> usize {
+        self.mins.len()
+    }
+
+    /// Transform up to eight candidates stored flat in `values`
+    /// (`count * dims` unit-cube coordinates) into lanes, writing into `out`.
+    /// Unused lanes repeat the last candidate so every lane stays finite.
+    /// `out` must have length >= `self.output_len()`.
+    #[inline]
+    pub fn transform_batch_into(&self, values: &[f32], count: usize, out: &mut [Vec8]) {
+        let dims = self.mins.len();
+        let out = &mut out[..dims];
+        for d in 0..dims {
+            let mut lanes = [0.0f32; 8];
+            for (k, lane) in lanes.iter_mut().enumerate() {
+                *lane = values[k.min(count - 1) * dims + d];
+            }
+            out[d] = Vec8(lanes).mul_add(Vec8::splat(self.ranges[d]), Vec8::splat(self.mins[d]));
+        }
+    }
+}
+
 pub fn fit_in_bounds_simd(values: &[f32], range_min: &[f32], range_max: &[f32]) -> Vec<Vec8> {
     let len = values.len();
     let groups = len / 16;
@@ -187,6 +231,43 @@ pub fn fit_in_bounds_simd(values: &[f32], range_min: &[f32], range_max: &[f32])
     out
 }
 
+/// Inverse of the pair layout of `fit_in_bounds_simd`: write the coordinates of
+/// `x` to `out` in dimension order.
+pub fn unpack_pair_layout(x: &[Vec8], out: &mut [f32]) {
+    for (g, pair) in x.chunks_exact(2).enumerate() {
+        let base = g * 16;
+        for j in 0..8 {
+            out[base + 2 * j] = pair[0][j];
+            out[base + 2 * j + 1] = pair[1][j];
+        }
+    }
+}
+
+/// Pack coordinates in dimension order into the pair layout of
+/// `fit_in_bounds_simd`, without rescaling.
+pub fn pack_pair_layout(values: &[f32]) -> Vec<Vec8> {
+    let mut out = Vec::with_capacity(values.len() / 8);
+    for group in values.chunks_exact(16) {
+        let mut evens = Vec8::ZERO;
+        let mut odds = Vec8::ZERO;
+        for j in 0..8 {
+            evens.0[j] = group[2 * j];
+            odds.0[j] = group[2 * j + 1];
+        }
+        out.push(evens);
+        out.push(odds);
+    }
+    out
+}
+
+/// Split `[min, max]` pairs into separate lower and upper bound vectors.
+pub fn split_bounds(bounds: &[[f32; 2]]) -> (Vec<f32>, Vec<f32>) {
+    (
+        bounds.iter().map(|b| b[0]).collect(),
+        bounds.iter().map(|b| b[1]).collect(),
+    )
+}
+
 pub fn fit_in_bounds(values: &[f32], range_min: &[f32], range_max: &[f32]) -> Vec<f32> {
     let values_len = values.len();
     let mut result = vec![0.0; values_len];
@@ -253,6 +334,21 @@ where
     }
 }
 
+/// Candidate-per-lane counterpart of `broadcast_simd`: `x[d]` holds coordinate
+/// `d` of eight candidates, and lane `k` of the result is the value for candidate `k`.
+pub fn broadcast_simd_batch<F>(func: F) -> impl Fn(&[Vec8]) -> Vec8 + Sync
+where
+    F: Fn(Vec8, Vec8) -> Vec8 + Sync,
+{
+    move |x: &[Vec8]| -> Vec8 {
+        let inv = 2.0 / x.len() as f32;
+        x.chunks_exact(2)
+            .map(|pair| func(pair[0], pair[1]))
+            .sum::<Vec8>()
+            * inv
+    }
+}
+
 pub fn broadcast_scalar<F>(func: F) -> impl Fn(&[f32]) -> f32 + Sync
 where
     F: Fn(Vec8, Vec8) -> Vec8 + Sync,
@@ -316,6 +412,19 @@ pub fn mean_and_mad(values: &[f32]) -> (f32, f32) {
     (mean, mad)
 }
 
+/// Quantile `q` in `[0, 1]` of `values`, interpolating linearly between the
+/// closest ranks; NaN if `values` is empty.
+pub fn quantile(values: &[f32], q: f32) -> f32 {
+    if values.is_empty() {
+        return f32::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f32;
+    let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (position - lo as f32)
+}
+
 #[derive(Debug, PartialEq)]
 pub struct GroupSummary {
     pub best: f32,
@@ -649,6 +758,16 @@ mod tests {
         assert!(out.is_empty());
     }
 
+    #[test]
+    fn test_unpack_pair_layout_roundtrip() {
+        let values: Vec<f32> = (0..32).map(|i| i as f32).collect();
+        let packed = fit_in_bounds_simd(&values, &[0.0; 32], &[1.0; 32]);
+        let mut out = vec![0.0; 32];
+        unpack_pair_layout(&packed, &mut out);
+        assert_eq!(out, values);
+        assert_eq!(pack_pair_layout(&values), packed);
+    }
+
     #[test]
     fn test_bounds_simd_new_and_output_len() {
         let mins = vec![0.0_f32; 16];
@@ -721,6 +840,15 @@ mod tests {
         assert!(almost_equal(mad, 0.0, 1e-6));
     }
 
+    #[test]
+    fn test_quantile() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+        assert!(almost_equal(quantile(&values, 0.0), 1.0, 1e-6));
+        assert!(almost_equal(quantile(&values, 0.5), 2.5, 1e-6));
+        assert!(almost_equal(quantile(&values, 1.0), 4.0, 1e-6));
+        assert!(quantile(&[], 0.5).is_nan());
+    }
+
     #[test]
     fn test_summarize_group_normal() {
         let s = summarize_group(&[1.0, 3.0, 5.0]);
@@ -774,4 +902,52 @@ mod tests {
         let key_2 = f32_to_i64(2.0);
         assert_eq!(grouped[&key_2].len(), 1);
     }
+
+    #[test]
+    fn test_broadcast_simd_batch_per_lane() {
+        let f = broadcast_simd_batch(|a, b| a + b);
+        let x0 = Vec8([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        let x1 = Vec8([1.0; 8]);
+        let x2 = Vec8([0.0; 8]);
+        let x3 = Vec8([2.0; 8]);
+        let res = f(&[x0, x1, x2, x3]);
+        assert_eq!(res, Vec8([2.0, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0, 5.5]));
+    }
+
+    #[test]
+    fn test_broadcast_simd_batch_matches_broadcast_simd() {
+        let single = broadcast_simd(|a, b| a * a + b);
+        let batch = broadcast_simd_batch(|a, b| a * a + b);
+        let values: Vec<f32> = (0..16).map(|i| i as f32 * 0.25).collect();
+        let mins = vec![0.0_f32; 16];
+        let maxs = vec![1.0_f32; 16];
+        let expected = single(&fit_in_bounds_simd(&values, &mins, &maxs));
+        let bs = BatchBoundsSimd::new(&mins, &maxs);
+        let mut out = vec![Vec8::ZERO; bs.output_len()];
+        bs.transform_batch_into(&values, 1, &mut out);
+        let res = batch(&out);
+        for k in 0..8 {
+            assert!(almost_equal(res[k], expected, 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_batch_bounds_simd_lanes() {
+        let mins = vec![0.0_f32, -1.0];
+        let maxs = vec![10.0_f32, 1.0];
+        let bs = BatchBoundsSimd::new(&mins, &maxs);
+        assert_eq!(bs.output_len(), 2);
+        let values = vec![0.0, 0.0, 0.5, 0.5, 1.0, 1.0];
+        let mut out = vec![Vec8::ZERO; bs.output_len()];
+        bs.transform_batch_into(&values, 3, &mut out);
+        assert_eq!(out[0], Vec8([0.0, 5.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0]));
+        assert_eq!(out[1], Vec8([-1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_split_bounds() {
+        let (mins, maxs) = split_bounds(&[[-1.0, 1.0], [2.0, 3.0]]);
+        assert_eq!(mins, vec![-1.0, 2.0]);
+        assert_eq!(maxs, vec![1.0, 3.0]);
+    }
 }
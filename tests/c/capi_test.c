@@ -0,0 +1,118 @@
+/* Exercises the C API; built and run by tests/capi.rs. */
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "optimizers.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                          \
+  do {                                                                       \
+    if (!(cond)) {                                                           \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+      failures++;                                                            \
+    }                                                                        \
+  } while (0)
+
+/* Shifted sphere; user_data holds the shift and counts calls. */
+typedef struct {
+  float shift;
+  unsigned long calls;
+} Sphere;
+
+static float sphere(const float *x, size_t dims, void *user_data) {
+  Sphere *s = (Sphere *)user_data;
+  float sum = 0.0f;
+  for (size_t i = 0; i < dims; i++) {
+    float d = x[i] - s->shift;
+    sum += d * d;
+  }
+  s->calls++;
+  return sum;
+}
+
+static float fails_everywhere(const float *x, size_t dims, void *user_data) {
+  (void)x;
+  (void)dims;
+  (void)user_data;
+  return NAN;
+}
+
+static void minimizes(const char *name) {
+  enum { DIMS = 10 };
+  float bounds[2 * DIMS];
+  for (int i = 0; i < DIMS; i++) {
+    bounds[2 * i] = -5.0f;
+    bounds[2 * i + 1] = 5.0f;
+  }
+  OptOptimizer *optimizer = opt_optimizer_new(name, NULL, NULL, 0);
+  CHECK(optimizer != NULL);
+  if (optimizer == NULL) {
+    return;
+  }
+  Sphere s = {1.5f, 0};
+  float x[DIMS];
+  OptResult result;
+  int status = opt_find_infimum(optimizer, sphere, &s, bounds, DIMS, 50000, 1,
+                                1e-4f, x, &result);
+  CHECK(status == OPT_OK);
+  CHECK(result.f_x <= 1e-4f);
+  CHECK(result.nfev == s.calls);
+  CHECK(fabsf(sphere(x, DIMS, &s) - result.f_x) < 1e-6f);
+  for (int i = 0; i < DIMS; i++) {
+    CHECK(fabsf(x[i] - 1.5f) < 0.01f);
+  }
+  printf("%s: f_x = %g after %llu evaluations\n", name, result.f_x,
+         (unsigned long long)result.nfev);
+  opt_optimizer_free(optimizer);
+}
+
+static void reports_errors(void) {
+  CHECK(opt_optimizer_new("nelder_mead", NULL, NULL, 0) == NULL);
+  CHECK(strstr(opt_last_error(), "unknown algorithm") != NULL);
+
+  const char *names[] = {"popsize"};
+  const float values[] = {32.0f};
+  CHECK(opt_optimizer_new("ansr", names, values, 1) == NULL);
+  CHECK(strstr(opt_last_error(), "missing parameters") != NULL);
+
+  const char *de_names[] = {"popsize", "f", "cr"};
+  const float de_values[] = {2.0f, 0.5f, 0.5f};
+  CHECK(opt_optimizer_new("de", de_names, de_values, 3) == NULL);
+  CHECK(strstr(opt_last_error(), "popsize") != NULL);
+
+  const char *shade_names[] = {"popsize", "h", "p_best_rate"};
+  const float shade_values[] = {32.0f, 10.0f, 0.1f};
+  OptOptimizer *optimizer = opt_optimizer_new("shade", shade_names, shade_values, 3);
+  if (optimizer == NULL) {
+    fprintf(stderr, "shade with parameters: %s\n", opt_last_error());
+  }
+  CHECK(optimizer != NULL);
+
+  float bounds[] = {1.0f, -1.0f};
+  float x[1];
+  OptResult result;
+  CHECK(opt_find_infimum(optimizer, sphere, NULL, bounds, 1, 100, 0, -INFINITY,
+                         x, &result) == OPT_ERROR);
+  CHECK(strstr(opt_last_error(), "invalid bounds") != NULL);
+
+  bounds[0] = -1.0f;
+  bounds[1] = 1.0f;
+  CHECK(opt_find_infimum(optimizer, fails_everywhere, NULL, bounds, 1, 100, 0,
+                         -INFINITY, x, &result) == OPT_OK);
+  CHECK(isinf(result.f_x));
+  opt_optimizer_free(optimizer);
+}
+
+int main(void) {
+  CHECK(opt_api_version() == OPT_API_VERSION);
+  minimizes("ansr");
+  minimizes("shade");
+  reports_errors();
+  if (failures > 0) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  return 0;
+}
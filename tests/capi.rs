@@ -0,0 +1,36 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+#[test]
+fn test_header_is_up_to_date() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let checked_in = fs::read_to_string(root.join("include/optimizers.h")).unwrap();
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/optimizers.h"));
+    assert!(checked_in == generated, "include/optimizers.h is stale, rebuild with OPTIMIZERS_WRITE_HEADER=1");
+}
+
+// Builds the C harness in tests/c against the cdylib and the generated header.
+#[test]
+fn test_c_harness() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // target/<profile>/deps/capi-<hash> -> target/<profile>
+    let lib_dir = env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let out = lib_dir.join("capi_test");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let built = Command::new(&cc)
+        .arg(root.join("tests/c/capi_test.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .args(["-loptimizers", "-lm", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&out)
+        .status();
+    let Ok(built) = built else {
+        eprintln!("No C compiler `{cc}`, skipping");
+        return;
+    };
+    assert!(built.success());
+    let output = Command::new(&out).env("LD_LIBRARY_PATH", &lib_dir).output().unwrap();
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use optimizers::{
+    default_algorithms_params::{DEFAULT_ANSR, DEFAULT_DE},
+    external::{ExternalConfig, ExternalError, ExternalObjective},
+    optimizer::{ExternalOptimizer, ObjectiveError},
+};
+
+fn stub(args: &[&str]) -> Vec<String> {
+    let mut command = vec![env!("CARGO_BIN_EXE_stub_evaluator").to_string()];
+    command.extend(args.iter().map(|s| s.to_string()));
+    command
+}
+
+const CONFIG: ExternalConfig = ExternalConfig {
+    timeout: Duration::from_secs(10),
+    max_restarts: 3,
+};
+
+#[test]
+fn test_batches_are_evaluated_in_order() {
+    let mut objective = ExternalObjective::new(&stub(&["sum(i*x_i)"]), 2, CONFIG).unwrap();
+    let values = objective.evaluate_batch(&[1.0, 0.0, 0.0, 1.0, 0.5, 0.25]).unwrap();
+    assert_eq!(values, vec![1.0, 2.0, 1.0]);
+    assert_eq!(objective.evaluate(&[3.0, 1.0]).unwrap(), 5.0);
+    assert_eq!(objective.calls(), 4);
+}
+
+#[test]
+fn test_optimizers_minimize_external_objective() {
+    let bounds = vec![[-5.0, 5.0]; 16];
+    let mut objective = ExternalObjective::new(&stub(&[]), 16, CONFIG).unwrap();
+    let result = DEFAULT_ANSR.find_infimum_external(&mut objective, &bounds, 20_000, 1, false, 0.01);
+    assert!(result.error.is_none());
+    assert!(result.result.f_x <= 0.01, "{}", result.result.f_x);
+    assert_eq!(result.result.nfev, objective.calls());
+    let result = DEFAULT_DE.find_infimum_external(&mut objective, &bounds, 5_000, 1, false, 0.01);
+    assert!(result.error.is_none());
+    assert!(result.result.f_x.is_finite());
+}
+
+#[test]
+fn test_crashed_evaluator_is_restarted() {
+    let bounds = vec![[-5.0, 5.0]; 16];
+    let mut objective = ExternalObjective::new(&stub(&["--crash-after", "3"]), 16, CONFIG).unwrap();
+    let result = DEFAULT_ANSR.find_infimum_external(&mut objective, &bounds, 5_000, 1, false, 0.0);
+    assert!(result.error.is_none());
+    assert!(objective.restarts() > 0);
+    // Re-sent batches count as calls
+    assert!(result.result.nfev > 5_000);
+}
+
+#[test]
+fn test_hanging_evaluator_times_out() {
+    let config = ExternalConfig {
+        timeout: Duration::from_millis(200),
+        max_restarts: 1,
+    };
+    let mut objective = ExternalObjective::new(&stub(&["--hang-after", "1"]), 2, config).unwrap();
+    assert!(objective.evaluate(&[1.0, 1.0]).is_ok());
+    // The restarted evaluator answers its first batch
+    assert_eq!(objective.evaluate(&[1.0, 3.0]).unwrap(), 5.0);
+    assert_eq!(objective.restarts(), 1);
+}
+
+#[test]
+fn test_unrecoverable_evaluator_aborts_the_run() {
+    let bounds = vec![[-5.0, 5.0]; 16];
+    let mut objective = ExternalObjective::new(&stub(&["--crash-after", "0"]), 16, CONFIG).unwrap();
+    let result = DEFAULT_ANSR.find_infimum_external(&mut objective, &bounds, 5_000, 1, false, 0.0);
+    assert_eq!(result.error, Some(ObjectiveError::Failed(ExternalError::Crashed)));
+    assert_eq!(objective.restarts(), 3);
+}
+
+#[test]
+fn test_nan_replies_are_failures() {
+    let bounds = vec![[-5.0, 5.0]; 16];
+    let mut objective = ExternalObjective::new(&stub(&["--nan-above", "5"]), 16, CONFIG).unwrap();
+    let result = DEFAULT_ANSR.find_infimum_external(&mut objective, &bounds, 2_000, 1, false, 0.0);
+    assert!(result.error.is_none());
+    assert!(result.failures > 0);
+    assert!(result.result.f_x <= 5.0);
+}
+
+#[test]
+fn test_missing_command_fails_to_spawn() {
+    let result = ExternalObjective::new(&["/nonexistent/evaluator".to_string()], 16, CONFIG);
+    assert!(matches!(result, Err(ExternalError::Spawn(_))));
+}
+
+#[test]
+fn test_dims_are_checked() {
+    let result = ExternalObjective::new(&stub(&[]), 0, CONFIG);
+    assert!(matches!(result, Err(ExternalError::Dims { dims: 0, .. })));
+    let mut objective = ExternalObjective::new(&stub(&[]), 2, CONFIG).unwrap();
+    assert_eq!(objective.evaluate_batch(&[1.0, 2.0, 3.0]), Err(ExternalError::Dims { dims: 2, coordinates: 3 }));
+    assert_eq!(objective.evaluate(&[1.0]), Err(ExternalError::Dims { dims: 2, coordinates: 1 }));
+    let result = DEFAULT_DE.find_infimum_external(&mut objective, &[[-5.0, 5.0]; 4], 1_000, 1, false, 0.0);
+    assert_eq!(result.error, Some(ObjectiveError::Failed(ExternalError::Dims { dims: 2, coordinates: 4 })));
+    assert_eq!(objective.calls(), 0);
+}
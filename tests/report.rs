@@ -0,0 +1,27 @@
+use std::{fs, process::Command};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/report");
+
+#[test]
+fn test_report_reproduces_fixture() {
+    let dir = std::env::temp_dir().join(format!("report_fixture_{}", std::process::id()));
+    let (latex, markdown) = (dir.join("latex"), dir.join("tables.md"));
+    let status = Command::new(env!("CARGO_BIN_EXE_report"))
+        .arg(format!("{FIXTURE}/results.csv"))
+        .arg("--latex")
+        .arg(&latex)
+        .arg("--markdown")
+        .arg(&markdown)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&markdown).unwrap(), fs::read_to_string(format!("{FIXTURE}/tables.md")).unwrap());
+    let mut names: Vec<String> = fs::read_dir(&latex).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    names.sort();
+    assert_eq!(names, ["hard_discrete_16d.tex", "medium_terrain_128d.tex", "medium_terrain_64d.tex"]);
+    for name in names {
+        let expected = fs::read_to_string(format!("{FIXTURE}/latex/{name}")).unwrap();
+        assert_eq!(fs::read_to_string(latex.join(&name)).unwrap(), expected, "{name}");
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
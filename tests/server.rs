@@ -0,0 +1,66 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+use optimizers::json::Json;
+
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn connect(socket: &std::path::Path) -> UnixStream {
+    for _ in 0..100 {
+        if let Ok(stream) = UnixStream::connect(socket) {
+            return stream;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!("server did not start");
+}
+
+fn request(stream: &mut UnixStream, line: &str) -> Json {
+    writeln!(stream, "{line}").unwrap();
+    let mut response = String::new();
+    BufReader::new(stream.try_clone().unwrap()).read_line(&mut response).unwrap();
+    Json::parse(&response).unwrap()
+}
+
+#[test]
+fn test_server_answers_over_unix_socket() {
+    let dir = std::env::temp_dir().join(format!("server_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let socket = dir.with_extension("sock");
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_server"))
+            .args(["--unix", socket.to_str().unwrap(), "--dir", dir.to_str().unwrap()])
+            .spawn()
+            .unwrap(),
+    );
+    let mut stream = connect(&socket);
+
+    let created = request(
+        &mut stream,
+        r#"{"op":"create","study":"s","algorithm":"de","bounds":[[-1,1],[-1,1]],"maxiter":100}"#,
+    );
+    assert_eq!(created.get("ok"), Some(&Json::Bool(true)), "{created}");
+    let asked = request(&mut stream, r#"{"op":"ask","study":"s","count":2}"#);
+    let candidates = asked.get("candidates").unwrap().as_array().unwrap();
+    assert_eq!(candidates.len(), 2);
+    let id = candidates[0].get("id").unwrap().as_u64().unwrap();
+    let told = request(&mut stream, &format!(r#"{{"op":"tell","study":"s","results":[{{"id":{id},"value":0.5}}]}}"#));
+    assert_eq!(told.get("told"), Some(&Json::Number(1.0)), "{told}");
+    let best = request(&mut stream, r#"{"op":"best","study":"s"}"#);
+    assert_eq!(best.get("value"), Some(&Json::Number(0.5)));
+    assert!(dir.join("s.json").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}